@@ -1,7 +1,14 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::holdings::{YahooProvider, QUOTE_CACHE_MINUTES};
 use crate::{
+    asset::Allocations,
     calc,
-    holdings::{parse_csv_download, ShareValues, StockSymbol, VanguardHoldings, VanguardRebalance},
+    holdings::{
+        parse_csv_download, parse_ofx_download, AccountHoldings, CashPercents, HoldingType,
+        ParseWarning, RoundingMode, ShareValues, StockSymbol, VanguardHoldings, VanguardRebalance,
+    },
 };
+use anyhow::Result;
 #[cfg(not(target_arch = "wasm32"))]
 use apca::{api::v2::account, ApiInfo, Client};
 use chrono::{Datelike, Local};
@@ -16,6 +23,44 @@ use std::{
 
 type ProfileName = String;
 
+// Loaded holdings which are persisted with the app state but are not part of exported profiles
+const HOLDINGS_FIELDS: [&str; 5] = [
+    "roth_holdings",
+    "hsa_holdings",
+    "traditional_holdings",
+    "brokerage_holdings",
+    "vanguard_holdings",
+];
+
+// Account numbers of a profile, where state saved before multiple accounts of the same type were
+// supported holds a single account number
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum AccountNums {
+    Single(u32),
+    Multiple(Vec<u32>),
+}
+
+// Deserializes the account numbers of each profile from either a single account number or a list
+// of account numbers
+fn deserialize_account_nums<'de, D>(
+    deserializer: D,
+) -> std::result::Result<HashMap<ProfileName, Vec<u32>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let account_nums: HashMap<ProfileName, AccountNums> =
+        serde::Deserialize::deserialize(deserializer)?;
+    Ok(account_nums
+        .into_iter()
+        .map(|(profile_name, account_nums)| match account_nums {
+            AccountNums::Single(0) => (profile_name, Vec::new()),
+            AccountNums::Single(account_num) => (profile_name, vec![account_num]),
+            AccountNums::Multiple(account_nums) => (profile_name, account_nums),
+        })
+        .collect())
+}
+
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)] // if we add new fields, give them default values when deserializing old state
@@ -24,10 +69,16 @@ pub struct VaporeApp {
     birth_year: HashMap<ProfileName, u32>, // Profile name: year
     retirement_year: HashMap<ProfileName, i32>, // Profile name: year
     brokerage_stock: HashMap<ProfileName, u32>, // Profile name: brokerage stock percent
-    brokerage_account_num: HashMap<ProfileName, u32>, // Profile name: brokerage account number
-    roth_account_num: HashMap<ProfileName, u32>, // Profile name: Roth account number
-    trad_account_num: HashMap<ProfileName, u32>, // Profile name: Traditional IRA account number
-    distribution_table: HashMap<u32, f32>, // Age: divider from IRS' distribution table
+    #[serde(deserialize_with = "deserialize_account_nums")]
+    brokerage_account_num: HashMap<ProfileName, Vec<u32>>, // Profile name: brokerage account numbers
+    #[serde(deserialize_with = "deserialize_account_nums")]
+    roth_account_num: HashMap<ProfileName, Vec<u32>>, // Profile name: Roth account numbers
+    #[serde(deserialize_with = "deserialize_account_nums")]
+    trad_account_num: HashMap<ProfileName, Vec<u32>>, // Profile name: Traditional IRA account numbers
+    #[serde(deserialize_with = "deserialize_account_nums")]
+    hsa_account_num: HashMap<ProfileName, Vec<u32>>, // Profile name: HSA account numbers
+    account_types: HashMap<u32, HoldingType>, // Account number: guessed or selected account type
+    distribution_table: HashMap<u32, f32>,    // Age: divider from IRS' distribution table
     #[serde(skip)]
     distribution_needed: String,
     #[cfg(not(target_arch = "wasm32"))]
@@ -45,7 +96,6 @@ pub struct VaporeApp {
     brokerage_us_bond_add: f32, // Bond add unused at this time
     #[serde(skip)]
     brokerage_int_bond_add: f32, // Bond add unused at this time
-    #[serde(skip)]
     roth_holdings: ShareValues, // Roth value holdings found from account number
     #[serde(skip)]
     roth_us_stock_add: f32, // Stock add unused at this time
@@ -57,7 +107,6 @@ pub struct VaporeApp {
     roth_int_bond_add: f32, // Bond add unused at this time
     #[serde(skip)]
     roth_cash_add: i32, // Cash added or subtracted from Roth account
-    #[serde(skip)]
     traditional_holdings: ShareValues, // Traditional stock values found with the account number
     #[serde(skip)]
     traditional_us_stock_add: f32, // Stock add unused at this time
@@ -69,13 +118,27 @@ pub struct VaporeApp {
     traditional_int_bond_add: f32, // Bond add unused at this time
     #[serde(skip)]
     traditional_cash_add: i32, // Cash to add or subtract from the Traditional IRA
-    use_brokerage_retirement: bool, // Whether to use the brokerage as the same allocation as retirement
+    hsa_holdings: ShareValues,  // HSA stock values found with the account number
     #[serde(skip)]
+    hsa_cash_add: i32, // Cash to add or subtract from the HSA
+    use_brokerage_retirement: bool, // Whether to use the brokerage as the same allocation as retirement
+    tax_aware_brokerage: bool, // Whether the brokerage shares the retirement allocation with tax efficient placement
+    rebalance_threshold: f32,  // Dollar value below which purchases/sales are dropped
+    rounding_mode: RoundingMode, // How the shares to buy and sell are rounded
+    cash_percents: CashPercents, // Percent of each account kept within cash
+    placement_preferences: HashMap<StockSymbol, HoldingType>, // Account each symbol is preferred within
     brokerage_holdings: ShareValues, // Brokerage holdings found from the account number
     #[serde(skip)]
     rebalance: VanguardRebalance, // Targets and purchases/sales needed to rebalance
     #[serde(skip)]
+    feasibility_message: String, // Why the rebalance can not be calculated with the inputs
+    #[serde(skip)]
+    profiles_message: String, // Why the profiles could not be exported or imported
     vanguard_holdings: Arc<Mutex<VanguardHoldings>>,
+    #[serde(skip)]
+    parse_warnings: Arc<Mutex<Vec<ParseWarning>>>, // Rows skipped while importing the Vanguard file
+    #[serde(skip)]
+    import_message: Arc<Mutex<String>>, // Why the Vanguard file could not be imported
 }
 
 impl Default for VaporeApp {
@@ -88,6 +151,8 @@ impl Default for VaporeApp {
             brokerage_account_num: HashMap::new(),
             roth_account_num: HashMap::new(),
             trad_account_num: HashMap::new(),
+            hsa_account_num: HashMap::new(),
+            account_types: HashMap::new(),
             distribution_table: HashMap::new(),
             distribution_needed: "Load distribution table for results. 1 year of VAPORE use needed.".to_string(),
             #[cfg(not(target_arch = "wasm32"))]
@@ -110,10 +175,21 @@ impl Default for VaporeApp {
             traditional_int_stock_add: 0.0,
             traditional_int_bond_add: 0.0,
             traditional_cash_add: 0,
+            hsa_holdings: ShareValues::new(),
+            hsa_cash_add: 0,
             use_brokerage_retirement: false,
+            tax_aware_brokerage: false,
+            rebalance_threshold: 0.0,
+            rounding_mode: RoundingMode::default(),
+            cash_percents: CashPercents::default(),
+            placement_preferences: HashMap::new(),
             brokerage_holdings: ShareValues::new(),
             rebalance: VanguardRebalance::default(),
+            feasibility_message: String::new(),
+            profiles_message: String::new(),
             vanguard_holdings: Arc::new(Mutex::new(VanguardHoldings::default())),
+            parse_warnings: Arc::new(Mutex::new(Vec::new())),
+            import_message: Arc::new(Mutex::new(String::new())),
         }
     }
 }
@@ -127,6 +203,152 @@ impl VaporeApp {
 
         Default::default()
     }
+
+    /// export_profiles returns the persisted app state, which includes all profiles, as a JSON
+    /// string so it can be backed up or moved to another machine.  The loaded Vanguard holdings
+    /// are left out.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the app state cannot be serialized into JSON, such as a map whose keys are not
+    /// strings or numbers.  The GUI shows the error within the profiles message instead of
+    /// panicking.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let json = r#"{"profile_name":"Jane","birth_year":{"Jane":1980,"John":1975},"retirement_year":{"Jane":2045,"John":2040}}"#;
+    ///
+    /// let mut app = vapore_gui::VaporeApp::default();
+    /// app.import_profiles(json).unwrap();
+    /// let exported = app.export_profiles().unwrap();
+    ///
+    /// let mut new_app = vapore_gui::VaporeApp::default();
+    /// new_app.import_profiles(&exported).unwrap();
+    /// let exported_value: serde_json::Value = serde_json::from_str(&exported).unwrap();
+    /// let new_exported_value: serde_json::Value =
+    ///     serde_json::from_str(&new_app.export_profiles().unwrap()).unwrap();
+    /// assert_eq!(exported_value, new_exported_value);
+    /// assert_eq!(exported_value["birth_year"]["John"], 1975);
+    /// assert!(exported_value.get("vanguard_holdings").is_none());
+    /// ```
+    pub fn export_profiles(&self) -> Result<String> {
+        let mut profiles = serde_json::to_value(self)?;
+        if let Some(state) = profiles.as_object_mut() {
+            for holdings_field in HOLDINGS_FIELDS {
+                state.remove(holdings_field);
+            }
+        }
+        Ok(serde_json::to_string_pretty(&profiles)?)
+    }
+
+    /// import_profiles replaces the persisted app state, which includes all profiles, with the
+    /// state from a JSON string created by export_profiles.  Loaded Vanguard holdings are kept.
+    /// Profiles exported with a single account number for each account type are also accepted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let json = r#"{"profile_name":"Jane","trad_account_num":{"Jane":123},"roth_account_num":{"Jane":[456,789]},"placement_preferences":{"VTIP":"TraditionalIra"}}"#;
+    ///
+    /// let mut app = vapore_gui::VaporeApp::default();
+    /// app.import_profiles(json).unwrap();
+    /// let exported: serde_json::Value = serde_json::from_str(&app.export_profiles().unwrap()).unwrap();
+    /// assert_eq!(exported["trad_account_num"]["Jane"], serde_json::json!([123]));
+    /// assert_eq!(exported["roth_account_num"]["Jane"], serde_json::json!([456, 789]));
+    /// assert_eq!(exported["placement_preferences"]["VTIP"], "TraditionalIra");
+    /// ```
+    pub fn import_profiles(&mut self, json: &str) -> Result<()> {
+        let imported: VaporeApp = serde_json::from_str(json)?;
+        self.profile_name = imported.profile_name;
+        self.birth_year = imported.birth_year;
+        self.retirement_year = imported.retirement_year;
+        self.brokerage_stock = imported.brokerage_stock;
+        self.brokerage_account_num = imported.brokerage_account_num;
+        self.roth_account_num = imported.roth_account_num;
+        self.trad_account_num = imported.trad_account_num;
+        self.hsa_account_num = imported.hsa_account_num;
+        self.account_types = imported.account_types;
+        self.distribution_table = imported.distribution_table;
+        self.use_brokerage_retirement = imported.use_brokerage_retirement;
+        self.tax_aware_brokerage = imported.tax_aware_brokerage;
+        self.rebalance_threshold = imported.rebalance_threshold;
+        self.rounding_mode = imported.rounding_mode;
+        self.cash_percents = imported.cash_percents;
+        self.placement_preferences = imported.placement_preferences;
+        Ok(())
+    }
+
+    /// set_account_type stores the account type for the account number, which persists with the
+    /// app state so that the account does not need to be selected again each session.
+    pub fn set_account_type(&mut self, account_number: u32, holding_type: HoldingType) {
+        self.account_types.insert(account_number, holding_type);
+    }
+
+    /// account_type returns the stored account type for the account number.  If there is not a
+    /// stored account type, it is guessed from the loaded Vanguard transactions and the guess is
+    /// stored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings::HoldingType;
+    ///
+    /// let mut app = vapore_gui::VaporeApp::default();
+    /// app.set_account_type(123456789, HoldingType::RothIra);
+    /// let exported = app.export_profiles().unwrap();
+    ///
+    /// let mut new_app = vapore_gui::VaporeApp::default();
+    /// assert_eq!(new_app.account_type(123456789), None);
+    /// new_app.import_profiles(&exported).unwrap();
+    /// assert_eq!(new_app.account_type(123456789), Some(HoldingType::RothIra));
+    /// ```
+    pub fn account_type(&mut self, account_number: u32) -> Option<HoldingType> {
+        if let Some(holding_type) = self.account_types.get(&account_number) {
+            return Some(*holding_type);
+        }
+        let guess = self
+            .vanguard_holdings
+            .lock()
+            .unwrap()
+            .guess_account_type(account_number);
+        if let Some(holding_type) = guess {
+            self.account_types.insert(account_number, holding_type);
+        }
+        guess
+    }
+
+    // Finds the loaded account numbers with the stored or guessed account type
+    fn account_numbers_of_type(&mut self, holding_type: HoldingType) -> Vec<u32> {
+        self.loaded_account_numbers()
+            .into_iter()
+            .filter(|account_number| self.account_type(*account_number) == Some(holding_type))
+            .collect()
+    }
+
+    // The rebalance of each account type along with the label of the account type
+    fn labeled_accounts(&self) -> [(&'static str, &AccountHoldings); 4] {
+        [
+            ("Brokerage", &self.rebalance.brokerage),
+            ("Traditional IRA", &self.rebalance.traditional_ira),
+            ("Roth IRA", &self.rebalance.roth_ira),
+            ("HSA", &self.rebalance.hsa),
+        ]
+    }
+
+    // Sorted account numbers of the loaded Vanguard holdings
+    fn loaded_account_numbers(&self) -> Vec<u32> {
+        let mut account_numbers = self
+            .vanguard_holdings
+            .lock()
+            .unwrap()
+            .accounts_values
+            .keys()
+            .copied()
+            .collect::<Vec<u32>>();
+        account_numbers.sort_unstable();
+        account_numbers
+    }
 }
 
 impl eframe::App for VaporeApp {
@@ -143,6 +365,36 @@ impl eframe::App for VaporeApp {
                 let is_web = cfg!(target_arch = "wasm32");
                 if !is_web {
                     ui.menu_button("File", |ui| {
+                        // Export or import all profiles to move them between machines
+                        #[cfg(not(target_arch = "wasm32"))]
+                        if ui.button("Export profiles").clicked() {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .set_file_name("vapore_profiles.json")
+                                .save_file()
+                            {
+                                let exported = self.export_profiles().and_then(|profiles| {
+                                    std::fs::write(path, profiles).map_err(anyhow::Error::from)
+                                });
+                                self.profiles_message = match exported {
+                                    Ok(()) => String::new(),
+                                    Err(error) => format!("Profiles not exported: {}", error),
+                                };
+                            }
+                            ui.close_menu();
+                        }
+                        #[cfg(not(target_arch = "wasm32"))]
+                        if ui.button("Import profiles").clicked() {
+                            if let Some(path) = rfd::FileDialog::new().pick_file() {
+                                let imported = std::fs::read_to_string(path)
+                                    .map_err(anyhow::Error::from)
+                                    .and_then(|json| self.import_profiles(&json));
+                                self.profiles_message = match imported {
+                                    Ok(()) => String::new(),
+                                    Err(error) => format!("Profiles not imported: {}", error),
+                                };
+                            }
+                            ui.close_menu();
+                        }
                         if ui.button("Quit").clicked() {
                             ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                         }
@@ -156,21 +408,72 @@ impl eframe::App for VaporeApp {
 
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("VAnguard POrtfolio REbalance");
+            if !self.profiles_message.is_empty() {
+                ui.colored_label(egui::Color32::RED, &self.profiles_message);
+            }
 
             // Button to import the Vanguard ofxdownload.csv file
             if ui.button("Open Vanguard File").clicked() {
                 let file_future = rfd::AsyncFileDialog::new().pick_file();
                 let vanguard_holdings = Arc::clone(&self.vanguard_holdings);
+                let parse_warnings = Arc::clone(&self.parse_warnings);
+                let import_message = Arc::clone(&self.import_message);
                 // Function below used to import and be compatable with both OS and WASM
                 execute(async move {
                     if let Some(file) = file_future.await {
-                        *vanguard_holdings.lock().unwrap() =
-                            parse_csv_download(String::from_utf8(file.read().await).unwrap())
-                                .await
-                                .unwrap();
+                        let file_name = file.file_name().to_lowercase();
+                        // OFX and QFX downloads share the same format, where anything else is
+                        // parsed as the CSV download
+                        let parsed = String::from_utf8(file.read().await)
+                            .map_err(anyhow::Error::from)
+                            .and_then(|file_string| {
+                                if file_name.ends_with(".ofx") || file_name.ends_with(".qfx") {
+                                    parse_ofx_download(file_string)
+                                } else {
+                                    parse_csv_download(file_string)
+                                }
+                            });
+                        #[allow(unused_mut)]
+                        let (mut new_holdings, new_warnings) = match parsed {
+                            Ok(parsed) => parsed,
+                            Err(error) => {
+                                *import_message.lock().unwrap() =
+                                    format!("{} not imported: {}", file.file_name(), error);
+                                return;
+                            }
+                        };
+                        import_message.lock().unwrap().clear();
+                        // Only the quotes missing from the file and not cached from the last
+                        // retrieval are retrieved.  A failed retrieval leaves the placeholders,
+                        // which are listed by missing_quotes
+                        #[cfg(not(target_arch = "wasm32"))]
+                        {
+                            let reused = new_holdings.reuse_cached_quotes(
+                                &vanguard_holdings.lock().unwrap(),
+                                chrono::Duration::minutes(QUOTE_CACHE_MINUTES),
+                            );
+                            let quotes_result = match reused {
+                                Ok(()) => {
+                                    new_holdings
+                                        .add_placeholder_quotes(&YahooProvider::default())
+                                        .await
+                                }
+                                Err(error) => Err(error),
+                            };
+                            if let Err(error) = quotes_result {
+                                *import_message.lock().unwrap() =
+                                    format!("Quotes not retrieved: {}", error);
+                            }
+                        }
+                        *vanguard_holdings.lock().unwrap() = new_holdings;
+                        *parse_warnings.lock().unwrap() = new_warnings;
                     }
                 });
             };
+            let import_message = self.import_message.lock().unwrap().clone();
+            if !import_message.is_empty() {
+                ui.colored_label(egui::Color32::RED, import_message);
+            }
 
             // If vanguard file is loaded, load the rest of the app
             if !self
@@ -180,19 +483,58 @@ impl eframe::App for VaporeApp {
                 .accounts_values
                 .is_empty()
             {
+                // Show how many rows of the Vanguard file were skipped, with the reasons on hover
+                let parse_warnings = self.parse_warnings.lock().unwrap().clone();
+                if !parse_warnings.is_empty() {
+                    ui.label(format!("{} rows skipped while importing", parse_warnings.len()))
+                        .on_hover_text(
+                            parse_warnings
+                                .iter()
+                                .map(|warning| warning.to_string())
+                                .collect::<Vec<String>>()
+                                .join("\n"),
+                        );
+                }
+
                 // Update with Yahoo quotes which is only possible when not WASM/website
                 #[cfg(not(target_arch = "wasm32"))]
                 ui.horizontal(|ui| {
                     if ui.button("Update with Yahoo stock quotes").clicked() {
-                        self.vanguard_holdings
-                            .lock()
-                            .unwrap()
-                            .update_with_yahoo_quotes()
-                            .unwrap();
-                        self.yahoo_updated = true;
+                        let updated =
+                            block_on(self.vanguard_holdings.lock().unwrap().add_missing_quotes(
+                                &YahooProvider::default(),
+                                chrono::Duration::minutes(QUOTE_CACHE_MINUTES),
+                            ));
+                        match updated {
+                            Ok(()) => {
+                                self.import_message.lock().unwrap().clear();
+                                self.yahoo_updated = true;
+                            }
+                            Err(error) => {
+                                *self.import_message.lock().unwrap() =
+                                    format!("Yahoo quotes not retrieved: {}", error)
+                            }
+                        }
                     };
                     if self.yahoo_updated {
-                        ui.label("Updated");
+                        if let Some(age) = self.vanguard_holdings.lock().unwrap().quotes_age() {
+                            ui.label(format!("Quotes from {} min ago", age.num_minutes()));
+                        }
+                    }
+                    // Warn that quotes from the file or an old update may throw off the rebalance
+                    match self
+                        .vanguard_holdings
+                        .lock()
+                        .unwrap()
+                        .quotes_are_stale(chrono::Duration::hours(24))
+                    {
+                        Some(true) => {
+                            ui.label("Quotes may be out of date");
+                        }
+                        Some(false) => (),
+                        None => {
+                            ui.label("Quotes are from the Vanguard file and may be out of date");
+                        }
                     }
                 });
 
@@ -226,15 +568,29 @@ impl eframe::App for VaporeApp {
                             if !self.brokerage_stock.contains_key(&self.profile_name) {
                                 self.brokerage_stock.insert(self.profile_name.clone(), 65);
                             };
+                            // Account numbers start with any stored or guessed account types
                             if !self.brokerage_account_num.contains_key(&self.profile_name) {
+                                let account_nums =
+                                    self.account_numbers_of_type(HoldingType::Brokerage);
                                 self.brokerage_account_num
-                                    .insert(self.profile_name.clone(), 0);
+                                    .insert(self.profile_name.clone(), account_nums);
                             };
                             if !self.roth_account_num.contains_key(&self.profile_name) {
-                                self.roth_account_num.insert(self.profile_name.clone(), 0);
+                                let account_nums =
+                                    self.account_numbers_of_type(HoldingType::RothIra);
+                                self.roth_account_num
+                                    .insert(self.profile_name.clone(), account_nums);
                             };
                             if !self.trad_account_num.contains_key(&self.profile_name) {
-                                self.trad_account_num.insert(self.profile_name.clone(), 0);
+                                let account_nums =
+                                    self.account_numbers_of_type(HoldingType::TraditionalIra);
+                                self.trad_account_num
+                                    .insert(self.profile_name.clone(), account_nums);
+                            };
+                            if !self.hsa_account_num.contains_key(&self.profile_name) {
+                                let account_nums = self.account_numbers_of_type(HoldingType::Hsa);
+                                self.hsa_account_num
+                                    .insert(self.profile_name.clone(), account_nums);
                             };
                         };
                         // Delete profile with the name from the text edit.  Remove all profile name
@@ -246,6 +602,7 @@ impl eframe::App for VaporeApp {
                             self.brokerage_account_num.remove(&self.profile_name);
                             self.roth_account_num.remove(&self.profile_name);
                             self.trad_account_num.remove(&self.profile_name);
+                            self.hsa_account_num.remove(&self.profile_name);
                         }
                     });
 
@@ -265,134 +622,95 @@ impl eframe::App for VaporeApp {
 
                     // If a profile has been created, allow selection of brokerage account numbers derived from
                     // the Vanguard download file
-                    if let Some(profile_account_num) =
+                    let loaded_account_nums = self.loaded_account_numbers();
+                    if let Some(profile_account_nums) =
                         self.brokerage_account_num.get_mut(&self.profile_name)
                     {
                         ui.horizontal(|ui| {
-                            ui.label("Brokerage account number:");
-                            egui::ComboBox::from_id_source("Brokerage")
-                                .selected_text(profile_account_num.to_string())
-                                .show_ui(ui, |ui| {
-                                    for acct_num in self
-                                        .vanguard_holdings
-                                        .lock()
-                                        .unwrap()
-                                        .accounts_values
-                                        .keys()
-                                    {
-                                        ui.selectable_value(
-                                            &mut *profile_account_num,
-                                            *acct_num,
-                                            acct_num.to_string(),
-                                        );
-                                    }
-                                });
+                            ui.label("Brokerage account numbers:");
+                            account_num_checkboxes(ui, profile_account_nums, &loaded_account_nums);
                             ui.checkbox(&mut self.use_brokerage_retirement, "Retirement");
+                            ui.checkbox(&mut self.tax_aware_brokerage, "Tax aware");
                         });
+                        for account_num in profile_account_nums.iter() {
+                            self.account_types.insert(*account_num, HoldingType::Brokerage);
+                        }
                     };
 
-                    // If a profile has been created, allow selection of IRA account numbers derived from
-                    // the Vanguard download file
-                    if let Some(profile_account_num) =
-                        self.trad_account_num.get_mut(&self.profile_name)
-                    {
-                        ui.horizontal(|ui| {
-                            ui.label("Traditional IRA account number:");
-                            egui::ComboBox::from_id_source("Traditional")
-                                .selected_text(profile_account_num.to_string())
-                                .show_ui(ui, |ui| {
-                                    for acct_num in self
-                                        .vanguard_holdings
-                                        .lock()
-                                        .unwrap()
-                                        .accounts_values
-                                        .keys()
-                                    {
-                                        ui.selectable_value(
-                                            &mut *profile_account_num,
-                                            *acct_num,
-                                            acct_num.to_string(),
-                                        );
-                                    }
-                                });
-                        });
-                    };
-
-                    // If a profile has been created, allow selection of Roth IRA account numbers derived from
-                    // the Vanguard download file
-                    if let Some(profile_account_num) =
-                        self.roth_account_num.get_mut(&self.profile_name)
-                    {
-                        ui.horizontal(|ui| {
-                            ui.label("Roth IRA account number:");
-                            egui::ComboBox::from_id_source("IRA")
-                                .selected_text(profile_account_num.to_string())
-                                .show_ui(ui, |ui| {
-                                    for acct_num in self
-                                        .vanguard_holdings
-                                        .lock()
-                                        .unwrap()
-                                        .accounts_values
-                                        .keys()
-                                    {
-                                        ui.selectable_value(
-                                            &mut *profile_account_num,
-                                            *acct_num,
-                                            acct_num.to_string(),
-                                        );
-                                    }
-                                });
-                        });
-                    };
+                    // If a profile has been created, allow selection of the retirement account
+                    // numbers derived from the Vanguard download file
+                    for (account_nums, label, holding_type) in [
+                        (
+                            &mut self.trad_account_num,
+                            "Traditional IRA",
+                            HoldingType::TraditionalIra,
+                        ),
+                        (&mut self.roth_account_num, "Roth IRA", HoldingType::RothIra),
+                        (&mut self.hsa_account_num, "HSA", HoldingType::Hsa),
+                    ] {
+                        if let Some(profile_account_nums) = account_nums.get_mut(&self.profile_name)
+                        {
+                            ui.horizontal(|ui| {
+                                ui.label(format!("{} account numbers:", label));
+                                account_num_checkboxes(
+                                    ui,
+                                    profile_account_nums,
+                                    &loaded_account_nums,
+                                );
+                            });
+                            for account_num in profile_account_nums.iter() {
+                                self.account_types.insert(*account_num, holding_type);
+                            }
+                        };
+                    }
                 });
 
-                // If there is a profile created, set the brokerage hodlings to the account number
-                // selected
-                if let Some(brokerage_account_num) =
-                    self.brokerage_account_num.get(&self.profile_name)
-                {
-                    self.brokerage_holdings = *self
-                        .vanguard_holdings
-                        .lock()
-                        .unwrap()
-                        .accounts_values
-                        .get(brokerage_account_num)
-                        .unwrap_or(&ShareValues::default());
-                };
-
-                // If there is a profile created, set the IRA hodlings to the account number
-                // selected
-                if let Some(trad_account_num) = self.trad_account_num.get(&self.profile_name) {
-                    self.traditional_holdings = *self
-                        .vanguard_holdings
-                        .lock()
-                        .unwrap()
-                        .accounts_values
-                        .get(trad_account_num)
-                        .unwrap_or(&ShareValues::default());
-                };
-
-                // If there is a profile created, set the Roth IRA hodlings to the account number
-                // selected
-                if let Some(roth_account_num) = self.roth_account_num.get(&self.profile_name) {
-                    self.roth_holdings = *self
-                        .vanguard_holdings
-                        .lock()
-                        .unwrap()
-                        .accounts_values
-                        .get(roth_account_num)
-                        .unwrap_or(&ShareValues::default());
-                };
+                // If there is a profile created, set the holdings of each account type to the
+                // combined holdings of the account numbers selected
+                for (holdings, account_nums) in [
+                    (&mut self.brokerage_holdings, &self.brokerage_account_num),
+                    (&mut self.traditional_holdings, &self.trad_account_num),
+                    (&mut self.roth_holdings, &self.roth_account_num),
+                    (&mut self.hsa_holdings, &self.hsa_account_num),
+                ] {
+                    if let Some(account_nums) = account_nums.get(&self.profile_name) {
+                        *holdings = self
+                            .vanguard_holdings
+                            .lock()
+                            .unwrap()
+                            .combined_values(account_nums);
+                    };
+                }
 
                 // If brokerage percentage is not set by retirement ratios and is kept separate, create
                 // a slider to input brokerage stock percent
-                if !self.use_brokerage_retirement {
+                if !self.use_brokerage_retirement && !self.tax_aware_brokerage {
                     if let Some(brokerage_stock) = self.brokerage_stock.get_mut(&self.profile_name)
                     {
                         ui.add(
                             egui::Slider::new(&mut *brokerage_stock, 0..=100)
                                 .text("Brokerage percentage stock"),
                         );
+                        // Live preview of the brokerage target while the slider is moved
+                        if let Ok(allocation) = Allocations::custom(
+                            *brokerage_stock as f32,
+                            100.0 - *brokerage_stock as f32,
+                            0.0,
+                        ) {
+                            let preview = self.brokerage_holdings.preview_target(allocation);
+                            egui::CollapsingHeader::new("Brokerage target preview").show(
+                                ui,
+                                |ui| {
+                                    for symbol in StockSymbol::list() {
+                                        ui.label(format!(
+                                            "{:?}: {:.1}",
+                                            symbol.clone(),
+                                            preview.stock_value(symbol).unwrap_or(0.0)
+                                        ));
+                                    }
+                                },
+                            );
+                        };
                     };
                 };
 
@@ -426,23 +744,80 @@ impl eframe::App for VaporeApp {
                     };
                 });
 
-                // Cash to add or subtract from the brokerage account
-                ui.add(
-                    egui::Slider::new(&mut self.brokerage_cash_add, -100000..=100000)
-                        .text("Brokerage cash add/remove"),
-                );
+                // Cash to add or subtract from each account
+                for (cash_add, holdings, label) in [
+                    (&mut self.brokerage_cash_add, &self.brokerage_holdings, "Brokerage"),
+                    (&mut self.roth_cash_add, &self.roth_holdings, "Roth IRA"),
+                    (&mut self.traditional_cash_add, &self.traditional_holdings, "Traditional IRA"),
+                    (&mut self.hsa_cash_add, &self.hsa_holdings, "HSA"),
+                ] {
+                    cash_add_slider(ui, cash_add, holdings, label);
+                }
 
-                // Cash to add or subtract from the Roth IRA account
+                // Purchases/sales worth less than the threshold are dropped from the rebalance
                 ui.add(
-                    egui::Slider::new(&mut self.roth_cash_add, -100000..=100000)
-                        .text("Roth IRA cash add/remove"),
+                    egui::Slider::new(&mut self.rebalance_threshold, 0.0..=1000.0)
+                        .text("Minimum trade ($)"),
                 );
 
-                // Cash to add or subtract from the IRA account
-                ui.add(
-                    egui::Slider::new(&mut self.traditional_cash_add, -100000..=100000)
-                        .text("Traditional IRA cash add/remove"),
-                );
+                // Percent of each account kept within cash instead of invested
+                egui::CollapsingHeader::new("Cash kept (%)").show(ui, |ui| {
+                    for (cash_percent, label) in [
+                        (&mut self.cash_percents.brokerage, "Brokerage"),
+                        (&mut self.cash_percents.traditional_ira, "Traditional IRA"),
+                        (&mut self.cash_percents.roth_ira, "Roth IRA"),
+                        (&mut self.cash_percents.hsa, "HSA"),
+                    ] {
+                        ui.add(egui::Slider::new(cash_percent, 0.0..=100.0).text(label));
+                    }
+                });
+
+                // Account that each symbol is placed within first, before the risk ordering
+                egui::CollapsingHeader::new("Account preferences").show(ui, |ui| {
+                    for stock_symbol in StockSymbol::list() {
+                        let mut preference = self.placement_preferences.get(&stock_symbol).copied();
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{:?}:", stock_symbol));
+                            for (holding_type, label) in [
+                                (None, "Any"),
+                                (Some(HoldingType::Brokerage), "Brokerage"),
+                                (Some(HoldingType::TraditionalIra), "Traditional IRA"),
+                                (Some(HoldingType::RothIra), "Roth IRA"),
+                                (Some(HoldingType::Hsa), "HSA"),
+                            ] {
+                                ui.selectable_value(&mut preference, holding_type, label);
+                            }
+                        });
+                        match preference {
+                            Some(holding_type) => {
+                                self.placement_preferences.insert(stock_symbol, holding_type);
+                            }
+                            None => {
+                                self.placement_preferences.remove(&stock_symbol);
+                            }
+                        }
+                    }
+                });
+
+                // Rounding of the shares to buy and sell for brokerages without fractional shares
+                ui.horizontal(|ui| {
+                    ui.label("Shares:");
+                    ui.selectable_value(
+                        &mut self.rounding_mode,
+                        RoundingMode::Fractional,
+                        "Fractional",
+                    );
+                    ui.selectable_value(
+                        &mut self.rounding_mode,
+                        RoundingMode::WholeShares,
+                        "Whole",
+                    );
+                    ui.selectable_value(
+                        &mut self.rounding_mode,
+                        RoundingMode::WholeSharesConservative,
+                        "Whole, rounded down",
+                    );
+                });
 
                 // Distribution requirements after retirement age for the IRA.  Cannot be used by
                 // WASM/website due to needing to get Yahoo quotes to determine the previous end of
@@ -455,25 +830,36 @@ impl eframe::App for VaporeApp {
                                 self.distribution_table = calc::get_distribution_table(path).unwrap();
                                 // If the profile's age is old enough, display the distribution needed
                                 if let Some(birth_year) = self.birth_year.get(&self.profile_name) {
-                                    if let Some(trad_account_num) =
+                                    if let Some(trad_account_nums) =
                                         self.trad_account_num.get(&self.profile_name)
                                     {
                                         let age = self.distribution_year - birth_year;
                                         if age >= *self.distribution_table.keys().min().unwrap_or(&70) {
                                             let mut v_holdings = self.vanguard_holdings.lock().unwrap();
+                                            // Distribution is from the combined traditional IRAs
+                                            let eoy_values = trad_account_nums
+                                                .iter()
+                                                .map(|trad_account_num| {
+                                                    block_on(v_holdings.eoy_value(
+                                                        self.distribution_year,
+                                                        *trad_account_num,
+                                                        &YahooProvider::default(),
+                                                    ))
+                                                })
+                                                .collect::<Result<Vec<Option<f32>>>>()
+                                                .unwrap();
                                             if let Some(traditional_value) =
-                                                block_on(v_holdings.eoy_value(
-                                                    self.distribution_year,
-                                                    *trad_account_num,
-                                                ))
-                                                .unwrap()
+                                                eoy_values.into_iter().sum::<Option<f32>>()
                                             {
                                                 let minimum_distribution_div =
                                                     *self.distribution_table.get(&age).unwrap_or(&0.0);
                                                 if minimum_distribution_div != 0.0 {
                                                     let minimum_distribution =
                                                         traditional_value / minimum_distribution_div;
-                                                    let so_far = v_holdings.get_distributions(trad_account_num);
+                                                    let so_far = trad_account_nums
+                                                        .iter()
+                                                        .map(|trad_account_num| v_holdings.get_distributions(trad_account_num))
+                                                        .sum::<f32>();
                                                     let left = (minimum_distribution - so_far).max(0.0);
                                                     self.distribution_needed = format!("Minimum distribution: ${:.2}  So far: ${:.2}  To go: ${:.2}", minimum_distribution, so_far, left);
                                                 }
@@ -509,32 +895,116 @@ impl eframe::App for VaporeApp {
                 if let Some(brokerage_stock) = self.brokerage_stock.get(&self.profile_name) {
                     if let Some(retirement_year) = self.retirement_year.get(&self.profile_name) {
                         if ui.button("Update target holdings").clicked() {
-                            self.rebalance = calc::to_buy(
-                                *brokerage_stock as f32,
-                                self.brokerage_cash_add as f32,
-                                self.brokerage_us_stock_add,
-                                self.brokerage_int_stock_add,
-                                self.brokerage_us_bond_add,
-                                self.brokerage_int_bond_add,
+                            // Check the inputs first so a friendly message is shown instead of
+                            // failing partway through the rebalance
+                            let account_values = HashMap::from([
+                                (
+                                    HoldingType::Brokerage,
+                                    self.brokerage_holdings.total_value()
+                                        + self.brokerage_cash_add as f32,
+                                ),
+                                (
+                                    HoldingType::TraditionalIra,
+                                    self.traditional_holdings.total_value()
+                                        + self.traditional_cash_add as f32,
+                                ),
+                                (
+                                    HoldingType::RothIra,
+                                    self.roth_holdings.total_value() + self.roth_cash_add as f32,
+                                ),
+                                (
+                                    HoldingType::Hsa,
+                                    self.hsa_holdings.total_value() + self.hsa_cash_add as f32,
+                                ),
+                            ]);
+                            if let Err(error) = calc::check_feasibility(
                                 *retirement_year,
-                                self.roth_holdings,
-                                self.roth_us_stock_add,
-                                self.roth_us_bond_add,
-                                self.roth_int_stock_add,
-                                self.roth_int_bond_add,
-                                self.roth_cash_add as f32,
-                                self.traditional_holdings,
-                                self.traditional_us_stock_add,
-                                self.traditional_us_bond_add,
-                                self.traditional_int_stock_add,
-                                self.traditional_int_bond_add,
-                                self.traditional_cash_add as f32,
-                                self.use_brokerage_retirement,
-                                self.brokerage_holdings,
-                                self.vanguard_holdings.lock().unwrap().stock_quotes(),
-                            )
-                            .unwrap();
+                                *brokerage_stock as f32,
+                                &account_values,
+                                self.brokerage_us_stock_add + self.brokerage_int_stock_add,
+                                self.brokerage_us_bond_add + self.brokerage_int_bond_add,
+                                self.use_brokerage_retirement || self.tax_aware_brokerage,
+                                &self.placement_preferences,
+                            ) {
+                                self.feasibility_message = error.to_string();
+                            } else {
+                                self.feasibility_message.clear();
+                                let options = calc::RebalanceOptions {
+                                    percent_stock: *brokerage_stock as f32,
+                                    retirement_year: *retirement_year,
+                                    brokerage: calc::AccountInputs {
+                                        holdings: self.brokerage_holdings,
+                                        cash_add: self.brokerage_cash_add as f32,
+                                        us_stock_add: self.brokerage_us_stock_add,
+                                        us_bond_add: self.brokerage_us_bond_add,
+                                        int_stock_add: self.brokerage_int_stock_add,
+                                        int_bond_add: self.brokerage_int_bond_add,
+                                    },
+                                    roth_ira: calc::AccountInputs {
+                                        holdings: self.roth_holdings,
+                                        cash_add: self.roth_cash_add as f32,
+                                        us_stock_add: self.roth_us_stock_add,
+                                        us_bond_add: self.roth_us_bond_add,
+                                        int_stock_add: self.roth_int_stock_add,
+                                        int_bond_add: self.roth_int_bond_add,
+                                    },
+                                    traditional_ira: calc::AccountInputs {
+                                        holdings: self.traditional_holdings,
+                                        cash_add: self.traditional_cash_add as f32,
+                                        us_stock_add: self.traditional_us_stock_add,
+                                        us_bond_add: self.traditional_us_bond_add,
+                                        int_stock_add: self.traditional_int_stock_add,
+                                        int_bond_add: self.traditional_int_bond_add,
+                                    },
+                                    hsa: calc::AccountInputs {
+                                        cash_add: self.hsa_cash_add as f32,
+                                        ..calc::AccountInputs::new(self.hsa_holdings)
+                                    },
+                                    use_brokerage_retirement: self.use_brokerage_retirement,
+                                    tax_aware_brokerage: self.tax_aware_brokerage,
+                                    rebalance_threshold: self.rebalance_threshold,
+                                    rounding_mode: self.rounding_mode,
+                                    cash_percents: self.cash_percents,
+                                    placement_preferences: self.placement_preferences.clone(),
+                                    ..Default::default()
+                                };
+                                // The brokerage cost basis from the imported transactions is
+                                // used to warn about wash sales
+                                let brokerage_account_nums = self
+                                    .brokerage_account_num
+                                    .get(&self.profile_name)
+                                    .cloned()
+                                    .unwrap_or_default();
+                                let vanguard_holdings = self.vanguard_holdings.lock().unwrap();
+                                let stock_quotes = vanguard_holdings.stock_quotes();
+                                let rebalance = vanguard_holdings
+                                    .cost_basis(&brokerage_account_nums)
+                                    .and_then(|brokerage_cost_basis| {
+                                        let options = calc::RebalanceOptions {
+                                            brokerage_cost_basis,
+                                            ..options
+                                        };
+                                        calc::to_buy(&options, stock_quotes)
+                                    });
+                                drop(vanguard_holdings);
+                                match rebalance {
+                                    Ok(rebalance) => self.rebalance = rebalance,
+                                    Err(error) => self.feasibility_message = error.to_string(),
+                                }
+                            }
                         };
+                        if !self.feasibility_message.is_empty() {
+                            ui.colored_label(egui::Color32::RED, &self.feasibility_message);
+                        }
+                        if let Some((allocation_used, _)) = self.rebalance.allocation_used() {
+                            ui.label(format!(
+                                "Target allocation for {}: {:.0}% stock / {:.0}% bond / {:.0}% inflation protected",
+                                retirement_year,
+                                allocation_used.total_stock(),
+                                allocation_used.total_bond(),
+                                allocation_used.total_inflation_protected()
+                            ));
+                        }
                     }
                 }
 
@@ -548,54 +1018,9 @@ impl eframe::App for VaporeApp {
                             }
                             ui.label("Other");
                         });
-                        ui.vertical(|ui| {
-                            ui.label("Brokerage");
-                            for symbol in StockSymbol::list() {
-                                ui.label(format!(
-                                    "{:.1}",
-                                    self.rebalance.brokerage.current.stock_value(symbol)
-                                ));
-                            }
-                            ui.label(format!(
-                                "{:.1}",
-                                self.rebalance
-                                    .brokerage
-                                    .current
-                                    .stock_value(StockSymbol::Other(String::default()))
-                            ));
-                        });
-                        ui.vertical(|ui| {
-                            ui.label("Traditional IRA");
-                            for symbol in StockSymbol::list() {
-                                ui.label(format!(
-                                    "{:.1}",
-                                    self.rebalance.traditional_ira.current.stock_value(symbol)
-                                ));
-                            }
-                            ui.label(format!(
-                                "{:.1}",
-                                self.rebalance
-                                    .traditional_ira
-                                    .current
-                                    .stock_value(StockSymbol::Other(String::default()))
-                            ));
-                        });
-                        ui.vertical(|ui| {
-                            ui.label("Roth IRA");
-                            for symbol in StockSymbol::list() {
-                                ui.label(format!(
-                                    "{:.1}",
-                                    self.rebalance.roth_ira.current.stock_value(symbol)
-                                ));
-                            }
-                            ui.label(format!(
-                                "{:.1}",
-                                self.rebalance
-                                    .roth_ira
-                                    .current
-                                    .stock_value(StockSymbol::Other(String::default()))
-                            ));
-                        });
+                        for (label, account) in self.labeled_accounts() {
+                            values_column(ui, label, &account.current);
+                        }
                     });
                 });
 
@@ -609,54 +1034,9 @@ impl eframe::App for VaporeApp {
                             }
                             ui.label("Other");
                         });
-                        ui.vertical(|ui| {
-                            ui.label("Brokerage");
-                            for symbol in StockSymbol::list() {
-                                ui.label(format!(
-                                    "{:.1}",
-                                    self.rebalance.brokerage.target.stock_value(symbol)
-                                ));
-                            }
-                            ui.label(format!(
-                                "{:.1}",
-                                self.rebalance
-                                    .brokerage
-                                    .target
-                                    .stock_value(StockSymbol::Other(String::default()))
-                            ));
-                        });
-                        ui.vertical(|ui| {
-                            ui.label("Traditional IRA");
-                            for symbol in StockSymbol::list() {
-                                ui.label(format!(
-                                    "{:.1}",
-                                    self.rebalance.traditional_ira.target.stock_value(symbol)
-                                ));
-                            }
-                            ui.label(format!(
-                                "{:.1}",
-                                self.rebalance
-                                    .traditional_ira
-                                    .target
-                                    .stock_value(StockSymbol::Other(String::default()))
-                            ));
-                        });
-                        ui.vertical(|ui| {
-                            ui.label("Roth IRA");
-                            for symbol in StockSymbol::list() {
-                                ui.label(format!(
-                                    "{:.1}",
-                                    self.rebalance.roth_ira.target.stock_value(symbol)
-                                ));
-                            }
-                            ui.label(format!(
-                                "{:.1}",
-                                self.rebalance
-                                    .roth_ira
-                                    .target
-                                    .stock_value(StockSymbol::Other(String::default()))
-                            ));
-                        });
+                        for (label, account) in self.labeled_accounts() {
+                            values_column(ui, label, &account.target);
+                        }
                     });
                 });
 
@@ -670,65 +1050,71 @@ impl eframe::App for VaporeApp {
                             }
                             ui.label("Other");
                         });
-                        ui.vertical(|ui| {
-                            ui.label("Brokerage");
-                            for symbol in StockSymbol::list() {
-                                ui.label(format!(
-                                    "{:.1}",
-                                    self.rebalance
-                                        .brokerage
-                                        .sale_purchases_needed
-                                        .stock_value(symbol)
-                                ));
-                            }
-                            ui.label(format!(
-                                "{:.1}",
-                                self.rebalance
-                                    .brokerage
-                                    .sale_purchases_needed
-                                    .stock_value(StockSymbol::Other(String::default()))
-                            ));
-                        });
-                        ui.vertical(|ui| {
-                            ui.label("Traditional IRA");
-                            for symbol in StockSymbol::list() {
-                                ui.label(format!(
-                                    "{:.1}",
-                                    self.rebalance
-                                        .traditional_ira
-                                        .sale_purchases_needed
-                                        .stock_value(symbol)
-                                ));
-                            }
-                            ui.label(format!(
-                                "{:.1}",
-                                self.rebalance
-                                    .traditional_ira
-                                    .sale_purchases_needed
-                                    .stock_value(StockSymbol::Other(String::default()))
-                            ));
-                        });
-                        ui.vertical(|ui| {
-                            ui.label("Roth IRA");
-                            for symbol in StockSymbol::list() {
-                                ui.label(format!(
-                                    "{:.1}",
-                                    self.rebalance
-                                        .roth_ira
-                                        .sale_purchases_needed
-                                        .stock_value(symbol)
-                                ));
-                            }
-                            ui.label(format!(
-                                "{:.1}",
-                                self.rebalance
-                                    .roth_ira
-                                    .sale_purchases_needed
-                                    .stock_value(StockSymbol::Other(String::default()))
-                            ));
-                        });
+                        for (label, account) in self.labeled_accounts() {
+                            values_column(ui, label, &account.sale_purchases_needed);
+                        }
                     });
+                    // Total dollars bought and sold across the accounts, leaving out the cash
+                    // used for the trades
+                    let quotes = self.vanguard_holdings.lock().unwrap().stock_quotes();
+                    let total_traded: f32 = [
+                        &self.rebalance.brokerage,
+                        &self.rebalance.traditional_ira,
+                        &self.rebalance.roth_ira,
+                        &self.rebalance.hsa,
+                    ]
+                    .iter()
+                    .map(|account| {
+                        let traded = account.sale_purchases_needed * quotes;
+                        traded.sum_abs()
+                            - traded.stock_value(StockSymbol::VMFXX).unwrap_or(0.0).abs()
+                    })
+                    .sum();
+                    ui.label(format!("Total traded: ${:.2}", total_traded));
                 });
+
+                // Display the purchase/sales split into each account number when an account type
+                // combines several accounts, so it is known which account to trade in
+                let account_type_nums = [
+                    ("Brokerage", &self.rebalance.brokerage, &self.brokerage_account_num),
+                    ("Traditional IRA", &self.rebalance.traditional_ira, &self.trad_account_num),
+                    ("Roth IRA", &self.rebalance.roth_ira, &self.roth_account_num),
+                    ("HSA", &self.rebalance.hsa, &self.hsa_account_num),
+                ];
+                if account_type_nums.iter().any(|(_, _, account_nums)| {
+                    account_nums.get(&self.profile_name).map_or(0, |nums| nums.len()) > 1
+                }) {
+                    egui::CollapsingHeader::new("Purchase by account").show(ui, |ui| {
+                        let v_holdings = self.vanguard_holdings.lock().unwrap();
+                        for (label, account_holdings, account_nums) in account_type_nums {
+                            let account_nums =
+                                account_nums.get(&self.profile_name).cloned().unwrap_or_default();
+                            let accounts_values = account_nums
+                                .iter()
+                                .map(|account_num| {
+                                    (*account_num, v_holdings.combined_values(&[*account_num]))
+                                })
+                                .collect::<HashMap<u32, ShareValues>>();
+                            let split = account_holdings
+                                .split_by_account(&accounts_values, v_holdings.stock_quotes());
+                            for account_num in &account_nums {
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("{} {}:", label, account_num));
+                                    for symbol in StockSymbol::list() {
+                                        ui.label(format!(
+                                            "{:?} {:.1}",
+                                            symbol.clone(),
+                                            split[account_num]
+                                                .sale_purchases_needed
+                                                .stock_value(symbol)
+                                                .unwrap_or(0.0)
+                                        ));
+                                    }
+                                });
+                            }
+                        }
+                    });
+                }
             }
 
             ui.separator();
@@ -740,6 +1126,57 @@ impl eframe::App for VaporeApp {
     }
 }
 
+// Slider of the cash to add or subtract from an account, along with the percent of the account
+fn cash_add_slider(ui: &mut egui::Ui, cash_add: &mut i32, holdings: &ShareValues, label: &str) {
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::Slider::new(cash_add, -100000..=100000)
+                .text(format!("{} cash add/remove", label)),
+        );
+        ui.label(format!(
+            "{:+.1}% of account",
+            holdings.cash_add_pct(*cash_add as f32)
+        ));
+    });
+}
+
+// Column of the labeled values of each stock symbol followed by the other holdings
+fn values_column(ui: &mut egui::Ui, label: &str, values: &ShareValues) {
+    ui.vertical(|ui| {
+        ui.label(label);
+        for symbol in StockSymbol::list() {
+            ui.label(format!("{:.1}", values.stock_value(symbol).unwrap_or(0.0)));
+        }
+        ui.label(format!(
+            "{:.1}",
+            values
+                .stock_value(StockSymbol::Other(String::default()))
+                .unwrap_or(0.0)
+        ));
+    });
+}
+
+// Checkboxes to select which of the loaded account numbers belong to an account type
+fn account_num_checkboxes(
+    ui: &mut egui::Ui,
+    account_nums: &mut Vec<u32>,
+    loaded_account_nums: &[u32],
+) {
+    for account_num in loaded_account_nums {
+        let mut selected = account_nums.contains(account_num);
+        if ui
+            .checkbox(&mut selected, account_num.to_string())
+            .changed()
+        {
+            if selected {
+                account_nums.push(*account_num);
+            } else {
+                account_nums.retain(|selected_num| selected_num != account_num);
+            }
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn execute<F: Future<Output = ()> + Send + 'static>(f: F) {
     // this is stupid... use any executor of your choice instead
@@ -1,8 +1,10 @@
+use crate::holdings::StockSymbol;
 use anyhow::{ensure, Result};
 use chrono::prelude::*;
 use std::fmt;
 
-// Constants used for proportion of portfolio contained within each.
+// Constants used for the default proportion of portfolio contained within each, which can be
+// changed at runtime with SubAllocationWeights.
 // Split by stocks and bonds
 // US stock as 2/3 of total stock.  Then split by 3 for Large, medium, and small cap
 const US_STOCK_FRACTION: f32 = 2.0 / 3.0;
@@ -18,8 +20,11 @@ const US_BOND_FRACTION: f32 = 2.0 / 3.0;
 const US_CORP_BOND_FRACTION: f32 = US_BOND_FRACTION / 2.0;
 const US_TOT_BOND_FRACTION: f32 = US_BOND_FRACTION / 2.0;
 const INT_BOND_FRACTION: f32 = 1.0 / 3.0;
+// Percent stock on either side of the 110 minus age rule of thumb that is considered appropriate
+const AGE_BAND: f32 = 10.0;
 
 /// Holds the stock, bond, and inflation protected percentages.
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Allocations {
     total_stock: f32,
     total_bond: f32,
@@ -38,6 +43,38 @@ impl Allocations {
     /// Calculates the stock, bond, and inflation protected percentages based on Vanguard target
     /// asset allocation.
     pub fn retirement(year: i32) -> Result<Self> {
+        Self::retirement_with_glide_path(year, &GlidePath::vanguard_default())
+    }
+
+    /// Calculates the stock, bond, and inflation protected percentages from the glide path at the
+    /// years until the retirement year, with bonds making up the remainder.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::prelude::*;
+    /// use vapore_gui::asset;
+    ///
+    /// // A more conservative path from 70% stock 20 years out down to 30% stock at retirement
+    /// let glide_path = asset::GlidePath::new(vec![(0.0, 30.0), (20.0, 70.0)], vec![]).unwrap();
+    /// let this_year = chrono::Local::now().year();
+    ///
+    /// let far = asset::Allocations::retirement_with_glide_path(this_year + 30, &glide_path).unwrap();
+    /// let middle = asset::Allocations::retirement_with_glide_path(this_year + 10, &glide_path).unwrap();
+    /// let retired = asset::Allocations::retirement_with_glide_path(this_year - 5, &glide_path).unwrap();
+    /// assert_eq!(far.total_stock(), 70.0);
+    /// assert_eq!(middle.total_stock(), 50.0);
+    /// assert_eq!(middle.total_bond(), 50.0);
+    /// assert_eq!(retired.total_stock(), 30.0);
+    ///
+    /// // The default glide path matches retirement
+    /// let vanguard = asset::GlidePath::vanguard_default();
+    /// for year in [this_year - 10, this_year, this_year + 10, this_year + 40] {
+    ///     let default_path = asset::Allocations::retirement_with_glide_path(year, &vanguard).unwrap();
+    ///     assert_eq!(default_path.total_stock(), asset::Allocations::retirement(year).unwrap().total_stock());
+    /// }
+    /// ```
+    pub fn retirement_with_glide_path(year: i32, glide_path: &GlidePath) -> Result<Self> {
         ensure!(
             (2000..3000).contains(&year),
             format!(
@@ -47,37 +84,93 @@ impl Allocations {
         );
         let this_year = chrono::Local::now().year();
         let years_to_retirement = (year - this_year) as f32;
-        let mut total_stock = 90.0;
-        let mut total_bond = 10.0;
-        let mut total_inflation_protected = 0.0;
-        if (5.0..30.0).contains(&years_to_retirement) {
-            total_stock = 90.0 - (1.5 * (25.0 - years_to_retirement));
-            total_bond = 100.0 - total_stock;
-        } else if (-5.0..5.0).contains(&years_to_retirement) {
-            total_stock = 60.0 - (-2.8 * (years_to_retirement - 5.0));
-            total_inflation_protected = -1.8 * (years_to_retirement - 5.0);
-            total_bond = 100.0 - total_stock - total_inflation_protected;
-        } else if years_to_retirement < -5.0 {
-            total_stock = 29.0;
-            total_bond = 53.0;
-            total_inflation_protected = 18.0;
-        }
+        let total_stock = glide_path.stock_percent(years_to_retirement);
+        let total_inflation_protected = glide_path.inflation_protected_percent(years_to_retirement);
         Ok(Allocations {
             total_stock,
-            total_bond,
+            total_bond: 100.0 - total_stock - total_inflation_protected,
+            total_inflation_protected,
+        })
+    }
+
+    /// Calculates the stock percentage based on Vanguard target asset allocation, similar to
+    /// retirement, but with a separate inflation protected glidepath in place of the Vanguard one.
+    /// The inflation protected percentage ramps linearly from 0 at ramp_years before retirement up
+    /// to the inflation_target at retirement and stays there after.  Bonds make up the remainder.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::prelude::*;
+    /// use vapore_gui::asset;
+    ///
+    /// let this_year = chrono::Local::now().year();
+    /// let far = asset::Allocations::retirement_with(this_year + 20, 10.0, 10).unwrap();
+    /// let near = asset::Allocations::retirement_with(this_year + 8, 10.0, 10).unwrap();
+    /// let nearer = asset::Allocations::retirement_with(this_year + 3, 10.0, 10).unwrap();
+    /// let retired = asset::Allocations::retirement_with(this_year - 2, 10.0, 10).unwrap();
+    ///
+    /// // Inflation protection rises before retirement
+    /// assert_eq!(far.total_inflation_protected(), 0.0);
+    /// assert!(near.total_inflation_protected() > far.total_inflation_protected());
+    /// assert!(nearer.total_inflation_protected() > near.total_inflation_protected());
+    /// assert_eq!(retired.total_inflation_protected(), 10.0);
+    /// assert_eq!(near.total_stock() + near.total_bond() + near.total_inflation_protected(), 100.0);
+    ///
+    /// assert!(asset::Allocations::retirement_with(this_year, 110.0, 10).is_err());
+    /// ```
+    pub fn retirement_with(year: i32, inflation_target: f32, ramp_years: u32) -> Result<Self> {
+        ensure!(
+            (0.0..=100.0).contains(&inflation_target),
+            format!(
+                "Inflation protected target needs to be between 0 and 100.  Target input: {}",
+                inflation_target
+            )
+        );
+        let total_stock = Self::retirement(year)?.total_stock;
+        let years_to_retirement = (year - chrono::Local::now().year()) as f32;
+        let ramp_years = ramp_years as f32;
+        let total_inflation_protected = if years_to_retirement <= 0.0 {
+            inflation_target
+        } else if years_to_retirement < ramp_years {
+            inflation_target * (ramp_years - years_to_retirement) / ramp_years
+        } else {
+            0.0
+        };
+        ensure!(
+            total_stock + total_inflation_protected <= 100.0,
+            format!(
+                "Stock ({}) + inflation protected ({}) is more than 100",
+                total_stock, total_inflation_protected
+            )
+        );
+        Ok(Allocations {
+            total_stock,
+            total_bond: 100.0 - total_stock - total_inflation_protected,
             total_inflation_protected,
         })
     }
 
     /// Creates a Allocations struct with custom input values for stock, bond, and inflaction
-    /// protected precentages.
+    /// protected precentages.  The percentages need to add up to within 0.01 of 100, which allows
+    /// for floating point error in percentages that were calculated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::asset;
+    ///
+    /// assert!(asset::Allocations::custom(33.33, 33.33, 33.34).is_ok());
+    /// assert!(asset::Allocations::custom(100.0 / 3.0, 100.0 / 3.0, 100.0 / 3.0).is_ok());
+    /// assert!(asset::Allocations::custom(90.0, 5.0, 3.0).is_err());
+    /// ```
     pub fn custom(
         total_stock: f32,
         total_bond: f32,
         total_inflation_protected: f32,
     ) -> Result<Self> {
         ensure!(
-            total_stock + total_bond + total_inflation_protected == 100.0,
+            ((total_stock + total_bond + total_inflation_protected) - 100.0).abs() <= 0.01,
             format!(
                 "Stock ({}) + bond ({}) + inflation protected ({}) does not equal 100",
                 total_stock, total_bond, total_inflation_protected
@@ -104,6 +197,31 @@ impl Allocations {
     pub fn total_inflation_protected(&self) -> f32 {
         self.total_inflation_protected
     }
+
+    /// Compares the total stock percentage to the 110 minus age rule of thumb with a 10 percent
+    /// band on either side.  Returns "aggressive" when above the band, "conservative" when below
+    /// the band, and "appropriate" when within the band.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::asset;
+    ///
+    /// let allocations = asset::Allocations::custom(70.0, 30.0, 0.0).unwrap();
+    /// assert_eq!(allocations.age_appropriateness(60), "aggressive");
+    /// assert_eq!(allocations.age_appropriateness(40), "appropriate");
+    /// assert_eq!(allocations.age_appropriateness(20), "conservative");
+    /// ```
+    pub fn age_appropriateness(&self, age: u32) -> String {
+        let rule_of_thumb_stock = (110.0 - age as f32).clamp(0.0, 100.0);
+        if self.total_stock > rule_of_thumb_stock + AGE_BAND {
+            "aggressive".to_string()
+        } else if self.total_stock < rule_of_thumb_stock - AGE_BAND {
+            "conservative".to_string()
+        } else {
+            "appropriate".to_string()
+        }
+    }
 }
 
 impl Default for Allocations {
@@ -122,8 +240,231 @@ impl fmt::Display for Allocations {
     }
 }
 
+/// GlidePath holds the control points of the stock and inflation protected percentages by the
+/// years until retirement, where negative years are after retirement.  Percentages between the
+/// control points are interpolated linearly, and before the first or after the last control point
+/// the percentage of that control point is kept.  Two control points at the same year create a
+/// step, where the second is used from that year on.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GlidePath {
+    stock: Vec<(f32, f32)>,
+    inflation_protected: Vec<(f32, f32)>,
+}
+
+impl GlidePath {
+    /// new creates a new GlidePath from the (years to retirement, percent) control points of the
+    /// stock and inflation protected percentages.  Without any inflation protected control points
+    /// there is no inflation protection.  Errors when there are no stock control points or when
+    /// the stock, inflation protected, or their sum is below 0 or above 100 at any control point,
+    /// which keeps every interpolated percentage between 0 and 100.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::asset;
+    ///
+    /// assert!(asset::GlidePath::new(vec![(0.0, 40.0), (30.0, 90.0)], vec![(0.0, 10.0)]).is_ok());
+    /// assert!(asset::GlidePath::new(vec![], vec![]).is_err());
+    /// assert!(asset::GlidePath::new(vec![(0.0, 40.0), (30.0, 110.0)], vec![]).is_err());
+    /// assert!(asset::GlidePath::new(vec![(0.0, -5.0)], vec![]).is_err());
+    /// // 95% stock with 10% inflation protected 10 years out is more than 100
+    /// assert!(asset::GlidePath::new(vec![(0.0, 40.0), (30.0, 100.0)], vec![(10.0, 10.0)]).is_err());
+    /// ```
+    pub fn new(
+        mut stock: Vec<(f32, f32)>,
+        mut inflation_protected: Vec<(f32, f32)>,
+    ) -> Result<Self> {
+        ensure!(
+            !stock.is_empty(),
+            "The glide path needs at least one stock control point"
+        );
+        // Stable sorts keep the order of control points at the same year, which are steps
+        stock.sort_by(|first, second| first.0.total_cmp(&second.0));
+        inflation_protected.sort_by(|first, second| first.0.total_cmp(&second.0));
+        let glide_path = GlidePath {
+            stock,
+            inflation_protected,
+        };
+        // Both percentages are linear between the control points, so the sum is largest at one of
+        // the control points, where each side of a step is its own control point
+        let stock_points = glide_path.stock.iter().map(|(years, stock_percent)| {
+            (
+                *years,
+                *stock_percent,
+                glide_path.inflation_protected_percent(*years),
+            )
+        });
+        let inflation_protected_points =
+            glide_path
+                .inflation_protected
+                .iter()
+                .map(|(years, inflation_protected_percent)| {
+                    (
+                        *years,
+                        glide_path.stock_percent(*years),
+                        *inflation_protected_percent,
+                    )
+                });
+        for (years, stock_percent, inflation_protected_percent) in
+            stock_points.chain(inflation_protected_points)
+        {
+            ensure!(
+                (0.0..=100.0).contains(&stock_percent)
+                    && (0.0..=100.0).contains(&inflation_protected_percent)
+                    && stock_percent + inflation_protected_percent <= 100.0,
+                format!(
+                    "Glide path percentages need to be between 0 and 100.  At {} years: stock {}, inflation protected {}",
+                    years, stock_percent, inflation_protected_percent
+                )
+            );
+        }
+        Ok(glide_path)
+    }
+
+    /// vanguard_default creates the GlidePath which follows the Vanguard target retirement funds.
+    /// Stock is at 90% until 25 years before retirement and then decreases to 60% at 5 years
+    /// before retirement.  Inflation protection then rises to 18% and stock decreases to 32% by 5
+    /// years after retirement, which steps to 29% stock after that.
+    pub fn vanguard_default() -> Self {
+        GlidePath {
+            stock: vec![
+                (-5.0, 29.0),
+                (-5.0, 32.0),
+                (5.0, 60.0),
+                (30.0, 97.5),
+                (30.0, 90.0),
+            ],
+            inflation_protected: vec![(-5.0, 18.0), (5.0, 0.0)],
+        }
+    }
+
+    /// stock_percent returns the percent stock at the years until retirement
+    pub fn stock_percent(&self, years_to_retirement: f32) -> f32 {
+        interpolate(&self.stock, years_to_retirement)
+    }
+
+    /// inflation_protected_percent returns the percent inflation protected at the years until
+    /// retirement
+    pub fn inflation_protected_percent(&self, years_to_retirement: f32) -> f32 {
+        interpolate(&self.inflation_protected, years_to_retirement)
+    }
+}
+
+impl Default for GlidePath {
+    fn default() -> Self {
+        Self::vanguard_default()
+    }
+}
+
+// Linearly interpolates the percent at the years from the (years, percent) control points, which
+// are sorted by years.  Control points at the same year step to the later control point
+fn interpolate(points: &[(f32, f32)], years: f32) -> f32 {
+    match points.first() {
+        None => 0.0,
+        Some((first_years, first_percent)) if years < *first_years => *first_percent,
+        Some(_) => points
+            .windows(2)
+            .find(|window| window[0].0 <= years && years < window[1].0)
+            .map(|window| {
+                let (lower_years, lower_percent) = window[0];
+                let (upper_years, upper_percent) = window[1];
+                lower_percent
+                    + (upper_percent - lower_percent) * (years - lower_years)
+                        / (upper_years - lower_years)
+            })
+            .unwrap_or(points[points.len() - 1].1),
+    }
+}
+
+/// AssetClass is the sub allocation category that a stock symbol fills.  Each of the supported
+/// Vanguard ETFs fills one asset class and registered symbols declare which one they fill.
+#[derive(Clone, Copy, Eq, Hash, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum AssetClass {
+    UsStockLarge,
+    UsStockMid,
+    UsStockSmall,
+    UsTotalBond,
+    UsCorpBond,
+    IntTotalStock,
+    IntEmergingStock,
+    IntBond,
+    InflationProtected,
+}
+
+impl AssetClass {
+    /// list returns all asset classes in the order of the AssetClass enum
+    pub fn list() -> [AssetClass; 9] {
+        [
+            AssetClass::UsStockLarge,
+            AssetClass::UsStockMid,
+            AssetClass::UsStockSmall,
+            AssetClass::UsTotalBond,
+            AssetClass::UsCorpBond,
+            AssetClass::IntTotalStock,
+            AssetClass::IntEmergingStock,
+            AssetClass::IntBond,
+            AssetClass::InflationProtected,
+        ]
+    }
+
+    /// is_stock returns whether the asset class is a stock asset class
+    pub fn is_stock(&self) -> bool {
+        matches!(
+            self,
+            AssetClass::UsStockLarge
+                | AssetClass::UsStockMid
+                | AssetClass::UsStockSmall
+                | AssetClass::IntTotalStock
+                | AssetClass::IntEmergingStock
+        )
+    }
+
+    /// is_bond returns whether the asset class is a bond asset class.  Inflation protected
+    /// securities are not included, which matches how stock, bond, and inflation protected
+    /// percentages are split elsewhere.
+    pub fn is_bond(&self) -> bool {
+        matches!(
+            self,
+            AssetClass::UsTotalBond | AssetClass::UsCorpBond | AssetClass::IntBond
+        )
+    }
+}
+
+/// Holds the fraction of the total stock within each stock asset class and the fraction of the
+/// total bond within each bond asset class, which are used to split the Allocations into
+/// SubAllocations.  The stock fractions and the bond fractions each need to add up to 1.  The
+/// default splits the stock 2/3 US and 1/3 international and the bond 2/3 US and 1/3
+/// international.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SubAllocationWeights {
+    pub us_stock_large: f32,
+    pub us_stock_mid: f32,
+    pub us_stock_small: f32,
+    pub int_tot_stock: f32,
+    pub int_emerging_stock: f32,
+    pub us_tot_bond: f32,
+    pub us_corp_bond: f32,
+    pub int_bond: f32,
+}
+
+impl Default for SubAllocationWeights {
+    fn default() -> Self {
+        SubAllocationWeights {
+            us_stock_large: LARGE_US_STOCK,
+            us_stock_mid: SMALL_MID_US_STOCK,
+            us_stock_small: SMALL_MID_US_STOCK,
+            int_tot_stock: INT_TOTAL,
+            int_emerging_stock: INT_EMERGING,
+            us_tot_bond: US_TOT_BOND_FRACTION,
+            us_corp_bond: US_CORP_BOND_FRACTION,
+            int_bond: INT_BOND_FRACTION,
+        }
+    }
+}
+
 /// Holds the percentage of allocation for each type of stock, bond, etc.  splitting by US and
 /// international and other categories.
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct SubAllocations {
     pub us_stock_large: f32,
     pub us_stock_mid: f32,
@@ -147,14 +488,91 @@ impl SubAllocations {
     /// Divides the asset bond/stock allocations set by the Allocations struct into percentages for
     /// the SubAllocations of how much within international, domestic, bond, stock etc.
     pub fn new_custom(allocations: Allocations) -> Result<Self> {
-        let us_stock_large = allocations.total_stock() * LARGE_US_STOCK;
-        let us_stock_mid = allocations.total_stock() * SMALL_MID_US_STOCK;
-        let us_stock_small = allocations.total_stock() * SMALL_MID_US_STOCK;
-        let us_tot_bond = allocations.total_bond() * US_TOT_BOND_FRACTION;
-        let us_corp_bond = allocations.total_bond() * US_CORP_BOND_FRACTION;
-        let int_tot_stock = allocations.total_stock() * INT_TOTAL;
-        let int_emerging_stock = allocations.total_stock() * INT_EMERGING;
-        let int_bond = allocations.total_bond() * INT_BOND_FRACTION;
+        Self::new_custom_weighted(allocations, SubAllocationWeights::default())
+    }
+
+    /// Divides the asset bond/stock allocations set by the Allocations struct into percentages for
+    /// the SubAllocations with the fractions of the SubAllocationWeights, which allows tilting
+    /// the stock or bond split.  Errors when a weight is negative or when either the stock or the
+    /// bond weights do not add up to 1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::asset;
+    ///
+    /// // 50/50 US/international stock without emerging markets and without international bonds
+    /// let weights = asset::SubAllocationWeights {
+    ///     us_stock_large: 0.25,
+    ///     us_stock_mid: 0.125,
+    ///     us_stock_small: 0.125,
+    ///     int_tot_stock: 0.5,
+    ///     int_emerging_stock: 0.0,
+    ///     us_tot_bond: 0.5,
+    ///     us_corp_bond: 0.5,
+    ///     int_bond: 0.0,
+    /// };
+    /// let allocations = asset::Allocations::custom(60.0, 40.0, 0.0).unwrap();
+    /// let sub_allocations = asset::SubAllocations::new_custom_weighted(allocations, weights).unwrap();
+    /// assert_eq!(sub_allocations.int_tot_stock, 30.0);
+    /// assert_eq!(sub_allocations.int_emerging_stock, 0.0);
+    /// assert_eq!(sub_allocations.us_tot_bond, 20.0);
+    /// assert_eq!(sub_allocations.int_bond, 0.0);
+    ///
+    /// let default_weighted = asset::SubAllocations::new_custom_weighted(
+    ///     asset::Allocations::custom(60.0, 40.0, 0.0).unwrap(),
+    ///     asset::SubAllocationWeights::default(),
+    /// )
+    /// .unwrap();
+    /// let default_sub_allocations =
+    ///     asset::SubAllocations::new_custom(asset::Allocations::custom(60.0, 40.0, 0.0).unwrap())
+    ///         .unwrap();
+    /// assert_eq!(default_weighted.us_stock_large, default_sub_allocations.us_stock_large);
+    ///
+    /// let overweight = asset::SubAllocationWeights { int_tot_stock: 0.9, ..weights };
+    /// let allocations = asset::Allocations::custom(60.0, 40.0, 0.0).unwrap();
+    /// assert!(asset::SubAllocations::new_custom_weighted(allocations, overweight).is_err());
+    /// ```
+    pub fn new_custom_weighted(
+        allocations: Allocations,
+        weights: SubAllocationWeights,
+    ) -> Result<Self> {
+        ensure!(
+            [
+                weights.us_stock_large,
+                weights.us_stock_mid,
+                weights.us_stock_small,
+                weights.int_tot_stock,
+                weights.int_emerging_stock,
+                weights.us_tot_bond,
+                weights.us_corp_bond,
+                weights.int_bond,
+            ]
+            .iter()
+            .all(|weight| *weight >= 0.0),
+            format!("Sub allocation weights can not be negative: {:?}", weights)
+        );
+        let stock_weight = weights.us_stock_large
+            + weights.us_stock_mid
+            + weights.us_stock_small
+            + weights.int_tot_stock
+            + weights.int_emerging_stock;
+        let bond_weight = weights.us_tot_bond + weights.us_corp_bond + weights.int_bond;
+        ensure!(
+            (0.999..1.001).contains(&stock_weight) && (0.999..1.001).contains(&bond_weight),
+            format!(
+                "Stock and bond sub allocation weights need to each add up to 1: {}, {}",
+                stock_weight, bond_weight
+            )
+        );
+        let us_stock_large = allocations.total_stock() * weights.us_stock_large;
+        let us_stock_mid = allocations.total_stock() * weights.us_stock_mid;
+        let us_stock_small = allocations.total_stock() * weights.us_stock_small;
+        let us_tot_bond = allocations.total_bond() * weights.us_tot_bond;
+        let us_corp_bond = allocations.total_bond() * weights.us_corp_bond;
+        let int_tot_stock = allocations.total_stock() * weights.int_tot_stock;
+        let int_emerging_stock = allocations.total_stock() * weights.int_emerging_stock;
+        let int_bond = allocations.total_bond() * weights.int_bond;
         let inflation_protected = allocations.total_inflation_protected();
         let sum = us_stock_large
             + us_stock_mid
@@ -181,4 +599,96 @@ impl SubAllocations {
             inflation_protected,
         })
     }
+    /// Creates a SubAllocations struct with an equal weight for each of the nine supported stock
+    /// symbols instead of the market capitalization weights.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset, holdings};
+    ///
+    /// let sub_allocations = asset::SubAllocations::equal_weight();
+    /// for stock_symbol in holdings::StockSymbol::list() {
+    ///     assert!((sub_allocations.symbol_percent(&stock_symbol) - 100.0 / 9.0).abs() < 0.001);
+    /// }
+    /// ```
+    pub fn equal_weight() -> Self {
+        let weight = 100.0 / 9.0;
+        SubAllocations {
+            us_stock_large: weight,
+            us_stock_mid: weight,
+            us_stock_small: weight,
+            us_tot_bond: weight,
+            us_corp_bond: weight,
+            int_tot_stock: weight,
+            int_emerging_stock: weight,
+            int_bond: weight,
+            inflation_protected: weight,
+        }
+    }
+
+    /// Creates a SubAllocations struct which keeps the stock, bond, and inflation protected split
+    /// of the Allocations struct, but weights each stock equally and each bond equally within
+    /// their split.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::asset;
+    ///
+    /// let allocations = asset::Allocations::custom(60.0, 40.0, 0.0).unwrap();
+    /// let sub_allocations = asset::SubAllocations::equal_weight_custom(allocations);
+    /// assert_eq!(sub_allocations.us_stock_large, 12.0);
+    /// assert_eq!(sub_allocations.int_emerging_stock, 12.0);
+    /// assert!((sub_allocations.us_tot_bond - 40.0 / 3.0).abs() < 0.001);
+    /// assert_eq!(sub_allocations.inflation_protected, 0.0);
+    /// ```
+    pub fn equal_weight_custom(allocations: Allocations) -> Self {
+        let stock_weight = allocations.total_stock() / 5.0;
+        let bond_weight = allocations.total_bond() / 3.0;
+        SubAllocations {
+            us_stock_large: stock_weight,
+            us_stock_mid: stock_weight,
+            us_stock_small: stock_weight,
+            us_tot_bond: bond_weight,
+            us_corp_bond: bond_weight,
+            int_tot_stock: stock_weight,
+            int_emerging_stock: stock_weight,
+            int_bond: bond_weight,
+            inflation_protected: allocations.total_inflation_protected(),
+        }
+    }
+
+    /// symbol_percent returns the percent of the total portfolio allocated to the stock symbol.
+    /// Symbols without an allocation, such as cash or other holdings, return 0.
+    pub fn symbol_percent(&self, stock_symbol: &StockSymbol) -> f32 {
+        match stock_symbol {
+            StockSymbol::VV => self.us_stock_large,
+            StockSymbol::VO => self.us_stock_mid,
+            StockSymbol::VB => self.us_stock_small,
+            StockSymbol::BND => self.us_tot_bond,
+            StockSymbol::VTC => self.us_corp_bond,
+            StockSymbol::VXUS => self.int_tot_stock,
+            StockSymbol::VWO => self.int_emerging_stock,
+            StockSymbol::BNDX => self.int_bond,
+            StockSymbol::VTIP => self.inflation_protected,
+            _ => 0.0,
+        }
+    }
+
+    /// asset_class_percent returns the percent of the total portfolio allocated to the asset
+    /// class.
+    pub fn asset_class_percent(&self, asset_class: AssetClass) -> f32 {
+        match asset_class {
+            AssetClass::UsStockLarge => self.us_stock_large,
+            AssetClass::UsStockMid => self.us_stock_mid,
+            AssetClass::UsStockSmall => self.us_stock_small,
+            AssetClass::UsTotalBond => self.us_tot_bond,
+            AssetClass::UsCorpBond => self.us_corp_bond,
+            AssetClass::IntTotalStock => self.int_tot_stock,
+            AssetClass::IntEmergingStock => self.int_emerging_stock,
+            AssetClass::IntBond => self.int_bond,
+            AssetClass::InflationProtected => self.inflation_protected,
+        }
+    }
 }
@@ -1,6 +1,8 @@
 use anyhow::{ensure, Context, Result};
+use chrono::{Datelike, Local, Months, NaiveDate, NaiveDateTime};
 use std::{
     collections::HashMap,
+    fmt,
     fs::File,
     io::{BufRead, BufReader},
     path::PathBuf,
@@ -8,7 +10,10 @@ use std::{
 
 use crate::{
     asset::{Allocations, SubAllocations},
-    holdings::{AccountHoldings, HoldingType, ShareValues, StockSymbol, VanguardRebalance},
+    holdings::{
+        AccountHoldings, CashPercents, HoldingType, Lot, LotStrategy, RebalanceFrequency,
+        RoundingMode, ShareValues, StockSymbol, Transaction, VanguardHoldings, VanguardRebalance,
+    },
 };
 
 const HIGH_TO_LOW_RISK: [StockSymbol; 9] = [
@@ -23,327 +28,1497 @@ const HIGH_TO_LOW_RISK: [StockSymbol; 9] = [
     StockSymbol::VTIP,
 ];
 
+// Most to least tax efficient, which is used to place assets within a taxable brokerage account.
+// International stock is first to use the foreign tax credit, followed by the low turnover US
+// stock index funds, with the bonds, whose interest is taxed as income, last
+const TAX_EFFICIENT_ORDER: [StockSymbol; 9] = [
+    StockSymbol::VXUS,
+    StockSymbol::VV,
+    StockSymbol::VWO,
+    StockSymbol::VO,
+    StockSymbol::VB,
+    StockSymbol::BNDX,
+    StockSymbol::VTIP,
+    StockSymbol::BND,
+    StockSymbol::VTC,
+];
+
+// Default long term annual market assumptions, as fractions, used to estimate the returns of the
+// stock/bond mix
+const RISK_FREE_RATE: f32 = 0.04;
+const EQUITY_PREMIUM: f32 = 0.03;
+const EXPECTED_BOND_RETURN: f32 = 0.03;
+const EXPECTED_INFLATION: f32 = 0.025;
+
+/// MarketAssumptions holds the long term annual market assumptions, as fractions, which are shared
+/// by the projection and return functions.  Stocks are expected to return the risk free rate plus
+/// the equity premium.  The default expects a 7% stock return, a 3% bond return, and 2.5%
+/// inflation.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MarketAssumptions {
+    pub risk_free: f32,
+    pub equity_premium: f32,
+    pub bond_return: f32,
+    pub inflation: f32,
+}
+
+impl MarketAssumptions {
+    /// stock_return returns the expected annual stock return, which is the risk free rate plus the
+    /// equity premium
+    pub fn stock_return(&self) -> f32 {
+        self.risk_free + self.equity_premium
+    }
+
+    /// portfolio_return returns the expected annual return of a portfolio with the percent stock
+    /// and the rest in bonds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::calc::MarketAssumptions;
+    ///
+    /// let assumptions = MarketAssumptions::default();
+    /// assert!((assumptions.portfolio_return(100.0) - 0.07).abs() < 0.0001);
+    /// assert!((assumptions.portfolio_return(50.0) - 0.05).abs() < 0.0001);
+    /// ```
+    pub fn portfolio_return(&self, percent_stock: f32) -> f32 {
+        let stock_fraction = percent_stock / 100.0;
+        stock_fraction * self.stock_return() + (1.0 - stock_fraction) * self.bond_return
+    }
+
+    /// sharpe_ratio returns the expected return of a portfolio with the percent stock over the risk
+    /// free rate, divided by the annual volatility.  Returns 0 when there is no volatility.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::calc::MarketAssumptions;
+    ///
+    /// let assumptions = MarketAssumptions::default();
+    /// assert!((assumptions.sharpe_ratio(100.0, 0.15) - 0.2).abs() < 0.0001);
+    ///
+    /// let higher_rates = MarketAssumptions { risk_free: 0.05, ..assumptions };
+    /// assert!(higher_rates.sharpe_ratio(60.0, 0.1) < assumptions.sharpe_ratio(60.0, 0.1));
+    /// assert_eq!(assumptions.sharpe_ratio(60.0, 0.0), 0.0);
+    /// ```
+    pub fn sharpe_ratio(&self, percent_stock: f32, volatility: f32) -> f32 {
+        if volatility <= 0.0 {
+            0.0
+        } else {
+            (self.portfolio_return(percent_stock) - self.risk_free) / volatility
+        }
+    }
+}
+
+impl Default for MarketAssumptions {
+    fn default() -> Self {
+        MarketAssumptions {
+            risk_free: RISK_FREE_RATE,
+            equity_premium: EQUITY_PREMIUM,
+            bond_return: EXPECTED_BOND_RETURN,
+            inflation: EXPECTED_INFLATION,
+        }
+    }
+}
+
+/// Default percent that the target of an account may differ from the account total before the
+/// rebalance errors with a ReconciliationError
+pub const RECONCILIATION_TOLERANCE: f32 = 1.0;
+
+/// ReconciliationError is returned by the rebalance when the target filled for an account from the
+/// available household target, before any remainder is kept within cash, does not match the
+/// account total within the reconciliation tolerance.  The mismatch is the target minus the total,
+/// in dollars.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ReconciliationError {
+    pub account: HoldingType,
+    pub target: f32,
+    pub total: f32,
+    pub tolerance: f32,
+}
+
+impl ReconciliationError {
+    /// mismatch returns the dollars that the target is over the account total, which is negative
+    /// when the target is under the total.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{calc::ReconciliationError, holdings::HoldingType};
+    ///
+    /// let error = anyhow::Error::from(ReconciliationError {
+    ///     account: HoldingType::RothIra,
+    ///     target: 9800.0,
+    ///     total: 10000.0,
+    ///     tolerance: 1.0,
+    /// });
+    /// let reconciliation = error.downcast_ref::<ReconciliationError>().unwrap();
+    /// assert_eq!(reconciliation.mismatch(), -200.0);
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "RothIra target of $9800.00 does not match the account total of $10000.00.  The mismatch of $-200.00 is more than the 1% tolerance"
+    /// );
+    /// ```
+    pub fn mismatch(&self) -> f32 {
+        self.target - self.total
+    }
+}
+
+impl fmt::Display for ReconciliationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} target of ${:.2} does not match the account total of ${:.2}.  The mismatch of ${:.2} is more than the {}% tolerance",
+            self.account,
+            self.target,
+            self.total,
+            self.mismatch(),
+            self.tolerance
+        )
+    }
+}
+
+impl std::error::Error for ReconciliationError {}
+
+/// AccountInputs holds the current holdings of an account along with the cash, and the outside US
+/// and international stock and bond values, that are added to the account for the rebalance.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct AccountInputs {
+    pub holdings: ShareValues,
+    pub cash_add: f32,
+    pub us_stock_add: f32,
+    pub us_bond_add: f32,
+    pub int_stock_add: f32,
+    pub int_bond_add: f32,
+}
+
+impl AccountInputs {
+    /// new creates the AccountInputs of the holdings without any added cash or outside values
+    pub fn new(holdings: ShareValues) -> Self {
+        AccountInputs {
+            holdings,
+            ..Default::default()
+        }
+    }
+
+    // Returns the holdings with the added cash within the cash symbol
+    fn holdings_with_cash(&self, cash_symbol: &StockSymbol) -> Result<ShareValues> {
+        let mut holdings = self.holdings;
+        holdings.add_stock_value(
+            cash_symbol.clone(),
+            holdings.stock_value(cash_symbol.clone())? + self.cash_add,
+        )?;
+        Ok(holdings)
+    }
+}
+
+/// RebalanceOptions holds the accounts and settings used by to_buy.  The percent_stock is the
+/// stock allocation of a brokerage that is not part of the retirement allocation, while the
+/// retirement accounts follow the glide path of the retirement_year.  With
+/// use_brokerage_retirement the brokerage shares the retirement allocation, and with
+/// tax_aware_brokerage it is also filled with the most tax efficient assets first.  The
+/// brokerage_cost_basis is the cost basis of each brokerage holding, used for the wash sale
+/// warnings.  Purchases and sales worth less than the rebalance_threshold dollars are dropped,
+/// the remaining shares are rounded with the rounding_mode, and the cash_percents keep that
+/// percent of each account within cash.  Cash is added to and held within the cash_symbol, which is
+/// VMFXX or another money market fund.  The household_target locks the household retirement target
+/// to a precomputed target, such as the retirement target of an earlier rebalance, so that only the
+/// placement within the accounts is redone.  The reconciliation_tolerance is the percent that the
+/// roth IRA, HSA, and brokerage targets filled from the household target may differ from the
+/// account totals before a ReconciliationError is returned, with any remainder within the tolerance
+/// kept in cash.  The placement_preferences pin stock symbols to the account type that they are
+/// preferred within.  The default has no accounts, a 60% stock brokerage, the current year as the
+/// retirement year, no threshold, fractional shares, no cash reserves, VMFXX as the cash symbol,
+/// and the RECONCILIATION_TOLERANCE.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RebalanceOptions {
+    pub percent_stock: f32,
+    pub retirement_year: i32,
+    pub brokerage: AccountInputs,
+    pub roth_ira: AccountInputs,
+    pub traditional_ira: AccountInputs,
+    /// hsa is treated like the roth IRA and is filled with the highest risk assets remaining after
+    /// the roth IRA
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{calc, holdings};
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let mut traditional = holdings::ShareValues::new();
+    /// traditional.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+    /// let mut roth = holdings::ShareValues::new();
+    /// roth.add_stock_value(holdings::StockSymbol::VMFXX, 1000.0).unwrap();
+    /// let mut hsa = holdings::ShareValues::new();
+    /// hsa.add_stock_value(holdings::StockSymbol::VMFXX, 1000.0).unwrap();
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2050,
+    ///     roth_ira: calc::AccountInputs::new(roth),
+    ///     traditional_ira: calc::AccountInputs::new(traditional),
+    ///     hsa: calc::AccountInputs::new(hsa),
+    ///     ..Default::default()
+    /// };
+    /// let hsa_target = calc::to_buy(&options, quotes).unwrap().hsa.target;
+    /// assert!((hsa_target.total_value() - 1000.0).abs() < 0.1);
+    /// assert_eq!(hsa_target.stock_value(holdings::StockSymbol::BND).unwrap(), 0.0);
+    /// let (hsa_stock_percent, _, _) = hsa_target.percent_stock_bond_infl();
+    /// assert!(hsa_stock_percent > 99.9);
+    ///
+    /// // An HSA holding most of the retirement money is filled past the stock into the bonds
+    /// let mut hsa = holdings::ShareValues::new();
+    /// hsa.add_stock_value(holdings::StockSymbol::VMFXX, 95000.0).unwrap();
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2050,
+    ///     traditional_ira: calc::AccountInputs::new(traditional),
+    ///     hsa: calc::AccountInputs::new(hsa),
+    ///     ..Default::default()
+    /// };
+    /// let rebalance = calc::to_buy(&options, quotes).unwrap();
+    /// assert!((rebalance.hsa.target.total_value() - 95000.0).abs() < 0.1);
+    /// let (_, hsa_bond_percent, _) = rebalance.hsa.target.percent_stock_bond_infl();
+    /// assert!(hsa_bond_percent > 0.0);
+    /// assert!((rebalance.traditional_ira.target.total_value() - 10000.0).abs() < 0.1);
+    /// ```
+    pub hsa: AccountInputs,
+    pub use_brokerage_retirement: bool,
+    pub tax_aware_brokerage: bool,
+    /// brokerage_cost_basis is the cost basis of each brokerage holding, which lists the symbols
+    /// the brokerage sells at a loss while another account buys them within the wash_sale_warnings
+    /// of the brokerage
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{calc, holdings};
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let mut traditional = holdings::ShareValues::new();
+    /// traditional.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+    /// let mut brokerage = holdings::ShareValues::new();
+    /// brokerage.add_stock_value(holdings::StockSymbol::VV, 10000.0).unwrap();
+    ///
+    /// // VV is sold at a loss within the brokerage while the traditional IRA buys VV
+    /// let mut cost_basis = holdings::ShareValues::new();
+    /// cost_basis.add_stock_value(holdings::StockSymbol::VV, 12000.0).unwrap();
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2070,
+    ///     traditional_ira: calc::AccountInputs::new(traditional),
+    ///     brokerage: calc::AccountInputs::new(brokerage),
+    ///     brokerage_cost_basis: cost_basis,
+    ///     ..Default::default()
+    /// };
+    /// let rebalance = calc::to_buy(&options, quotes).unwrap();
+    /// assert_eq!(rebalance.brokerage.wash_sale_warnings, vec![holdings::StockSymbol::VV]);
+    ///
+    /// // Selling at a gain does not trigger the wash sale rules
+    /// cost_basis.add_stock_value(holdings::StockSymbol::VV, 8000.0).unwrap();
+    /// let options = calc::RebalanceOptions {
+    ///     brokerage_cost_basis: cost_basis,
+    ///     ..options
+    /// };
+    /// let rebalance = calc::to_buy(&options, quotes).unwrap();
+    /// assert!(rebalance.brokerage.wash_sale_warnings.is_empty());
+    /// ```
+    pub brokerage_cost_basis: ShareValues,
+    /// rebalance_threshold drops the purchases and sales worth less than this many dollars, which
+    /// are kept within cash.  A dropped sale that was paying for a purchase cuts the purchase back
+    /// by the same amount so that the purchases are still paid for.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset, calc, holdings};
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let sixty_forty = asset::SubAllocations::new().unwrap();
+    /// let balanced = holdings::ShareValues::new_target(sixty_forty, 10000.0, 0.0, 0.0, 0.0, 0.0);
+    /// let vv = balanced.stock_value(holdings::StockSymbol::VV).unwrap();
+    /// let vo = balanced.stock_value(holdings::StockSymbol::VO).unwrap();
+    /// let bnd = balanced.stock_value(holdings::StockSymbol::BND).unwrap();
+    /// let bndx = balanced.stock_value(holdings::StockSymbol::BNDX).unwrap();
+    ///
+    /// // Trades under $50 are dropped while the larger trades are kept
+    /// let mut brokerage = balanced;
+    /// brokerage.add_stock_value(holdings::StockSymbol::VV, vv + 30.0).unwrap();
+    /// brokerage.add_stock_value(holdings::StockSymbol::VO, vo - 30.0).unwrap();
+    /// brokerage.add_stock_value(holdings::StockSymbol::BND, bnd + 1000.0).unwrap();
+    /// brokerage.add_stock_value(holdings::StockSymbol::BNDX, bndx - 1000.0).unwrap();
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2070,
+    ///     brokerage: calc::AccountInputs::new(brokerage),
+    ///     rebalance_threshold: 50.0,
+    ///     ..Default::default()
+    /// };
+    /// let purchases = calc::to_buy(&options, quotes).unwrap().brokerage.sale_purchases_needed;
+    /// assert_eq!(purchases.stock_value(holdings::StockSymbol::VV).unwrap(), 0.0);
+    /// assert_eq!(purchases.stock_value(holdings::StockSymbol::VO).unwrap(), 0.0);
+    /// assert!((purchases.stock_value(holdings::StockSymbol::BND).unwrap() + 1000.0).abs() < 0.1);
+    /// assert!((purchases.stock_value(holdings::StockSymbol::BNDX).unwrap() - 1000.0).abs() < 0.1);
+    /// assert!(purchases.stock_value(holdings::StockSymbol::VMFXX).unwrap().abs() < 0.1);
+    ///
+    /// // The dropped $40 VV sale is paired with $40 less of the BND purchase it would have paid for
+    /// let mut brokerage = balanced;
+    /// brokerage.add_stock_value(holdings::StockSymbol::VV, vv + 40.0).unwrap();
+    /// brokerage.add_stock_value(holdings::StockSymbol::BND, bnd - 1040.0).unwrap();
+    /// brokerage.add_stock_value(holdings::StockSymbol::BNDX, bndx + 1000.0).unwrap();
+    /// let options = calc::RebalanceOptions {
+    ///     brokerage: calc::AccountInputs::new(brokerage),
+    ///     ..options
+    /// };
+    /// let adjusted = calc::to_buy(&options, quotes).unwrap().brokerage;
+    /// let purchases = adjusted.sale_purchases_needed;
+    /// assert_eq!(purchases.stock_value(holdings::StockSymbol::VV).unwrap(), 0.0);
+    /// assert!((purchases.stock_value(holdings::StockSymbol::BND).unwrap() - 1000.0).abs() < 0.1);
+    /// assert!((purchases.stock_value(holdings::StockSymbol::BNDX).unwrap() + 1000.0).abs() < 0.1);
+    /// assert!(adjusted.target.stock_value(holdings::StockSymbol::VMFXX).unwrap() > -0.01);
+    /// assert!((adjusted.target.total_value() - 10000.0).abs() < 0.1);
+    /// ```
+    pub rebalance_threshold: f32,
+    pub rounding_mode: RoundingMode,
+    /// cash_percents keeps the percent of each account within cash instead of being invested
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{calc, holdings};
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let mut traditional = holdings::ShareValues::new();
+    /// traditional.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+    /// let mut brokerage = holdings::ShareValues::new();
+    /// brokerage.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+    ///
+    /// // Keep 1% cash within the brokerage while the IRAs stay fully invested
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2050,
+    ///     traditional_ira: calc::AccountInputs::new(traditional),
+    ///     brokerage: calc::AccountInputs::new(brokerage),
+    ///     cash_percents: holdings::CashPercents::new(1.0, 0.0, 0.0, 0.0).unwrap(),
+    ///     ..Default::default()
+    /// };
+    /// let rebalance = calc::to_buy(&options, quotes).unwrap();
+    /// let brokerage_target = rebalance.brokerage.target;
+    /// assert!((brokerage_target.stock_value(holdings::StockSymbol::VMFXX).unwrap() - 100.0).abs() < 0.1);
+    /// assert!((brokerage_target.total_value() - 10000.0).abs() < 0.1);
+    /// let (stock_percent, bond_percent, _) = brokerage_target.percent_stock_bond_infl();
+    /// assert!((stock_percent - 60.0).abs() < 0.1 && (bond_percent - 40.0).abs() < 0.1);
+    /// let traditional_target = rebalance.traditional_ira.target;
+    /// assert_eq!(traditional_target.stock_value(holdings::StockSymbol::VMFXX).unwrap(), 0.0);
+    /// assert!(holdings::CashPercents::new(101.0, 0.0, 0.0, 0.0).is_err());
+    /// ```
+    pub cash_percents: CashPercents,
+    /// cash_symbol is the money market fund that cash is added to and held within
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{calc, holdings};
+    ///
+    /// // Cash is added to the settlement fund of the account when it is not held within VMFXX, and
+    /// // the money market fund is held as cash rather than with the other holdings
+    /// let cash_fund = holdings::StockSymbol::new("VMRXX");
+    /// let mut brokerage = holdings::ShareValues::new();
+    /// brokerage.add_stock_value(holdings::StockSymbol::VV, 9000.0).unwrap();
+    /// brokerage.add_stock_value(cash_fund.clone(), 1000.0).unwrap();
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2050,
+    ///     brokerage: calc::AccountInputs {
+    ///         cash_add: 500.0,
+    ///         ..calc::AccountInputs::new(brokerage)
+    ///     },
+    ///     cash_symbol: cash_fund.clone(),
+    ///     ..Default::default()
+    /// };
+    /// let rebalance = calc::to_buy(&options, holdings::ShareValues::new_quote()).unwrap();
+    /// assert_eq!(rebalance.brokerage.current.stock_value(cash_fund.clone()).unwrap(), 1500.0);
+    /// let other = holdings::StockSymbol::Other(String::new());
+    /// assert_eq!(rebalance.brokerage.current.stock_value(other.clone()).unwrap(), 0.0);
+    /// assert_eq!(rebalance.brokerage.target.stock_value(other).unwrap(), 0.0);
+    /// assert_eq!(rebalance.brokerage.target.total_value(), 10500.0);
+    ///
+    /// // Only VMFXX and other money market funds can hold the cash
+    /// let options = calc::RebalanceOptions {
+    ///     cash_symbol: holdings::StockSymbol::Other("VMRXX".to_string()),
+    ///     ..options
+    /// };
+    /// assert!(calc::to_buy(&options, holdings::ShareValues::new_quote()).is_err());
+    /// ```
+    pub cash_symbol: StockSymbol,
+    /// household_target locks the household retirement target so that only the placement within
+    /// the accounts is redone
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{calc, holdings};
+    ///
+    /// // Swap which account is the roth IRA while keeping the household target locked
+    /// let mut first = holdings::ShareValues::new();
+    /// first.add_stock_value(holdings::StockSymbol::VMFXX, 3000.0).unwrap();
+    /// let mut second = holdings::ShareValues::new();
+    /// second.add_stock_value(holdings::StockSymbol::VMFXX, 7000.0).unwrap();
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2050,
+    ///     roth_ira: calc::AccountInputs::new(first),
+    ///     traditional_ira: calc::AccountInputs::new(second),
+    ///     ..Default::default()
+    /// };
+    /// let household_target = calc::to_buy(&options, quotes).unwrap().retirement_target();
+    /// let swapped_options = calc::RebalanceOptions {
+    ///     roth_ira: calc::AccountInputs::new(second),
+    ///     traditional_ira: calc::AccountInputs::new(first),
+    ///     household_target: Some(household_target),
+    ///     ..options
+    /// };
+    /// let swapped = calc::to_buy(&swapped_options, quotes).unwrap();
+    /// assert_eq!(swapped.retirement_target(), household_target);
+    /// assert!((swapped.roth_ira.target.total_value() - 7000.0).abs() < 0.1);
+    /// assert!((swapped.traditional_ira.target.total_value() - 3000.0).abs() < 0.1);
+    ///
+    /// // A roth IRA larger than the targets of all of the risk assets keeps the rest within cash
+    /// let mut locked_target = holdings::ShareValues::new();
+    /// locked_target.add_stock_value(holdings::StockSymbol::VV, 8000.0).unwrap();
+    /// locked_target.add_stock_value(holdings::StockSymbol::VTIP, 1000.0).unwrap();
+    /// locked_target.add_stock_value(holdings::StockSymbol::VMFXX, 1000.0).unwrap();
+    /// let mut roth = holdings::ShareValues::new();
+    /// roth.add_stock_value(holdings::StockSymbol::VV, 9500.0).unwrap();
+    /// let mut traditional = holdings::ShareValues::new();
+    /// traditional.add_stock_value(holdings::StockSymbol::VMFXX, 500.0).unwrap();
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2050,
+    ///     roth_ira: calc::AccountInputs::new(roth),
+    ///     traditional_ira: calc::AccountInputs::new(traditional),
+    ///     household_target: Some(locked_target),
+    ///     ..Default::default()
+    /// };
+    /// let large_roth = calc::to_buy(&options, quotes).unwrap();
+    /// assert_eq!(large_roth.roth_ira.target.stock_value(holdings::StockSymbol::VV).unwrap(), 8000.0);
+    /// assert_eq!(large_roth.roth_ira.target.stock_value(holdings::StockSymbol::VTIP).unwrap(), 1000.0);
+    /// assert_eq!(large_roth.roth_ira.target.stock_value(holdings::StockSymbol::VMFXX).unwrap(), 500.0);
+    /// assert_eq!(large_roth.traditional_ira.target.stock_value(holdings::StockSymbol::VMFXX).unwrap(), 500.0);
+    /// ```
+    pub household_target: Option<ShareValues>,
+    /// reconciliation_tolerance is the percent that the account targets filled from the household
+    /// target may differ from the account totals before a ReconciliationError is returned
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{calc, holdings};
+    ///
+    /// // A locked target $50 short of the roth IRA is kept within cash inside of the tolerance
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let mut roth = holdings::ShareValues::new();
+    /// roth.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+    /// let mut short_target = holdings::ShareValues::new();
+    /// short_target.add_stock_value(holdings::StockSymbol::VV, 9950.0).unwrap();
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2050,
+    ///     roth_ira: calc::AccountInputs::new(roth),
+    ///     household_target: Some(short_target),
+    ///     ..Default::default()
+    /// };
+    /// let rebalance = calc::to_buy(&options, quotes).unwrap();
+    /// assert_eq!(rebalance.roth_ira.target.stock_value(holdings::StockSymbol::VMFXX).unwrap(), 50.0);
+    ///
+    /// // Without a tolerance the unfilled $50 is a reconciliation error instead
+    /// let options = calc::RebalanceOptions {
+    ///     reconciliation_tolerance: 0.0,
+    ///     ..options
+    /// };
+    /// let error = calc::to_buy(&options, quotes).unwrap_err();
+    /// let reconciliation = error.downcast_ref::<calc::ReconciliationError>().unwrap();
+    /// assert_eq!(reconciliation.account, holdings::HoldingType::RothIra);
+    /// assert_eq!(reconciliation.mismatch(), -50.0);
+    /// ```
+    pub reconciliation_tolerance: f32,
+    /// placement_preferences pin stock symbols to the account type that they are preferred within,
+    /// which are filled before falling back to the risk ordering
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use vapore_gui::{calc, holdings};
+    ///
+    /// // Prefer the inflation protected securities within the traditional IRA over the brokerage,
+    /// // which would otherwise be filled with them first as the lowest risk asset
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let mut roth = holdings::ShareValues::new();
+    /// roth.add_stock_value(holdings::StockSymbol::VMFXX, 40000.0).unwrap();
+    /// let mut traditional = holdings::ShareValues::new();
+    /// traditional.add_stock_value(holdings::StockSymbol::VMFXX, 50000.0).unwrap();
+    /// let mut brokerage = holdings::ShareValues::new();
+    /// brokerage.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2000,
+    ///     roth_ira: calc::AccountInputs::new(roth),
+    ///     traditional_ira: calc::AccountInputs::new(traditional),
+    ///     brokerage: calc::AccountInputs::new(brokerage),
+    ///     use_brokerage_retirement: true,
+    ///     ..Default::default()
+    /// };
+    /// let default_rebalance = calc::to_buy(&options, quotes).unwrap();
+    /// let vtip_target = default_rebalance.retirement_target().stock_value(holdings::StockSymbol::VTIP).unwrap();
+    /// assert!(default_rebalance.brokerage.target.stock_value(holdings::StockSymbol::VTIP).unwrap() > 0.0);
+    ///
+    /// let options = calc::RebalanceOptions {
+    ///     placement_preferences: HashMap::from([
+    ///         (holdings::StockSymbol::VTIP, holdings::HoldingType::TraditionalIra),
+    ///         (holdings::StockSymbol::VTC, holdings::HoldingType::TraditionalIra),
+    ///         (holdings::StockSymbol::VWO, holdings::HoldingType::RothIra),
+    ///     ]),
+    ///     ..options
+    /// };
+    /// let rebalance = calc::to_buy(&options, quotes).unwrap();
+    /// assert_eq!(rebalance.brokerage.target.stock_value(holdings::StockSymbol::VTIP).unwrap(), 0.0);
+    /// assert_eq!(rebalance.brokerage.target.stock_value(holdings::StockSymbol::VTC).unwrap(), 0.0);
+    /// assert!((rebalance.traditional_ira.target.stock_value(holdings::StockSymbol::VTIP).unwrap() - vtip_target).abs() < 0.1);
+    /// assert!((rebalance.brokerage.target.total_value() - 10000.0).abs() < 0.1);
+    /// assert_eq!(rebalance.retirement_target(), default_rebalance.retirement_target());
+    /// ```
+    pub placement_preferences: HashMap<StockSymbol, HoldingType>,
+}
+
+impl Default for RebalanceOptions {
+    fn default() -> Self {
+        RebalanceOptions {
+            percent_stock: 60.0,
+            retirement_year: Local::now().year(),
+            brokerage: AccountInputs::default(),
+            roth_ira: AccountInputs::default(),
+            traditional_ira: AccountInputs::default(),
+            hsa: AccountInputs::default(),
+            use_brokerage_retirement: false,
+            tax_aware_brokerage: false,
+            brokerage_cost_basis: ShareValues::new(),
+            rebalance_threshold: 0.0,
+            rounding_mode: RoundingMode::default(),
+            cash_percents: CashPercents::default(),
+            cash_symbol: StockSymbol::VMFXX,
+            household_target: None,
+            reconciliation_tolerance: RECONCILIATION_TOLERANCE,
+            placement_preferences: HashMap::new(),
+        }
+    }
+}
+
 /// to_buy calculates how much of each stock and bond should be bought and sold to rebalance the
-/// portfolio.
-pub fn to_buy(
-    percent_stock: f32,
-    brokerage_cash_add: f32,
-    brokerage_us_stock_add: f32,
-    brokerage_int_stock_add: f32,
-    brokerage_us_bond_add: f32,
-    brokerage_int_bond_add: f32,
-    retirement_year: i32,
-    roth_holdings: ShareValues,
-    roth_us_stock_add: f32,
-    roth_us_bond_add: f32,
-    roth_int_stock_add: f32,
-    roth_int_bond_add: f32,
-    roth_cash_add: f32,
-    traditional_holdings: ShareValues,
-    traditional_us_stock_add: f32,
-    traditional_us_bond_add: f32,
-    traditional_int_stock_add: f32,
-    traditional_int_bond_add: f32,
-    traditional_cash_add: f32,
-    use_brokerage_retirement: bool,
-    brokerage_holdings: ShareValues,
-    stock_quotes: ShareValues,
-) -> Result<VanguardRebalance> {
+/// portfolio with the options.  When tax_aware_brokerage is set, the brokerage shares the
+/// retirement allocation while being filled with the most tax efficient assets first.  Dropped
+/// purchases and sales, from the rebalance threshold, are kept within cash.  The brokerage
+/// wash_sale_warnings list the symbols the brokerage sells at a loss, from the brokerage cost
+/// basis, while another account buys the same symbol.  The HSA is treated like the roth IRA and
+/// is filled with the highest risk assets remaining after the roth IRA.  Placement preferences
+/// are filled before falling back to the risk ordering, and without preferences the placement is
+/// by risk alone.  Examples of each option are with the fields of the RebalanceOptions.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::{calc, holdings};
+///
+/// let mut traditional = holdings::ShareValues::new();
+/// traditional.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+/// let mut brokerage = holdings::ShareValues::new();
+/// brokerage.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+/// let quotes = holdings::ShareValues::new_quote();
+///
+/// let options = calc::RebalanceOptions {
+///     retirement_year: 2070,
+///     traditional_ira: calc::AccountInputs::new(traditional),
+///     brokerage: calc::AccountInputs::new(brokerage),
+///     tax_aware_brokerage: true,
+///     ..Default::default()
+/// };
+/// let rebalance = calc::to_buy(&options, quotes).unwrap();
+///
+/// // The 90% stock household allocation places the tax efficient stock in the brokerage
+/// let brokerage_target = rebalance.brokerage.target;
+/// assert!((brokerage_target.stock_value(holdings::StockSymbol::VXUS).unwrap() - 4000.0).abs() < 0.1);
+/// assert!((brokerage_target.stock_value(holdings::StockSymbol::VV).unwrap() - 6000.0).abs() < 0.1);
+/// assert_eq!(brokerage_target.stock_value(holdings::StockSymbol::BND).unwrap(), 0.0);
+/// let traditional_target = rebalance.traditional_ira.target;
+/// assert!((traditional_target.stock_value(holdings::StockSymbol::BND).unwrap() - 666.67).abs() < 0.1);
+/// assert!((traditional_target.stock_value(holdings::StockSymbol::VWO).unwrap() - 2000.0).abs() < 0.1);
+/// ```
+pub fn to_buy(options: &RebalanceOptions, stock_quotes: ShareValues) -> Result<VanguardRebalance> {
+    ensure!(
+        options.cash_symbol.is_cash(),
+        "Cash symbol {:?} is not VMFXX or another money market fund",
+        options.cash_symbol
+    );
     let mut rebalance = VanguardRebalance::new();
-    let (
-        traditional_ira_account_option,
-        roth_ira_account_option,
-        brokerage_account_option,
-        target_overall_retirement_option,
-    ) = retirement_calc(
-        retirement_year,
-        roth_holdings,
-        roth_us_stock_add,
-        roth_us_bond_add,
-        roth_int_stock_add,
-        roth_int_bond_add,
-        roth_cash_add,
-        traditional_holdings,
-        traditional_us_stock_add,
-        traditional_us_bond_add,
-        traditional_int_stock_add,
-        traditional_int_bond_add,
-        traditional_cash_add,
-        use_brokerage_retirement,
-        brokerage_holdings,
-        brokerage_us_stock_add,
-        brokerage_us_bond_add,
-        brokerage_int_stock_add,
-        brokerage_int_bond_add,
-        brokerage_cash_add,
-        stock_quotes,
-    )?;
-    if let Some(traditional_account) = traditional_ira_account_option {
-        rebalance.add_account_holdings(traditional_account, HoldingType::TraditionalIra)
-    }
-    if let Some(roth_account) = roth_ira_account_option {
-        rebalance.add_account_holdings(roth_account, HoldingType::RothIra)
-    }
-    if let Some(brokerage_account) = brokerage_account_option {
-        rebalance.add_account_holdings(brokerage_account, HoldingType::Brokerage)
-    } else if brokerage_holdings.total_value() != 0.0 {
-        rebalance.add_account_holdings(
-            brokerage_calc(
-                stock_quotes,
-                brokerage_holdings,
-                percent_stock,
-                brokerage_cash_add,
-                brokerage_us_stock_add,
-                brokerage_int_stock_add,
-                brokerage_us_bond_add,
-                brokerage_int_bond_add,
-            )?,
+    let placement = retirement_calc(options, stock_quotes)?;
+    rebalance.add_brokerage_in_retirement(placement.brokerage.is_some());
+    // A brokerage outside of the retirement allocation is rebalanced with its own percent stock
+    let brokerage_account = match placement.brokerage {
+        Some(brokerage_account) => Some(brokerage_account),
+        None if options.brokerage.holdings.total_value() != 0.0 => Some(brokerage_calc(
+            stock_quotes,
+            &options.brokerage,
+            options.percent_stock,
+            &options.cash_symbol,
+        )?),
+        None => None,
+    };
+    let accounts = [
+        (
+            placement.traditional_ira,
+            options.cash_percents.traditional_ira,
+            HoldingType::TraditionalIra,
+        ),
+        (
+            placement.roth_ira,
+            options.cash_percents.roth_ira,
+            HoldingType::RothIra,
+        ),
+        (placement.hsa, options.cash_percents.hsa, HoldingType::Hsa),
+        (
+            brokerage_account,
+            options.cash_percents.brokerage,
             HoldingType::Brokerage,
-        )
+        ),
+    ];
+    for (account_option, cash_percent, holding_type) in accounts {
+        if let Some(account) = account_option {
+            rebalance.add_account_holdings(
+                adjust_trades(
+                    reserve_cash(
+                        account,
+                        cash_percent,
+                        stock_quotes,
+                        options.cash_symbol.clone(),
+                    )?,
+                    options.rebalance_threshold,
+                    options.rounding_mode,
+                    stock_quotes,
+                    options.cash_symbol.clone(),
+                )?,
+                holding_type,
+            )
+        }
     }
-    if let Some(target_overall_retirement) = target_overall_retirement_option {
+    if let Some(target_overall_retirement) = placement.retirement_target {
         rebalance.add_retirement_target(target_overall_retirement);
     }
+    if let Some((allocation_used, sub_allocations)) = placement.allocation_used {
+        rebalance.add_allocation_used(allocation_used, sub_allocations);
+    }
+    rebalance.brokerage.wash_sale_warnings =
+        wash_sale_warnings(&rebalance, &options.brokerage_cost_basis)?;
     Ok(rebalance)
 }
 
+/// liquidate_plan creates the AccountHoldings which sell every holding of an account into the cash
+/// symbol, such as before transferring the account to another custodian.  This is separate from
+/// the rebalance and the target allocation is not used, so the target is the full value of the
+/// account within the cash symbol and the sales/purchases needed are the shares to sell of each
+/// holding.  Outside stock and bond values are not held within the account and are left as is.
+///
+/// # Errors
+///
+/// Errors when the cash symbol is not VMFXX or another money market fund
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::{calc, holdings::{ShareValues, StockSymbol}};
+///
+/// let mut quotes = ShareValues::new_quote();
+/// quotes.add_stock_value(StockSymbol::VV, 200.0).unwrap();
+/// quotes.add_stock_value(StockSymbol::BND, 80.0).unwrap();
+///
+/// let mut current = ShareValues::new();
+/// current.add_stock_value(StockSymbol::VV, 6000.0).unwrap();
+/// current.add_stock_value(StockSymbol::BND, 3000.0).unwrap();
+/// current.add_stock_value(StockSymbol::new("VMRXX"), 500.0).unwrap();
+/// current.add_stock_value(StockSymbol::Other("VTSAX".to_string()), 500.0).unwrap();
+/// current.add_outside_stock_value(20000.0);
+///
+/// let liquidation = calc::liquidate_plan(current, quotes, StockSymbol::new("VMRXX")).unwrap();
+/// assert_eq!(liquidation.target.stock_value(StockSymbol::new("VMRXX")).unwrap(), 10000.0);
+/// assert_eq!(liquidation.target.total_value(), current.total_value());
+/// assert_eq!(liquidation.target.outside_stock_value(), 20000.0);
+/// for stock_symbol in StockSymbol::list() {
+///     let shares_held = (current / quotes).stock_value(stock_symbol.clone()).unwrap();
+///     let shares_sold = liquidation.sale_purchases_needed.stock_value(stock_symbol).unwrap();
+///     assert_eq!(shares_held + shares_sold, 0.0);
+/// }
+/// assert_eq!(liquidation.sale_purchases_needed.stock_value(StockSymbol::VV).unwrap(), -30.0);
+/// assert_eq!(liquidation.sale_purchases_needed.stock_value(StockSymbol::BND).unwrap(), -37.5);
+/// assert_eq!(liquidation.sale_purchases_needed.stock_value(StockSymbol::Other(String::new())).unwrap(), -500.0);
+/// let other = StockSymbol::Other("VTSAX".to_string());
+/// assert!(calc::liquidate_plan(current, quotes, other).is_err());
+/// ```
+pub fn liquidate_plan(
+    holdings: ShareValues,
+    quotes: ShareValues,
+    cash_symbol: StockSymbol,
+) -> Result<AccountHoldings> {
+    ensure!(
+        cash_symbol.is_cash(),
+        "{:?} is not a cash symbol",
+        cash_symbol
+    );
+    let mut target = ShareValues::new();
+    target.add_outside_stock_value(holdings.outside_stock_value());
+    target.add_outside_bond_value(holdings.outside_bond_value());
+    target.add_stock_value(cash_symbol, holdings.total_value())?;
+    let mut liquidation = AccountHoldings::new(holdings, target, (target - holdings) / quotes);
+    liquidation.add_placement_note("Account is sold entirely into cash".to_string());
+    Ok(liquidation)
+}
+
+/// recovery_plan creates the AccountHoldings which rebalance the current holdings back to the
+/// target model after a market drop.  This is the standard rebalance of the total value into the
+/// target model, which is validated to buy the asset class that fell below its target, either the
+/// stocks or the bonds and inflation protected.  Errors if the trades would not buy the depressed
+/// asset class.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::{asset, calc, holdings::{ShareValues, StockSymbol}};
+///
+/// let quotes = ShareValues::new_quote();
+/// let sixty_forty = asset::SubAllocations::new().unwrap();
+///
+/// // A 60/40 portfolio of 100,000 after stocks dropped 25%
+/// let mut current = ShareValues::new_target(sixty_forty, 100000.0, 0.0, 0.0, 0.0, 0.0);
+/// for stock_symbol in StockSymbol::list() {
+///     if stock_symbol.is_stock() {
+///         let value = current.stock_value(stock_symbol.clone()).unwrap();
+///         current.add_stock_value(stock_symbol, value * 0.75).unwrap();
+///     }
+/// }
+///
+/// let recovery = calc::recovery_plan(current, sixty_forty, quotes).unwrap();
+/// let (stock, _, _) = recovery.target.percent_stock_bond_infl();
+/// assert!((stock - 60.0).abs() < 0.01);
+/// assert!((recovery.target.total_value() - 85000.0).abs() < 0.01);
+/// for stock_symbol in StockSymbol::list() {
+///     let trade = recovery.sale_purchases_needed.stock_value(stock_symbol.clone()).unwrap();
+///     if stock_symbol.is_stock() {
+///         assert!(trade >= 0.0);
+///     } else {
+///         assert!(trade <= 0.0);
+///     }
+/// }
+/// assert!(recovery.total_to_buy(quotes) > 0.0);
+/// ```
+pub fn recovery_plan(
+    current: ShareValues,
+    target_model: SubAllocations,
+    quotes: ShareValues,
+) -> Result<AccountHoldings> {
+    let target = ShareValues::new_target(target_model, current.total_value(), 0.0, 0.0, 0.0, 0.0);
+    let recovery = AccountHoldings::new(current, target, (target - current) / quotes);
+    let (current_stock, _, _) = current.percent_stock_bond_infl();
+    let (target_stock, _, _) = target.percent_stock_bond_infl();
+    let purchases = recovery.sale_purchases_needed * quotes;
+    let mut stock_purchases = 0.0;
+    let mut bond_purchases = 0.0;
+    for stock_symbol in StockSymbol::list() {
+        let purchase = purchases.stock_value(stock_symbol.clone())?;
+        if stock_symbol.is_stock() {
+            stock_purchases += purchase;
+        } else {
+            bond_purchases += purchase;
+        }
+    }
+    if current_stock < target_stock - 0.01 {
+        ensure!(
+            stock_purchases > 0.0,
+            "Recovery plan does not buy stocks, which are at {:.2}% below the target of {:.2}%",
+            current_stock,
+            target_stock
+        );
+    } else if current_stock > target_stock + 0.01 {
+        ensure!(
+            bond_purchases > 0.0,
+            "Recovery plan does not buy bonds, which are at {:.2}% below the target of {:.2}%",
+            100.0 - current_stock,
+            100.0 - target_stock
+        );
+    }
+    Ok(recovery)
+}
+
+/// check_feasibility validates the rebalance inputs before to_buy is run, returning a single error
+/// that describes why the rebalance can not be calculated.  The account_values hold the value of
+/// each account after the cash add/remove, and the outside stock and bond values are the values
+/// held outside of Vanguard, which are treated as US stock and bond.  brokerage_in_retirement is
+/// set when the brokerage shares the retirement allocation, either through
+/// use_brokerage_retirement or tax_aware_brokerage.  The placement_preferences need to pin
+/// supported stock ETFs to accounts that hold part of the retirement allocation, and the
+/// retirement targets of the symbols pinned to an account need to fit within the account value.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use vapore_gui::{calc, holdings::{HoldingType, StockSymbol}};
+///
+/// let mut account_values = HashMap::new();
+/// account_values.insert(HoldingType::RothIra, 10000.0);
+/// account_values.insert(HoldingType::TraditionalIra, 40000.0);
+/// account_values.insert(HoldingType::Brokerage, 20000.0);
+/// let no_preferences = HashMap::new();
+/// assert!(calc::check_feasibility(2050, 60.0, &account_values, 0.0, 0.0, false, &no_preferences).is_ok());
+///
+/// // Invalid allocations
+/// let error = calc::check_feasibility(1900, 60.0, &account_values, 0.0, 0.0, false, &no_preferences).unwrap_err();
+/// assert!(error.to_string().contains("Retirement year 1900"));
+/// let error = calc::check_feasibility(2050, 120.0, &account_values, 0.0, 0.0, false, &no_preferences).unwrap_err();
+/// assert!(error.to_string().contains("percent stock"));
+///
+/// // More cash removed than is within the account
+/// let mut overdrawn = account_values.clone();
+/// overdrawn.insert(HoldingType::RothIra, -500.0);
+/// let error = calc::check_feasibility(2050, 60.0, &overdrawn, 0.0, 0.0, false, &no_preferences).unwrap_err();
+/// assert!(error.to_string().contains("RothIra value is negative"));
+///
+/// // Outside holdings larger than the target they count towards can not be placed
+/// let error = calc::check_feasibility(2050, 60.0, &account_values, 0.0, 100000.0, false, &no_preferences).unwrap_err();
+/// assert!(error.to_string().contains("Outside holdings are larger than the"));
+/// let error = calc::check_feasibility(2050, 60.0, &account_values, 100000.0, 0.0, true, &no_preferences).unwrap_err();
+/// assert!(error.to_string().contains("Outside holdings are larger than the"));
+///
+/// // Placement preferences that fit within the account
+/// let preferences = HashMap::from([(StockSymbol::VTIP, HoldingType::TraditionalIra)]);
+/// assert!(calc::check_feasibility(2050, 60.0, &account_values, 0.0, 0.0, false, &preferences).is_ok());
+///
+/// // Only supported stock ETFs can be pinned
+/// let preferences = HashMap::from([(StockSymbol::VMFXX, HoldingType::RothIra)]);
+/// let error = calc::check_feasibility(2050, 60.0, &account_values, 0.0, 0.0, false, &preferences).unwrap_err();
+/// assert!(error.to_string().contains("VMFXX is not a supported stock ETF"));
+///
+/// // The brokerage is not part of the retirement allocation and the HSA holds nothing
+/// let preferences = HashMap::from([(StockSymbol::BND, HoldingType::Brokerage)]);
+/// let error = calc::check_feasibility(2050, 60.0, &account_values, 0.0, 0.0, false, &preferences).unwrap_err();
+/// assert!(error.to_string().contains("BND is pinned to the Brokerage"));
+/// let preferences = HashMap::from([(StockSymbol::BND, HoldingType::Hsa)]);
+/// let error = calc::check_feasibility(2050, 60.0, &account_values, 0.0, 0.0, false, &preferences).unwrap_err();
+/// assert!(error.to_string().contains("BND is pinned to the Hsa"));
+///
+/// // The US large stock target is larger than the roth IRA
+/// let preferences = HashMap::from([(StockSymbol::VV, HoldingType::RothIra)]);
+/// let error = calc::check_feasibility(2050, 60.0, &account_values, 0.0, 0.0, false, &preferences).unwrap_err();
+/// assert!(error.to_string().contains("pinned to the RothIra"));
+/// ```
+pub fn check_feasibility(
+    retirement_year: i32,
+    percent_stock: f32,
+    account_values: &HashMap<HoldingType, f32>,
+    outside_stock: f32,
+    outside_bond: f32,
+    brokerage_in_retirement: bool,
+    placement_preferences: &HashMap<StockSymbol, HoldingType>,
+) -> Result<()> {
+    ensure!(
+        (0.0..=100.0).contains(&percent_stock),
+        "Brokerage percent stock needs to be between 0 and 100: {}",
+        percent_stock
+    );
+    let allocations = Allocations::retirement(retirement_year).with_context(|| {
+        format!(
+            "Retirement year {} does not give a valid allocation",
+            retirement_year
+        )
+    })?;
+    let sub_allocations = SubAllocations::new_custom(allocations)?;
+    for (holding_type, value) in account_values {
+        ensure!(
+            *value >= 0.0,
+            "{:?} value is negative after the cash add/remove: {}",
+            holding_type,
+            value
+        );
+    }
+    let retirement_values = account_values
+        .iter()
+        .filter(|(holding_type, value)| {
+            **value > 0.0 && (brokerage_in_retirement || **holding_type != HoldingType::Brokerage)
+        })
+        .map(|(holding_type, value)| (*holding_type, *value))
+        .collect::<HashMap<HoldingType, f32>>();
+    for (stock_symbol, holding_type) in placement_preferences {
+        ensure!(
+            StockSymbol::list().contains(stock_symbol),
+            "{:?} is not a supported stock ETF and can not be pinned to the {:?}",
+            stock_symbol,
+            holding_type
+        );
+        ensure!(
+            retirement_values.contains_key(holding_type),
+            "{:?} is pinned to the {:?}, which does not hold any of the retirement allocation",
+            stock_symbol,
+            holding_type
+        );
+    }
+    let retirement_value = retirement_values.values().sum::<f32>();
+    if retirement_value == 0.0 {
+        return Ok(());
+    }
+    let target = ShareValues::new_target(
+        sub_allocations,
+        retirement_value,
+        outside_stock,
+        outside_bond,
+        0.0,
+        0.0,
+    );
+    for stock_symbol in StockSymbol::list() {
+        let value = target.stock_value(stock_symbol.clone())?;
+        ensure!(
+            value > -0.01,
+            "Outside holdings are larger than the {:?} target by {:.2}, so the retirement target can not be placed within the accounts",
+            stock_symbol,
+            -value
+        );
+    }
+    for (holding_type, account_value) in &retirement_values {
+        let pinned_value = placement_preferences
+            .iter()
+            .filter(|(_, preferred)| *preferred == holding_type)
+            .map(|(stock_symbol, _)| target.stock_value(stock_symbol.clone()))
+            .sum::<Result<f32>>()?;
+        ensure!(
+            pinned_value <= account_value + 0.01,
+            "The symbols pinned to the {:?} have a retirement target of {:.2}, which does not fit within the account value of {:.2}",
+            holding_type,
+            pinned_value,
+            account_value
+        );
+    }
+    Ok(())
+}
+
+// Moves the cash percent of the account target out of the invested stock symbols and into cash,
+// keeping the ratios between the invested stock symbols, and updates the purchases/sales needed
+fn reserve_cash(
+    account: AccountHoldings,
+    cash_percent: f32,
+    quotes: ShareValues,
+    cash_symbol: StockSymbol,
+) -> Result<AccountHoldings> {
+    let mut target = account.target;
+    let invested_value = StockSymbol::list()
+        .into_iter()
+        .map(|stock_symbol| target.stock_value(stock_symbol))
+        .sum::<Result<f32>>()?;
+    let cash_value = target.stock_value(cash_symbol.clone())?;
+    let reserve = target.total_value() * cash_percent / 100.0 - cash_value;
+    if cash_percent == 0.0 || invested_value <= 0.0 || reserve <= 0.0 {
+        return Ok(account);
+    }
+    let invested_factor = (invested_value - reserve.min(invested_value)) / invested_value;
+    for stock_symbol in StockSymbol::list() {
+        let value = target.stock_value(stock_symbol.clone())?;
+        target.add_stock_value(stock_symbol, value * invested_factor)?;
+    }
+    target.add_stock_value(cash_symbol, cash_value + reserve.min(invested_value))?;
+    let sale_purchases_needed = (target - account.current) / quotes;
+    let mut reserved = AccountHoldings::new(account.current, target, sale_purchases_needed);
+    reserved.add_placement_note(account.placement_note().to_string());
+    Ok(reserved)
+}
+
+// Stock symbols that the brokerage sells at a loss, from the cost basis, while the same symbol is
+// bought within another account, which would trigger the wash sale rules
+fn wash_sale_warnings(
+    rebalance: &VanguardRebalance,
+    brokerage_cost_basis: &ShareValues,
+) -> Result<Vec<StockSymbol>> {
+    let mut warnings = Vec::new();
+    for stock_symbol in StockSymbol::list() {
+        let brokerage = &rebalance.brokerage;
+        let sold = brokerage
+            .sale_purchases_needed
+            .stock_value(stock_symbol.clone())?
+            < 0.0;
+        let at_loss = brokerage.current.stock_value(stock_symbol.clone())?
+            < brokerage_cost_basis.stock_value(stock_symbol.clone())?;
+        let mut bought = false;
+        for account in [
+            &rebalance.roth_ira,
+            &rebalance.traditional_ira,
+            &rebalance.hsa,
+        ] {
+            bought |= account
+                .sale_purchases_needed
+                .stock_value(stock_symbol.clone())?
+                > 0.0;
+        }
+        if sold && at_loss && bought {
+            warnings.push(stock_symbol);
+        }
+    }
+    Ok(warnings)
+}
+
+// Drops the purchases/sales whose value is under the rebalance threshold and then rounds the
+// remaining shares with the rounding mode, before pairing the kept trades with keep_trades
+fn adjust_trades(
+    account: AccountHoldings,
+    rebalance_threshold: f32,
+    rounding_mode: RoundingMode,
+    quotes: ShareValues,
+    cash_symbol: StockSymbol,
+) -> Result<AccountHoldings> {
+    let mut shares_needed = Vec::new();
+    for stock_symbol in StockSymbol::list() {
+        let quote = quotes.stock_value(stock_symbol.clone())?;
+        let shares = account
+            .sale_purchases_needed
+            .stock_value(stock_symbol.clone())?;
+        let kept_shares = if (shares * quote).abs() < rebalance_threshold {
+            0.0
+        } else {
+            rounding_mode.round(shares)
+        };
+        shares_needed.push((stock_symbol, quote, kept_shares));
+    }
+    keep_trades(
+        &account,
+        shares_needed,
+        rebalance_threshold,
+        rounding_mode,
+        quotes,
+        cash_symbol,
+    )
+}
+
+// Keeps the shares of each stock symbol within shares_needed, as (stock symbol, quote, shares).
+// When the dropped sales or the rounding leave less cash than the kept purchases need, the
+// purchases are cut back, largest first and by whole shares unless the shares are fractional, so
+// that the dropped sales are paired with dropped purchases and the cash target is never negative.
+// The target of each stock symbol is moved to what the kept shares buy or sell, with the
+// difference rolled into the cash target
+fn keep_trades(
+    account: &AccountHoldings,
+    mut shares_needed: Vec<(StockSymbol, f32, f32)>,
+    rebalance_threshold: f32,
+    rounding_mode: RoundingMode,
+    quotes: ShareValues,
+    cash_symbol: StockSymbol,
+) -> Result<AccountHoldings> {
+    ensure!(
+        cash_symbol.is_cash(),
+        "{:?} is not a cash symbol",
+        cash_symbol
+    );
+    let mut cash_target = account.target.stock_value(cash_symbol.clone())?;
+    for (stock_symbol, quote, kept_shares) in &shares_needed {
+        let current_value = account.current.stock_value(stock_symbol.clone())?;
+        let target_value = account.target.stock_value(stock_symbol.clone())?;
+        cash_target += target_value - current_value - kept_shares * quote;
+    }
+    // Cut back the largest purchases until the cash covers the kept purchases
+    let mut purchases = (0..shares_needed.len())
+        .filter(|index| shares_needed[*index].2 > 0.0)
+        .collect::<Vec<usize>>();
+    purchases.sort_by(|index_a, index_b| {
+        let (_, quote_b, shares_b) = &shares_needed[*index_b];
+        let (_, quote_a, shares_a) = &shares_needed[*index_a];
+        (shares_b * quote_b).total_cmp(&(shares_a * quote_a))
+    });
+    for index in purchases {
+        if cash_target > -0.01 {
+            break;
+        }
+        let (_, quote, kept_shares) = &mut shares_needed[index];
+        let mut cut_shares = (-cash_target / *quote).min(*kept_shares);
+        if rounding_mode != RoundingMode::Fractional {
+            cut_shares = cut_shares.ceil().min(*kept_shares);
+        }
+        // A purchase cut back under the threshold is dropped along with the dropped sales
+        if (*kept_shares - cut_shares) * *quote < rebalance_threshold {
+            cut_shares = *kept_shares;
+        }
+        *kept_shares -= cut_shares;
+        cash_target += cut_shares * *quote;
+    }
+    let mut target = account.target;
+    for (stock_symbol, quote, kept_shares) in &shares_needed {
+        let current_value = account.current.stock_value(stock_symbol.clone())?;
+        target.add_stock_value(stock_symbol.clone(), current_value + kept_shares * quote)?;
+    }
+    target.add_stock_value(cash_symbol, cash_target)?;
+    let mut sale_purchases_needed = (target - account.current) / quotes;
+    for (stock_symbol, _, kept_shares) in shares_needed {
+        sale_purchases_needed.add_stock_value(stock_symbol, kept_shares)?;
+    }
+    let mut adjusted = AccountHoldings::new(account.current, target, sale_purchases_needed);
+    adjusted.add_placement_note(account.placement_note().to_string());
+    Ok(adjusted)
+}
+
 /// brokerage_calc calculates the amount of stocks and bonds that should be bought/sold within the
 /// brokerage account in order to rebalance
 fn brokerage_calc(
     quotes: ShareValues,
-    mut brokerage: ShareValues,
+    inputs: &AccountInputs,
     percent_stock: f32,
-    brokerage_cash_add: f32,
-    brokerage_us_stock_add: f32,
-    brokerage_int_stock_add: f32,
-    brokerage_us_bond_add: f32,
-    brokerage_int_bond_add: f32,
+    cash_symbol: &StockSymbol,
 ) -> Result<AccountHoldings> {
     let percent_bond = 100.0 - percent_stock;
-    brokerage.add_stock_value(
-        StockSymbol::VMFXX,
-        brokerage.stock_value(StockSymbol::VMFXX) + brokerage_cash_add,
-    );
-    brokerage.add_outside_stock_value(brokerage_us_stock_add + brokerage_int_stock_add);
-    brokerage.add_outside_bond_value(brokerage_us_bond_add + brokerage_int_bond_add);
+    let mut brokerage = inputs.holdings_with_cash(cash_symbol)?;
+    brokerage.add_outside_stock_value(inputs.us_stock_add + inputs.int_stock_add);
+    brokerage.add_outside_bond_value(inputs.us_bond_add + inputs.int_bond_add);
     let asset_allocations = Allocations::custom(percent_stock, percent_bond, 0.0)?;
     let sub_allocations = SubAllocations::new_custom(asset_allocations)?;
-    let target_holdings = ShareValues::new_target(
+    let target_holdings = ShareValues::new_target_with_registered(
         sub_allocations,
         brokerage.total_value(),
-        brokerage_us_stock_add,
-        brokerage_us_bond_add,
-        brokerage_int_stock_add,
-        brokerage_int_bond_add,
+        inputs.us_stock_add,
+        inputs.us_bond_add,
+        inputs.int_stock_add,
+        inputs.int_bond_add,
+        &brokerage,
     );
     let difference = target_holdings - brokerage;
     let stock_purchase = difference / quotes;
-    Ok(AccountHoldings::new(
-        brokerage,
-        target_holdings,
-        stock_purchase,
-    ))
+    let mut brokerage_account = AccountHoldings::new(brokerage, target_holdings, stock_purchase);
+    brokerage_account.add_placement_note(format!(
+        "Brokerage holds {} from its own {}% stock allocation",
+        placed_symbols(&target_holdings),
+        percent_stock
+    ));
+    Ok(brokerage_account)
 }
 
-type TraditionalIraAccount = AccountHoldings;
-type RothIraAccount = AccountHoldings;
-type BrokerageAccount = AccountHoldings;
-type TargetOverallRetirement = ShareValues;
+// Reorders the placement order so the symbols preferred within the account are placed first and
+// the symbols preferred within other accounts are placed last, which leaves them for the other
+// accounts unless nothing else remains.  Without preferences the order is unchanged
+fn preferred_order(
+    placement_order: &[StockSymbol],
+    account: HoldingType,
+    placement_preferences: &HashMap<StockSymbol, HoldingType>,
+) -> Vec<StockSymbol> {
+    let mut ordered = placement_order.to_vec();
+    ordered.sort_by_key(
+        |stock_symbol| match placement_preferences.get(stock_symbol) {
+            Some(preferred) if *preferred == account => 0,
+            Some(_) => 2,
+            None => 1,
+        },
+    );
+    ordered
+}
+
+// Fills the account total from the available target in the placement order, followed by cash.
+// Registered holdings are kept within the account they are held in and the rest of the account is
+// filled.  The fill is short of the account total when the account is larger than the available
+// target, which is reconciled before the remainder is kept within cash with remainder_in_cash
+fn fill_account(
+    holdings: &ShareValues,
+    available_target: &ShareValues,
+    placement_order: &[StockSymbol],
+    cash_symbol: &StockSymbol,
+) -> Result<ShareValues> {
+    let mut account_target = holdings.registered_holdings();
+    let mut account_total = holdings.total_value() - account_target.total_value();
+    for stock_symbol in placement_order.iter().chain([cash_symbol]) {
+        if account_total <= 0.0 {
+            break;
+        }
+        let value = available_target
+            .stock_value(stock_symbol.clone())?
+            .max(0.0)
+            .min(account_total);
+        account_total -= value;
+        account_target.add_stock_value(stock_symbol.clone(), value)?;
+    }
+    Ok(account_target)
+}
+
+// Keeps the remainder of the account total that the fill did not reach within cash, so the
+// target always matches the account total
+fn remainder_in_cash(
+    mut account_target: ShareValues,
+    account_total: f32,
+    cash_symbol: &StockSymbol,
+) -> Result<ShareValues> {
+    let remainder = account_total - account_target.total_value();
+    if remainder > 0.0 {
+        account_target.add_stock_value(
+            cash_symbol.clone(),
+            account_target.stock_value(cash_symbol.clone())? + remainder,
+        )?;
+    }
+    Ok(account_target)
+}
+
+// Errors with a ReconciliationError when the target of the account is not within the tolerance
+// percent of the account total
+fn reconcile(
+    account: HoldingType,
+    target: &ShareValues,
+    total: &ShareValues,
+    tolerance: f32,
+) -> Result<()> {
+    let reconciliation = ReconciliationError {
+        account,
+        target: target.total_value(),
+        total: total.total_value(),
+        tolerance,
+    };
+    if reconciliation.mismatch().abs() > reconciliation.total.abs() * tolerance / 100.0 {
+        return Err(reconciliation.into());
+    }
+    Ok(())
+}
+
+// Lists the stock symbols with a target value for the placement notes
+fn placed_symbols(target: &ShareValues) -> String {
+    StockSymbol::list()
+        .into_iter()
+        .filter(|stock_symbol| target.stock_value(stock_symbol.clone()).unwrap_or(0.0) > 0.01)
+        .map(|stock_symbol| format!("{:?}", stock_symbol))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// The accounts placed within the retirement allocation by retirement_calc, along with the
+// household retirement target and the allocation and sub allocations used.  Accounts that are not
+// included are None
+#[derive(Default)]
+struct RetirementPlacement {
+    traditional_ira: Option<AccountHoldings>,
+    roth_ira: Option<AccountHoldings>,
+    hsa: Option<AccountHoldings>,
+    brokerage: Option<AccountHoldings>,
+    retirement_target: Option<ShareValues>,
+    allocation_used: Option<(Allocations, SubAllocations)>,
+}
 
 /// retirement_calc calculates the amount of stocks and bonds that should be bought/sold within the
 /// retirement account in order to rebalance.  If there are both a roth and traditional IRA
 /// account, the riskiest assets are shifted towards the roth account while the less risky assets
 /// are within the traditonal account.  This is to keep the largest growth within the account that
-/// is not taxed after withdrawals
+/// is not taxed after withdrawals.  When tax_aware_brokerage is set, the brokerage is included
+/// within the overall allocation and filled with the most tax efficient of the remaining assets.
 fn retirement_calc(
-    retirement_year: i32,
-    mut roth_holdings: ShareValues,
-    roth_us_stock_add: f32,
-    roth_us_bond_add: f32,
-    roth_int_stock_add: f32,
-    roth_int_bond_add: f32,
-    roth_cash_add: f32,
-    mut traditional_holdings: ShareValues,
-    traditional_us_stock_add: f32,
-    traditional_us_bond_add: f32,
-    traditional_int_stock_add: f32,
-    traditional_int_bond_add: f32,
-    traditional_cash_add: f32,
-    use_brokerage_retirement: bool,
-    mut brokerage_holdings: ShareValues,
-    brokerage_us_stock_add: f32,
-    brokerage_us_bond_add: f32,
-    brokerage_int_stock_add: f32,
-    brokerage_int_bond_add: f32,
-    brokerage_cash_add: f32,
+    options: &RebalanceOptions,
     stock_quotes: ShareValues,
-) -> Result<(
-    Option<TraditionalIraAccount>,
-    Option<RothIraAccount>,
-    Option<BrokerageAccount>,
-    Option<TargetOverallRetirement>,
-)> {
-    let mut traditional_ira_account_option = None;
-    let mut roth_ira_account_option = None;
-    let mut brokerage_account_option = None;
-    let mut target_overall_retirement_option = None;
-
-    let allocations = Allocations::retirement(retirement_year)?;
+) -> Result<RetirementPlacement> {
+    let mut placement = RetirementPlacement::default();
+    let cash_symbol = &options.cash_symbol;
+
+    let allocations = Allocations::retirement(options.retirement_year)?;
     let sub_allocations = SubAllocations::new_custom(allocations)?;
 
-    let mut holdings_value = 0.0;
+    // Accounts are included when they have holdings, with the brokerage only included when it
+    // shares the retirement allocation
+    let include_brokerage = options.use_brokerage_retirement || options.tax_aware_brokerage;
+    let included = |inputs: &AccountInputs, include: bool| -> Result<Option<ShareValues>> {
+        if include && inputs.holdings.total_value() != 0.0 {
+            Ok(Some(inputs.holdings_with_cash(cash_symbol)?))
+        } else {
+            Ok(None)
+        }
+    };
+    let roth_holdings = included(&options.roth_ira, true)?;
+    let traditional_holdings = included(&options.traditional_ira, true)?;
+    let hsa_holdings = included(&options.hsa, true)?;
+    let brokerage_holdings = included(&options.brokerage, include_brokerage)?;
+
+    let mut combined_holdings = ShareValues::new();
     let mut us_stock_add = 0.0;
     let mut us_bond_add = 0.0;
     let mut int_stock_add = 0.0;
     let mut int_bond_add = 0.0;
+    for (inputs, holdings) in [
+        (&options.roth_ira, roth_holdings),
+        (&options.traditional_ira, traditional_holdings),
+        (&options.hsa, hsa_holdings),
+        (&options.brokerage, brokerage_holdings),
+    ] {
+        if let Some(holdings) = holdings {
+            combined_holdings = combined_holdings + holdings;
+            us_stock_add += inputs.us_stock_add;
+            us_bond_add += inputs.us_bond_add;
+            int_stock_add += inputs.int_stock_add;
+            int_bond_add += inputs.int_bond_add;
+        }
+    }
+    if [
+        roth_holdings,
+        traditional_holdings,
+        hsa_holdings,
+        brokerage_holdings,
+    ]
+    .iter()
+    .all(Option::is_none)
+    {
+        return Ok(placement);
+    }
 
-    let mut include_roth = false;
-    let mut include_traditional = false;
-    let mut include_brokerage = false;
-
-    let mut roth_total = 0.0;
-    let mut brokerage_total = 0.0;
-
-    let mut roth_holdings_final = ShareValues::new();
-    let mut brokerage_holdings_final = ShareValues::new();
-    let mut traditional_holdings_final = ShareValues::new();
-    if roth_holdings.total_value() != 0.0 {
-        roth_holdings.add_stock_value(
-            StockSymbol::VMFXX,
-            roth_holdings.stock_value(StockSymbol::VMFXX) + roth_cash_add,
-        );
-        holdings_value += roth_holdings.total_value();
-        us_stock_add += roth_us_stock_add;
-        us_bond_add += roth_us_bond_add;
-        int_stock_add += roth_int_stock_add;
-        int_bond_add += roth_int_bond_add;
-        include_roth = true;
-        roth_total = roth_holdings.total_value();
-        roth_holdings_final = roth_holdings;
-    }
-    // If there are both Roth and Traditional accounts, shift the risky assets to the roth
-    // account
-    if traditional_holdings.total_value() != 0.0 {
-        traditional_holdings.add_stock_value(
-            StockSymbol::VMFXX,
-            traditional_holdings.stock_value(StockSymbol::VMFXX) + traditional_cash_add,
-        );
-        holdings_value += traditional_holdings.total_value();
-        us_stock_add += traditional_us_stock_add;
-        us_bond_add += traditional_us_bond_add;
-        int_stock_add += traditional_int_stock_add;
-        int_bond_add += traditional_int_bond_add;
-        include_traditional = true;
-        traditional_holdings_final = traditional_holdings;
-    }
-    if use_brokerage_retirement && brokerage_holdings.total_value() != 0.0 {
-        brokerage_holdings.add_stock_value(
-            StockSymbol::VMFXX,
-            brokerage_holdings.stock_value(StockSymbol::VMFXX) + brokerage_cash_add,
+    let holdings_value = combined_holdings.total_value();
+    let target_overall_retirement = if let Some(locked_target) = options.household_target {
+        ensure!(
+            (locked_target.total_value() - holdings_value).abs() <= 0.01 * holdings_value,
+            "Locked household target ({}) does not match the retirement accounts total ({})",
+            locked_target.total_value(),
+            holdings_value
         );
-        holdings_value += brokerage_holdings.total_value();
-        us_stock_add += brokerage_us_stock_add;
-        us_bond_add += brokerage_us_bond_add;
-        int_stock_add += brokerage_int_stock_add;
-        int_bond_add += brokerage_int_bond_add;
-        include_brokerage = true;
-        brokerage_total = brokerage_holdings.total_value();
-        brokerage_holdings_final = brokerage_holdings;
-    }
-
-    let mut target_overall_retirement = ShareValues::new();
-    if [include_brokerage, include_traditional, include_roth]
-        .iter()
-        .any(|&x| x)
-    {
-        target_overall_retirement = ShareValues::new_target(
+        locked_target
+    } else {
+        // registered holdings count towards their asset class and stay within their accounts
+        ShareValues::new_target_with_registered(
             sub_allocations,
             holdings_value,
             us_stock_add,
             us_bond_add,
             int_stock_add,
             int_bond_add,
-        );
-        target_overall_retirement_option = Some(target_overall_retirement);
-    }
+            &combined_holdings,
+        )
+    };
+    placement.retirement_target = Some(target_overall_retirement);
+    placement.allocation_used = Some((allocations, sub_allocations));
+
+    // Placement preferences only apply to the accounts which are included, so that symbols
+    // preferred within a missing account are placed by the risk ordering
+    let included_accounts = [
+        (HoldingType::RothIra, roth_holdings.is_some()),
+        (HoldingType::Hsa, hsa_holdings.is_some()),
+        (HoldingType::Brokerage, brokerage_holdings.is_some()),
+        (HoldingType::TraditionalIra, traditional_holdings.is_some()),
+    ];
+    let active_preferences = options
+        .placement_preferences
+        .iter()
+        .filter(|(_, holding_type)| included_accounts.contains(&(**holding_type, true)))
+        .map(|(stock_symbol, holding_type)| (stock_symbol.clone(), *holding_type))
+        .collect::<HashMap<StockSymbol, HoldingType>>();
 
     let mut remaining_target = target_overall_retirement;
-    if include_roth {
-        let mut roth_target = ShareValues::new();
-        for stock_symbol in HIGH_TO_LOW_RISK {
-            let value = target_overall_retirement
-                .stock_value(stock_symbol.clone())
-                .min(roth_total);
-            roth_total -= value;
-            roth_target.add_stock_value(stock_symbol.clone(), value);
-            if roth_total <= 0.0 {
-                break;
-            }
-        }
-        ensure!(roth_total == 0.0, "Unexpected leftover roth cash");
-        ensure!(
-            roth_target.total_value() > (0.99 * roth_holdings_final.total_value())
-                && roth_target.total_value() < (1.01 * roth_holdings_final.total_value()),
-            "Roth target and total do not match\n\nRoth target:\n{}\n\nRoth:\n{}",
-            roth_target,
-            roth_holdings_final
-        );
-        let roth_difference = roth_target - roth_holdings_final;
+    if let Some(roth_holdings) = roth_holdings {
+        let roth_fill = fill_account(
+            &roth_holdings,
+            &target_overall_retirement,
+            &preferred_order(&HIGH_TO_LOW_RISK, HoldingType::RothIra, &active_preferences),
+            cash_symbol,
+        )?;
+        reconcile(
+            HoldingType::RothIra,
+            &roth_fill,
+            &roth_holdings,
+            options.reconciliation_tolerance,
+        )?;
+        let roth_target = remainder_in_cash(roth_fill, roth_holdings.total_value(), cash_symbol)?;
+        let roth_difference = roth_target - roth_holdings;
         let roth_purchase = roth_difference / stock_quotes;
-        let roth_account = AccountHoldings::new(roth_holdings_final, roth_target, roth_purchase);
+        let mut roth_account = AccountHoldings::new(roth_holdings, roth_target, roth_purchase);
+        roth_account.add_placement_note(format!(
+            "Roth IRA holds {} because they are the highest risk assets, which keeps the largest growth from being taxed",
+            placed_symbols(&roth_target)
+        ));
         remaining_target = remaining_target - roth_target;
-        roth_ira_account_option = Some(roth_account);
-    }
-
-    if include_brokerage {
-        let mut brokerage_target = ShareValues::new();
-        for stock_symbol in HIGH_TO_LOW_RISK.iter().rev() {
-            let value = target_overall_retirement
-                .stock_value(stock_symbol.clone())
-                .min(brokerage_total);
-            brokerage_total -= value;
-            brokerage_target.add_stock_value(stock_symbol.clone(), value);
-            if brokerage_total <= 0.0 {
-                break;
-            }
-        }
-        ensure!(brokerage_total == 0.0, "Unexpected leftover brokerage cash");
-        ensure!(
-            brokerage_target.total_value() > (0.99 * brokerage_holdings_final.total_value())
-                && brokerage_target.total_value() < (1.01 * brokerage_holdings_final.total_value()),
-            "brokerage target and total do not match\n\nbrokerage target:\n{}\n\nbrokerage:\n{}",
-            brokerage_target,
-            brokerage_holdings_final
-        );
-        let brokerage_difference = brokerage_target - brokerage_holdings_final;
+        placement.roth_ira = Some(roth_account);
+    }
+
+    if let Some(hsa_holdings) = hsa_holdings {
+        let hsa_fill = fill_account(
+            &hsa_holdings,
+            &remaining_target,
+            &preferred_order(&HIGH_TO_LOW_RISK, HoldingType::Hsa, &active_preferences),
+            cash_symbol,
+        )?;
+        reconcile(
+            HoldingType::Hsa,
+            &hsa_fill,
+            &hsa_holdings,
+            options.reconciliation_tolerance,
+        )?;
+        let hsa_target = remainder_in_cash(hsa_fill, hsa_holdings.total_value(), cash_symbol)?;
+        let hsa_difference = hsa_target - hsa_holdings;
+        let hsa_purchase = hsa_difference / stock_quotes;
+        let mut hsa_account = AccountHoldings::new(hsa_holdings, hsa_target, hsa_purchase);
+        hsa_account.add_placement_note(format!(
+            "HSA holds {} because they are the highest risk assets remaining after the Roth IRA, which keeps the growth from being taxed",
+            placed_symbols(&hsa_target)
+        ));
+        remaining_target = remaining_target - hsa_target;
+        placement.hsa = Some(hsa_account);
+    }
+
+    if let Some(brokerage_holdings) = brokerage_holdings {
+        // Tax aware placement fills from what remains after the roth, otherwise the brokerage is
+        // filled with the least risky assets
+        let (placement_order, available_target) = if options.tax_aware_brokerage {
+            (TAX_EFFICIENT_ORDER.to_vec(), remaining_target)
+        } else {
+            (
+                HIGH_TO_LOW_RISK
+                    .iter()
+                    .rev()
+                    .cloned()
+                    .collect::<Vec<StockSymbol>>(),
+                target_overall_retirement,
+            )
+        };
+        let brokerage_fill = fill_account(
+            &brokerage_holdings,
+            &available_target,
+            &preferred_order(
+                &placement_order,
+                HoldingType::Brokerage,
+                &active_preferences,
+            ),
+            cash_symbol,
+        )?;
+        reconcile(
+            HoldingType::Brokerage,
+            &brokerage_fill,
+            &brokerage_holdings,
+            options.reconciliation_tolerance,
+        )?;
+        let brokerage_target = remainder_in_cash(
+            brokerage_fill,
+            brokerage_holdings.total_value(),
+            cash_symbol,
+        )?;
+        let brokerage_difference = brokerage_target - brokerage_holdings;
         let brokerage_purchase = brokerage_difference / stock_quotes;
-        let brokerage_account = AccountHoldings::new(
-            brokerage_holdings_final,
-            brokerage_target,
-            brokerage_purchase,
-        );
+        let mut brokerage_account =
+            AccountHoldings::new(brokerage_holdings, brokerage_target, brokerage_purchase);
+        let placement_reason = if options.tax_aware_brokerage {
+            "they are the most tax efficient of the assets remaining after the Roth IRA"
+        } else {
+            "they are the lowest risk assets of the retirement allocation"
+        };
+        brokerage_account.add_placement_note(format!(
+            "Brokerage holds {} because {}",
+            placed_symbols(&brokerage_target),
+            placement_reason
+        ));
         remaining_target = remaining_target - brokerage_target;
-        brokerage_account_option = Some(brokerage_account);
+        placement.brokerage = Some(brokerage_account);
     }
 
-    if include_traditional {
+    if let Some(traditional_holdings) = traditional_holdings {
         let traditional_target = remaining_target;
-        let traditional_difference = traditional_target - traditional_holdings_final;
+        let traditional_difference = traditional_target - traditional_holdings;
         let traditional_purchase = traditional_difference / stock_quotes;
-        let traditional_account = AccountHoldings::new(
-            traditional_holdings_final,
+        let mut traditional_account = AccountHoldings::new(
+            traditional_holdings,
             traditional_target,
             traditional_purchase,
         );
-        traditional_ira_account_option = Some(traditional_account);
+        traditional_account.add_placement_note(format!(
+            "Traditional IRA holds {} because they remain after placing the higher risk assets in the other accounts",
+            placed_symbols(&traditional_target)
+        ));
+        placement.traditional_ira = Some(traditional_account);
     }
 
-    Ok((
-        traditional_ira_account_option,
-        roth_ira_account_option,
-        brokerage_account_option,
-        target_overall_retirement_option,
-    ))
+    Ok(placement)
 }
 
 pub fn get_distribution_table(csv_path: PathBuf) -> Result<HashMap<u32, f32>> {
@@ -370,9 +1545,65 @@ pub fn get_distribution_table(csv_path: PathBuf) -> Result<HashMap<u32, f32>> {
             }
         }
     }
+    validate_distribution_table(&distribution_table)?;
     Ok(distribution_table)
 }
 
+/// validate_distribution_table checks that the ages of the IRS distribution table are contiguous
+/// and that the distribution periods are positive and decrease with age.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use vapore_gui::calc;
+///
+/// let distribution_table = HashMap::from([(72, 27.4), (73, 26.5), (74, 25.5)]);
+/// assert!(calc::validate_distribution_table(&distribution_table).is_ok());
+///
+/// // Age 73 is missing
+/// let gap_table = HashMap::from([(72, 27.4), (74, 25.5)]);
+/// assert!(calc::validate_distribution_table(&gap_table).is_err());
+///
+/// // Distribution period does not decrease from age 72 to 73
+/// let non_decreasing_table = HashMap::from([(72, 27.4), (73, 27.4), (74, 25.5)]);
+/// assert!(calc::validate_distribution_table(&non_decreasing_table).is_err());
+/// ```
+pub fn validate_distribution_table(distribution_table: &HashMap<u32, f32>) -> Result<()> {
+    ensure!(
+        !distribution_table.is_empty(),
+        "Distribution table does not contain any ages"
+    );
+    let mut ages = distribution_table.keys().copied().collect::<Vec<u32>>();
+    ages.sort_unstable();
+    for age in ages.iter() {
+        ensure!(
+            distribution_table[age] > 0.0,
+            "Distribution period needs to be positive.  Age: {}, period: {}",
+            age,
+            distribution_table[age]
+        );
+    }
+    for age_pair in ages.windows(2) {
+        let (age, next_age) = (age_pair[0], age_pair[1]);
+        ensure!(
+            next_age == age + 1,
+            "Ages in distribution table are not contiguous.  Missing ages between {} and {}",
+            age,
+            next_age
+        );
+        ensure!(
+            distribution_table[&next_age] < distribution_table[&age],
+            "Distribution period needs to decrease with age.  Age {} period: {}, age {} period: {}",
+            age,
+            distribution_table[&age],
+            next_age,
+            distribution_table[&next_age]
+        );
+    }
+    Ok(())
+}
+
 // Calculates the minimum distribution for an unmarried individual or someone without a spouse
 // greater than 10 years younger.
 pub fn calculate_minimum_distribution(
@@ -386,3 +1617,730 @@ pub fn calculate_minimum_distribution(
         Ok(0.0)
     }
 }
+
+/// distribution_tax_estimate estimates the incremental tax owed on a distribution when it is added
+/// on top of other income.  Brackets are (lower income bound, tax rate) pairs sorted by the lower
+/// bound, where the tax rate is a fraction.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::calc;
+///
+/// let brackets = [(0.0, 0.10), (10000.0, 0.20), (50000.0, 0.30)];
+///
+/// // 5,000 taxed at 10% and 15,000 taxed at 20%
+/// let tax = calc::distribution_tax_estimate(20000.0, 5000.0, &brackets);
+/// assert!((tax - 3500.0).abs() < 0.01);
+/// ```
+pub fn distribution_tax_estimate(
+    distribution: f32,
+    other_income: f32,
+    brackets: &[(f32, f32)],
+) -> f32 {
+    bracket_tax(other_income + distribution, brackets) - bracket_tax(other_income, brackets)
+}
+
+// Calculates the total tax on an income with the (lower income bound, tax rate) brackets
+fn bracket_tax(income: f32, brackets: &[(f32, f32)]) -> f32 {
+    let mut tax = 0.0;
+    for (index, (lower_bound, rate)) in brackets.iter().enumerate() {
+        if income <= *lower_bound {
+            break;
+        }
+        let upper_bound = brackets
+            .get(index + 1)
+            .map(|(next_lower_bound, _)| *next_lower_bound)
+            .unwrap_or(f32::MAX);
+        tax += (income.min(upper_bound) - lower_bound) * rate;
+    }
+    tax
+}
+
+/// blend_models linearly interpolates between two target models so that a transition between
+/// strategies can happen gradually.  A fraction of 0 returns the from model and a fraction of 1
+/// returns the to model.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::{asset, calc};
+///
+/// let from = asset::SubAllocations::new().unwrap();
+/// let to = asset::SubAllocations::new_custom(asset::Allocations::custom(80.0, 20.0, 0.0).unwrap()).unwrap();
+///
+/// let blended = calc::blend_models(from, to, 0.5).unwrap();
+/// let halfway = asset::SubAllocations::new_custom(asset::Allocations::custom(70.0, 30.0, 0.0).unwrap()).unwrap();
+/// assert!((blended.us_stock_large - halfway.us_stock_large).abs() < 0.001);
+/// assert!((blended.int_bond - halfway.int_bond).abs() < 0.001);
+///
+/// assert!(calc::blend_models(asset::SubAllocations::new().unwrap(), asset::SubAllocations::new().unwrap(), 1.5).is_err());
+/// ```
+pub fn blend_models(
+    from: SubAllocations,
+    to: SubAllocations,
+    fraction: f32,
+) -> Result<SubAllocations> {
+    ensure!(
+        (0.0..=1.0).contains(&fraction),
+        "Blend fraction needs to be between 0 and 1.  Fraction input: {}",
+        fraction
+    );
+    let blend = |from_value: f32, to_value: f32| from_value + (to_value - from_value) * fraction;
+    Ok(SubAllocations {
+        us_stock_large: blend(from.us_stock_large, to.us_stock_large),
+        us_stock_mid: blend(from.us_stock_mid, to.us_stock_mid),
+        us_stock_small: blend(from.us_stock_small, to.us_stock_small),
+        us_tot_bond: blend(from.us_tot_bond, to.us_tot_bond),
+        us_corp_bond: blend(from.us_corp_bond, to.us_corp_bond),
+        int_tot_stock: blend(from.int_tot_stock, to.int_tot_stock),
+        int_emerging_stock: blend(from.int_emerging_stock, to.int_emerging_stock),
+        int_bond: blend(from.int_bond, to.int_bond),
+        inflation_protected: blend(from.inflation_protected, to.inflation_protected),
+    })
+}
+
+/// bucket_allocation sizes the target values for a bucket strategy, where near term spending is
+/// kept in a cash bucket within the cash symbol, the following years of spending are kept in a
+/// bond bucket, and the rest is invested in stocks.  The bond and stock buckets are split across
+/// the supported ETFs with the default sub allocations.  Buckets are capped at the total value of
+/// the portfolio.  Errors when the total is not above 0 or the cash symbol is not VMFXX or another
+/// money market fund.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::{calc, holdings::StockSymbol};
+///
+/// let target = calc::bucket_allocation(1000000.0, 2, 6, 40000.0, StockSymbol::VMFXX).unwrap();
+/// assert_eq!(target.stock_value(StockSymbol::VMFXX).unwrap(), 80000.0);
+/// assert_eq!(target.stock_value(StockSymbol::VTIP).unwrap(), 0.0);
+/// assert!((target.total_value() - 1000000.0).abs() < 0.5);
+/// let bucket_value = |is_bucket: fn(&StockSymbol) -> bool| -> f32 {
+///     StockSymbol::list()
+///         .into_iter()
+///         .filter(is_bucket)
+///         .map(|stock_symbol| target.stock_value(stock_symbol).unwrap())
+///         .sum()
+/// };
+/// assert!((bucket_value(StockSymbol::is_stock) - 680000.0).abs() < 0.5);
+/// assert!((bucket_value(StockSymbol::is_bond) - 240000.0).abs() < 0.5);
+///
+/// // The cash bucket is capped at the total
+/// let target = calc::bucket_allocation(50000.0, 2, 6, 40000.0, StockSymbol::VMFXX).unwrap();
+/// assert_eq!(target.stock_value(StockSymbol::VMFXX).unwrap(), 50000.0);
+/// assert!(calc::bucket_allocation(50000.0, 2, 6, 40000.0, StockSymbol::BND).is_err());
+/// ```
+pub fn bucket_allocation(
+    total: f32,
+    years_cash: u32,
+    years_bonds: u32,
+    annual_spend: f32,
+    cash_symbol: StockSymbol,
+) -> Result<ShareValues> {
+    ensure!(
+        total > 0.0,
+        "Total value needs to be greater than 0.  Total input: {}",
+        total
+    );
+    ensure!(
+        cash_symbol.is_cash(),
+        "{:?} is not a cash symbol",
+        cash_symbol
+    );
+    let cash_value = (years_cash as f32 * annual_spend).min(total);
+    let invested = total - cash_value;
+    let bond_value = (years_bonds as f32 * annual_spend).min(invested);
+    // With nothing invested the split of the stock and bond buckets does not matter
+    let bond_percent = if invested > 0.0 {
+        bond_value / invested * 100.0
+    } else {
+        0.0
+    };
+    let allocations = Allocations::custom(100.0 - bond_percent, bond_percent, 0.0)?;
+    let mut target = ShareValues::new_target(
+        SubAllocations::new_custom(allocations)?,
+        invested,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+    );
+    target.add_stock_value(cash_symbol, cash_value)?;
+    Ok(target)
+}
+
+/// tax_efficient_drift_threshold calculates the minimum drift, in percentage points, at which
+/// rebalancing a taxable account is worth the tax cost.  The tax rate is the fraction of each
+/// dollar sold that is lost to taxes.  The expected return benefit is the fraction of each dollar
+/// rebalanced that is gained per unit of drift corrected, so the benefit grows with drift while
+/// the tax cost does not.  An infinite threshold is returned when there is no benefit.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::calc;
+///
+/// let threshold = calc::tax_efficient_drift_threshold(0.15, 1.5);
+/// assert!((threshold - 10.0).abs() < 0.001);
+/// assert_eq!(calc::tax_efficient_drift_threshold(0.15, 0.0), f32::INFINITY);
+/// ```
+pub fn tax_efficient_drift_threshold(tax_rate: f32, expected_return_benefit: f32) -> f32 {
+    if expected_return_benefit <= 0.0 {
+        f32::INFINITY
+    } else {
+        tax_rate / expected_return_benefit * 100.0
+    }
+}
+
+/// allocation_with_income_floor treats the net present value of a guaranteed income stream, such
+/// as a pension or Social Security, as bonds that are already held.  The base allocations are
+/// applied to the portfolio plus the income value and the income value is removed from the bonds
+/// needed, which shifts the portfolio towards stocks.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::{asset, calc};
+///
+/// let base = asset::Allocations::custom(60.0, 40.0, 0.0).unwrap();
+/// let allocations = calc::allocation_with_income_floor(500000.0, 1000000.0, base).unwrap();
+/// assert_eq!(allocations.total_stock(), 100.0);
+/// assert_eq!(allocations.total_bond(), 0.0);
+///
+/// let base = asset::Allocations::custom(60.0, 40.0, 0.0).unwrap();
+/// let allocations = calc::allocation_with_income_floor(500000.0, 100000.0, base).unwrap();
+/// assert!((allocations.total_stock() - 72.0).abs() < 0.001);
+/// ```
+pub fn allocation_with_income_floor(
+    portfolio: f32,
+    income_npv: f32,
+    base: Allocations,
+) -> Result<Allocations> {
+    ensure!(
+        portfolio > 0.0,
+        "Portfolio value needs to be greater than 0.  Portfolio input: {}",
+        portfolio
+    );
+    let household = portfolio + income_npv;
+    let bond_value = (household * base.total_bond() / 100.0 - income_npv).clamp(0.0, portfolio);
+    let inflation_protected_value =
+        (household * base.total_inflation_protected() / 100.0).min(portfolio - bond_value);
+    let bond_percent = bond_value / portfolio * 100.0;
+    let inflation_protected_percent = inflation_protected_value / portfolio * 100.0;
+    Allocations::custom(
+        100.0 - bond_percent - inflation_protected_percent,
+        bond_percent,
+        inflation_protected_percent,
+    )
+}
+
+/// threshold_rebalance only keeps the sales/purchases of the symbols which have drifted from their
+/// target by at least the threshold, in percentage points of the account value.  Stocks and bonds
+/// use separate thresholds since more drift is often tolerated within bonds.  Symbols within their
+/// threshold keep their current value and the difference is placed in the cash target of the
+/// cash symbol, such as the cash_symbol of the RebalanceOptions.  When a dropped sale was paying
+/// for a kept purchase, the purchase is cut back to the kept sales plus the available cash so
+/// that the cash target is never negative.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::{calc, holdings};
+///
+/// let mut current = holdings::ShareValues::new();
+/// current.add_stock_value(holdings::StockSymbol::VV, 5500.0).unwrap();
+/// current.add_stock_value(holdings::StockSymbol::BND, 4500.0).unwrap();
+/// let mut target = holdings::ShareValues::new();
+/// target.add_stock_value(holdings::StockSymbol::VV, 5000.0).unwrap();
+/// target.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
+/// let quotes = holdings::ShareValues::new_quote();
+/// let account = holdings::AccountHoldings::new(current, target, (target - current) / quotes);
+/// let cash = holdings::StockSymbol::VMFXX;
+///
+/// let rebalanced = calc::threshold_rebalance(&account, 3.0, 10.0, quotes, cash.clone()).unwrap();
+/// assert_eq!(rebalanced.sale_purchases_needed.stock_value(holdings::StockSymbol::VV).unwrap(), -500.0);
+/// assert_eq!(rebalanced.sale_purchases_needed.stock_value(holdings::StockSymbol::BND).unwrap(), 0.0);
+/// assert_eq!(rebalanced.target.stock_value(cash.clone()).unwrap(), 500.0);
+///
+/// // The dropped bond sale was paying for the stock purchase, which is cut back to the cash
+/// let mut current = holdings::ShareValues::new();
+/// current.add_stock_value(holdings::StockSymbol::VV, 4600.0).unwrap();
+/// current.add_stock_value(holdings::StockSymbol::BND, 5300.0).unwrap();
+/// current.add_stock_value(cash.clone(), 100.0).unwrap();
+/// let account = holdings::AccountHoldings::new(current, target, (target - current) / quotes);
+///
+/// let rebalanced = calc::threshold_rebalance(&account, 3.0, 10.0, quotes, cash.clone()).unwrap();
+/// let vv_purchase = rebalanced.sale_purchases_needed.stock_value(holdings::StockSymbol::VV).unwrap();
+/// assert!((vv_purchase - 100.0).abs() < 0.01);
+/// assert_eq!(rebalanced.sale_purchases_needed.stock_value(holdings::StockSymbol::BND).unwrap(), 0.0);
+/// assert!(rebalanced.target.stock_value(cash).unwrap() > -0.01);
+/// ```
+pub fn threshold_rebalance(
+    account: &AccountHoldings,
+    stock_threshold: f32,
+    bond_threshold: f32,
+    quotes: ShareValues,
+    cash_symbol: StockSymbol,
+) -> Result<AccountHoldings> {
+    let total = account.current.total_value();
+    let mut shares_needed = Vec::new();
+    for stock_symbol in StockSymbol::list() {
+        let quote = quotes.stock_value(stock_symbol.clone())?;
+        let current_value = account.current.stock_value(stock_symbol.clone())?;
+        let target_value = account.target.stock_value(stock_symbol.clone())?;
+        let threshold = if stock_symbol.is_stock() {
+            stock_threshold
+        } else {
+            bond_threshold
+        };
+        let drift = (current_value - target_value).abs() / total * 100.0;
+        let kept_shares = if total == 0.0 || drift >= threshold {
+            (target_value - current_value) / quote
+        } else {
+            0.0
+        };
+        shares_needed.push((stock_symbol, quote, kept_shares));
+    }
+    keep_trades(
+        account,
+        shares_needed,
+        0.0,
+        RoundingMode::Fractional,
+        quotes,
+        cash_symbol,
+    )
+}
+
+/// lump_sum_vs_dca compares investing the amount all at once against dollar cost averaging the
+/// amount in equal monthly installments over the number of months.  Both are valued at the end of
+/// the months using the median growth of the expected annual return of the percent stock, from the
+/// market assumptions, reduced by half of the annual volatility squared.  Returns the (lump sum,
+/// dollar cost average) ending values.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::calc::{self, MarketAssumptions};
+///
+/// let assumptions = MarketAssumptions::default();
+/// let (lump_sum, dca) = calc::lump_sum_vs_dca(12000.0, 12, 100.0, 0.15, &assumptions);
+/// assert!(lump_sum > dca);
+/// assert!(dca > 12000.0);
+///
+/// let no_growth = MarketAssumptions {
+///     risk_free: 0.0,
+///     equity_premium: 0.0,
+///     bond_return: 0.0,
+///     inflation: 0.0,
+/// };
+/// let (lump_sum, dca) = calc::lump_sum_vs_dca(12000.0, 12, 100.0, 0.0, &no_growth);
+/// assert_eq!(lump_sum, 12000.0);
+/// assert_eq!(dca, 12000.0);
+/// ```
+pub fn lump_sum_vs_dca(
+    amount: f32,
+    months: u32,
+    percent_stock: f32,
+    volatility: f32,
+    assumptions: &MarketAssumptions,
+) -> (f32, f32) {
+    if months == 0 {
+        return (amount, amount);
+    }
+    let expected_return = assumptions.portfolio_return(percent_stock);
+    let monthly_growth = (expected_return - volatility.powi(2) / 2.0) / 12.0;
+    let lump_sum = amount * (monthly_growth * months as f32).exp();
+    let installment = amount / months as f32;
+    let dca = (0..months)
+        .map(|month| installment * (monthly_growth * (months - month) as f32).exp())
+        .sum();
+    (lump_sum, dca)
+}
+
+/// allocation_for_income_goal estimates the stock/bond allocation needed for the current value to
+/// grow, over the years, into the portfolio size that supports the target annual income at the
+/// withdrawal rate.  This uses the default MarketAssumptions, see
+/// allocation_for_income_goal_with_assumptions.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::calc;
+///
+/// // 1,000,000 is needed for 40,000 a year at a 4% withdrawal rate
+/// let allocations = calc::allocation_for_income_goal(500000.0, 15, 40000.0, 0.04).unwrap();
+/// assert_eq!(allocations.total_stock(), 43.2);
+/// assert_eq!(allocations.total_bond(), 56.8);
+///
+/// // Doubling within 5 years is not expected even with all stock
+/// assert!(calc::allocation_for_income_goal(500000.0, 5, 40000.0, 0.04).is_err());
+/// ```
+pub fn allocation_for_income_goal(
+    current: f32,
+    years: u32,
+    target_income: f32,
+    withdrawal_rate: f32,
+) -> Result<Allocations> {
+    allocation_for_income_goal_with_assumptions(
+        current,
+        years,
+        target_income,
+        withdrawal_rate,
+        &MarketAssumptions::default(),
+    )
+}
+
+/// allocation_for_income_goal_with_assumptions estimates the stock/bond allocation needed for the
+/// current value to grow, over the years, into the portfolio size that supports the target annual
+/// income at the withdrawal rate.  The required annual return is met by mixing the expected stock
+/// and bond returns of the market assumptions, and the stock percent is rounded to one decimal.
+/// Errors if the goal needs a return greater than an all stock portfolio.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::calc::{self, MarketAssumptions};
+///
+/// let assumptions = MarketAssumptions { equity_premium: 0.05, ..Default::default() };
+/// let allocations =
+///     calc::allocation_for_income_goal_with_assumptions(500000.0, 15, 40000.0, 0.04, &assumptions)
+///         .unwrap();
+/// assert!(allocations.total_stock() < 43.2);
+///
+/// // Doubling within 5 years is feasible with a 15% stock return
+/// let assumptions = MarketAssumptions { equity_premium: 0.11, ..Default::default() };
+/// assert!(calc::allocation_for_income_goal_with_assumptions(500000.0, 5, 40000.0, 0.04, &assumptions).is_ok());
+/// ```
+pub fn allocation_for_income_goal_with_assumptions(
+    current: f32,
+    years: u32,
+    target_income: f32,
+    withdrawal_rate: f32,
+    assumptions: &MarketAssumptions,
+) -> Result<Allocations> {
+    ensure!(
+        current > 0.0,
+        "Current value needs to be greater than 0.  Current input: {}",
+        current
+    );
+    ensure!(
+        years > 0,
+        "Years needs to be greater than 0.  Years input: {}",
+        years
+    );
+    ensure!(
+        withdrawal_rate > 0.0 && withdrawal_rate <= 1.0,
+        "Withdrawal rate needs to be between 0 and 1.  Withdrawal rate input: {}",
+        withdrawal_rate
+    );
+    let goal = target_income / withdrawal_rate;
+    let required_return = (goal / current).powf(1.0 / years as f32) - 1.0;
+    ensure!(
+        required_return <= assumptions.stock_return(),
+        "Income goal is not feasible.  Required annual return of {:.2}% is greater than the expected stock return of {:.2}%",
+        required_return * 100.0,
+        assumptions.stock_return() * 100.0
+    );
+    let stock_fraction = ((required_return - assumptions.bond_return)
+        / (assumptions.stock_return() - assumptions.bond_return))
+        .clamp(0.0, 1.0);
+    let stock_percent = (stock_fraction * 1000.0).round() / 10.0;
+    Allocations::custom(stock_percent, 100.0 - stock_percent, 0.0)
+}
+
+/// project_value projects the current value over the years with the expected return of the
+/// percent stock, in today's dollars by removing the expected inflation.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::calc::{self, MarketAssumptions};
+///
+/// let assumptions = MarketAssumptions::default();
+/// let projected = calc::project_value(100000.0, 10, 60.0, &assumptions);
+/// assert!((projected - 100000.0 * (1.054_f32 / 1.025).powi(10)).abs() < 1.0);
+///
+/// let higher_inflation = MarketAssumptions { inflation: 0.04, ..assumptions };
+/// assert!(calc::project_value(100000.0, 10, 60.0, &higher_inflation) < projected);
+/// let higher_premium = MarketAssumptions { equity_premium: 0.05, ..assumptions };
+/// assert!(calc::project_value(100000.0, 10, 60.0, &higher_premium) > projected);
+/// assert_eq!(calc::project_value(100000.0, 0, 60.0, &assumptions), 100000.0);
+/// ```
+pub fn project_value(
+    current: f32,
+    years: u32,
+    percent_stock: f32,
+    assumptions: &MarketAssumptions,
+) -> f32 {
+    let real_growth =
+        (1.0 + assumptions.portfolio_return(percent_stock)) / (1.0 + assumptions.inflation);
+    current * real_growth.powi(years as i32)
+}
+
+/// time_weighted_return calculates the time weighted return, as a fraction, of an account from
+/// its transactions and its values over time.  The values are the account value at the end of each
+/// date, which includes any cash flow on that date.  The cash flows into and out of the account
+/// are removed from each period between values so that only the investment growth is measured.
+///
+/// # Example
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use vapore_gui::{calc, holdings::{StockSymbol, Transaction, TransactionType}};
+///
+/// let jan = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+/// let jun = NaiveDate::from_ymd_opt(2023, 6, 30).unwrap();
+/// let jul = NaiveDate::from_ymd_opt(2023, 7, 1).unwrap();
+/// let dec = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+///
+/// // 10% growth, a 1000 deposit, then another 10% growth
+/// let transactions = [Transaction::new(1, jul, StockSymbol::Empty, 0.0, 1000.0, TransactionType::Fundsreceived)];
+/// let values = [(jan, 1000.0), (jun, 1100.0), (jul, 2100.0), (dec, 2310.0)];
+///
+/// let twr = calc::time_weighted_return(&transactions, &values);
+/// assert!((twr - 0.21).abs() < 0.0001);
+/// ```
+pub fn time_weighted_return(
+    transactions: &[Transaction],
+    values_over_time: &[(NaiveDate, f32)],
+) -> f32 {
+    let mut values = values_over_time.to_vec();
+    values.sort_by_key(|(date, _)| *date);
+    let mut growth = 1.0;
+    for period in values.windows(2) {
+        let ((start_date, start_value), (end_date, end_value)) = (period[0], period[1]);
+        if start_value == 0.0 {
+            continue;
+        }
+        let cash_flow: f32 = transactions
+            .iter()
+            .filter(|transaction| {
+                transaction.trade_date() > start_date && transaction.trade_date() <= end_date
+            })
+            .filter_map(|transaction| transaction.cash_flow())
+            .sum();
+        growth *= (end_value - cash_flow) / start_value;
+    }
+    growth - 1.0
+}
+
+/// internal_rate_of_return calculates the money weighted annual return, as a fraction, from the
+/// cash flows into and out of an account and its current value.  Deposits are positive and
+/// withdrawals are negative, with the current value as of the as_of date, which is usually today.
+/// The rate is solved for with bisection and errors if there are no cash flows or no rate between
+/// -99% and 1000% is found.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{Duration, NaiveDate};
+/// use vapore_gui::calc;
+///
+/// let as_of = NaiveDate::from_ymd_opt(2024, 6, 30).unwrap();
+/// let cash_flows = [
+///     (as_of - Duration::days(730), 1000.0),
+///     (as_of - Duration::days(365), 1000.0),
+/// ];
+///
+/// // 1000 grows to 1210 over two years and 1000 grows to 1100 over one year at 10%
+/// let irr = calc::internal_rate_of_return(&cash_flows, 2310.0, as_of).unwrap();
+/// assert!((irr - 0.10).abs() < 0.0001);
+/// assert!(calc::internal_rate_of_return(&[], 2310.0, as_of).is_err());
+/// ```
+pub fn internal_rate_of_return(
+    cash_flows: &[(NaiveDate, f32)],
+    current_value: f32,
+    as_of: NaiveDate,
+) -> Result<f32> {
+    ensure!(!cash_flows.is_empty(), "No cash flows to calculate the IRR");
+    // Value of the cash flows as of the date when grown at the rate minus the current value
+    let future_value_difference = |rate: f64| -> f64 {
+        cash_flows
+            .iter()
+            .map(|(date, cash_flow)| {
+                let years = (as_of - *date).num_days() as f64 / 365.0;
+                *cash_flow as f64 * (1.0 + rate).powf(years)
+            })
+            .sum::<f64>()
+            - current_value as f64
+    };
+    let mut low = -0.99;
+    let mut high = 10.0;
+    let low_difference = future_value_difference(low);
+    ensure!(
+        low_difference.signum() != future_value_difference(high).signum(),
+        "IRR could not be found between -99% and 1000%"
+    );
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if future_value_difference(mid).signum() == low_difference.signum() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(((low + high) / 2.0) as f32)
+}
+
+/// select_lots_to_sell chooses which lots to sell for the number of shares, following the lot
+/// strategy.  Lots with the highest cost basis are sold first, and with LongTermFirst all long term
+/// lots are sold before any short term lots, as of today.  The last lot selected only contains
+/// the shares still needed, and fewer shares are selected if the lots do not hold enough.
+///
+/// # Example
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use vapore_gui::{calc, holdings::{Lot, LotStrategy, StockSymbol}};
+///
+/// let today = NaiveDate::from_ymd_opt(2024, 6, 30).unwrap();
+/// let long_low = Lot::new(StockSymbol::VV, 10.0, 50.0, NaiveDate::from_ymd_opt(2022, 6, 30).unwrap());
+/// let short_high = Lot::new(StockSymbol::VV, 10.0, 90.0, NaiveDate::from_ymd_opt(2024, 5, 31).unwrap());
+/// let long_mid = Lot::new(StockSymbol::VV, 10.0, 70.0, NaiveDate::from_ymd_opt(2023, 5, 26).unwrap());
+/// let lots = [long_low.clone(), short_high.clone(), long_mid.clone()];
+///
+/// let sold = calc::select_lots_to_sell(&lots, 15.0, LotStrategy::HighestBasis, today);
+/// assert_eq!(sold, vec![short_high, Lot { shares: 5.0, ..long_mid.clone() }]);
+///
+/// let sold = calc::select_lots_to_sell(&lots, 15.0, LotStrategy::LongTermFirst, today);
+/// assert_eq!(sold, vec![long_mid, Lot { shares: 5.0, ..long_low }]);
+/// ```
+pub fn select_lots_to_sell(
+    lots: &[Lot],
+    amount: f32,
+    strategy: LotStrategy,
+    today: NaiveDate,
+) -> Vec<Lot> {
+    let mut ordered_lots = lots.to_vec();
+    ordered_lots.sort_by(|lot_a, lot_b| lot_b.cost_basis.total_cmp(&lot_a.cost_basis));
+    if strategy == LotStrategy::LongTermFirst {
+        // Stable sort keeps the highest basis order within the long and short term lots
+        ordered_lots.sort_by_key(|lot| !lot.is_long_term(today));
+    }
+    let mut remaining = amount;
+    let mut selected = Vec::new();
+    for mut lot in ordered_lots {
+        if remaining <= 0.0 {
+            break;
+        }
+        lot.shares = lot.shares.min(remaining);
+        remaining -= lot.shares;
+        selected.push(lot);
+    }
+    selected
+}
+
+/// next_rebalance_date returns the date the next calendar rebalance is due after the last
+/// rebalance.  Dates past the end of a shorter month are moved to the last day of that month.
+///
+/// # Example
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use vapore_gui::{calc, holdings::RebalanceFrequency};
+///
+/// let last = NaiveDate::from_ymd_opt(2023, 1, 31).unwrap();
+///
+/// assert_eq!(
+///     calc::next_rebalance_date(last, RebalanceFrequency::Monthly),
+///     NaiveDate::from_ymd_opt(2023, 2, 28).unwrap()
+/// );
+/// assert_eq!(
+///     calc::next_rebalance_date(last, RebalanceFrequency::Quarterly),
+///     NaiveDate::from_ymd_opt(2023, 4, 30).unwrap()
+/// );
+/// assert_eq!(
+///     calc::next_rebalance_date(last, RebalanceFrequency::Annually),
+///     NaiveDate::from_ymd_opt(2024, 1, 31).unwrap()
+/// );
+/// ```
+pub fn next_rebalance_date(last: NaiveDate, frequency: RebalanceFrequency) -> NaiveDate {
+    last + Months::new(frequency.months())
+}
+
+/// should_rebalance_now returns whether the next calendar rebalance after the last rebalance is
+/// due on or before today.  This can be combined with threshold_rebalance for a policy which
+/// rebalances on a schedule and only trades the symbols which have drifted.
+///
+/// # Example
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use vapore_gui::{calc, holdings::RebalanceFrequency};
+///
+/// let last = NaiveDate::from_ymd_opt(2023, 3, 15).unwrap();
+/// let today = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+///
+/// assert!(calc::should_rebalance_now(last, RebalanceFrequency::Monthly, today));
+/// assert!(!calc::should_rebalance_now(last, RebalanceFrequency::Quarterly, today));
+/// assert!(calc::should_rebalance_now(
+///     last,
+///     RebalanceFrequency::Quarterly,
+///     NaiveDate::from_ymd_opt(2023, 6, 15).unwrap()
+/// ));
+/// assert!(!calc::should_rebalance_now(last, RebalanceFrequency::Annually, today));
+/// ```
+pub fn should_rebalance_now(
+    last: NaiveDate,
+    frequency: RebalanceFrequency,
+    today: NaiveDate,
+) -> bool {
+    today >= next_rebalance_date(last, frequency)
+}
+
+/// allocation_trend returns the household stock, bond, and inflation protected percents of each
+/// snapshot, from the combined values of all of the accounts, along with when the snapshot was
+/// created.  The trend is sorted from the oldest to the newest snapshot for a drift chart and
+/// snapshots without any invested holdings are left out.
+///
+/// # Example
+///
+/// ```
+/// use chrono::{Duration, Utc};
+/// use vapore_gui::{
+///     calc,
+///     holdings::{ShareValues, StockSymbol, VanguardHoldings},
+/// };
+///
+/// let now = Utc::now();
+/// let mut snapshots = Vec::new();
+/// for (days_ago, stock_value) in [(0, 7000.0), (60, 5000.0), (30, 6000.0)] {
+///     let mut brokerage = ShareValues::new();
+///     brokerage.add_stock_value(StockSymbol::VV, stock_value).unwrap();
+///     let mut roth = ShareValues::new();
+///     roth.add_stock_value(StockSymbol::BND, 10000.0 - stock_value).unwrap();
+///     let mut snapshot = VanguardHoldings::new(ShareValues::new_quote());
+///     snapshot.accounts_values.insert(1, brokerage);
+///     snapshot.accounts_values.insert(2, roth);
+///     snapshot.set_parsed_at(now - Duration::days(days_ago));
+///     snapshots.push(snapshot);
+/// }
+///
+/// let trend = calc::allocation_trend(&snapshots);
+/// assert_eq!(trend.len(), 3);
+/// assert_eq!(trend[0].0, (now - Duration::days(60)).naive_utc());
+/// assert_eq!(trend[0].1, (50.0, 50.0, 0.0));
+/// assert_eq!(trend[1].1, (60.0, 40.0, 0.0));
+/// assert_eq!(trend[2].1, (70.0, 30.0, 0.0));
+/// ```
+pub fn allocation_trend(snapshots: &[VanguardHoldings]) -> Vec<(NaiveDateTime, (f32, f32, f32))> {
+    let mut trend = snapshots
+        .iter()
+        .filter_map(|snapshot| {
+            let account_numbers = snapshot
+                .accounts_values
+                .keys()
+                .copied()
+                .collect::<Vec<u32>>();
+            let household = snapshot.combined_values(&account_numbers);
+            let (stock, bond, inflation) = household.percent_stock_bond_infl();
+            if stock.is_nan() {
+                return None;
+            }
+            Some((snapshot.parsed_at().naive_utc(), (stock, bond, inflation)))
+        })
+        .collect::<Vec<(NaiveDateTime, (f32, f32, f32))>>();
+    trend.sort_by_key(|(parsed_at, _)| *parsed_at);
+    trend
+}
@@ -1,14 +1,24 @@
-use crate::asset::SubAllocations;
-use anyhow::Result;
-#[cfg(not(target_arch = "wasm32"))]
-use chrono::Duration;
-use chrono::NaiveDate;
+use crate::asset::{Allocations, AssetClass, SubAllocations};
+use crate::calc::{select_lots_to_sell, MarketAssumptions};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 #[cfg(not(target_arch = "wasm32"))]
 use futures::executor::block_on;
+use futures::future::try_join_all;
+use futures::{
+    future::{select, Either},
+    pin_mut,
+};
+use futures_timer::Delay;
+use std::future::Future;
 use std::{
     collections::HashMap,
     fmt,
+    fs::File,
+    io::{BufRead, BufReader},
     ops::{Add, Div, Mul, Sub},
+    path::PathBuf,
     vec::Vec,
 };
 #[cfg(not(target_arch = "wasm32"))]
@@ -34,10 +44,346 @@ lazy_static! {
     };
 }
 
+// HEADER_ALIASES holds the column headers of older and newer Vanguard downloads along with the
+// header the parser matches them as, since Vanguard periodically renames columns
+lazy_static! {
+    static ref HEADER_ALIASES: HashMap<&'static str, &'static str> = {
+        let mut m = HashMap::new();
+        m.insert("Account", "Account Number");
+        m.insert("Account #", "Account Number");
+        m.insert("Fund Symbol", "Symbol");
+        m.insert("Ticker", "Symbol");
+        m.insert("Quantity", "Shares");
+        m.insert("Share Quantity", "Shares");
+        m.insert("Price", "Share Price");
+        m.insert("Last Price", "Share Price");
+        m.insert("Market Value", "Total Value");
+        m.insert("Current Value", "Total Value");
+        m.insert("Transaction Date", "Trade Date");
+        m.insert("Amount", "Net Amount");
+        m.insert("Activity Type", "Transaction Type");
+        m
+    };
+}
+
+// EXPENSE_RATIO holds the expense ratios, in percent, for the stock symbols which is used to
+// calculate the blended expense ratio of holdings
+lazy_static! {
+    static ref EXPENSE_RATIO: HashMap<StockSymbol, f32> = {
+        let mut m = HashMap::new();
+        m.insert(StockSymbol::VV, 0.04);
+        m.insert(StockSymbol::VO, 0.04);
+        m.insert(StockSymbol::VB, 0.05);
+        m.insert(StockSymbol::VTC, 0.04);
+        m.insert(StockSymbol::BND, 0.03);
+        m.insert(StockSymbol::VXUS, 0.08);
+        m.insert(StockSymbol::VWO, 0.08);
+        m.insert(StockSymbol::BNDX, 0.07);
+        m.insert(StockSymbol::VTIP, 0.04);
+        m.insert(StockSymbol::VMFXX, 0.11);
+        m
+    };
+}
+
+// ESG_SCORE holds approximate ESG quality scores, from 0 to 10, for the stock funds.  Symbols
+// without a score are left out of the weighted ESG score.  The table can be cloned and edited to
+// override the scores with weighted_esg_score_with
+lazy_static! {
+    pub static ref ESG_SCORE: HashMap<StockSymbol, f32> = {
+        let mut m = HashMap::new();
+        m.insert(StockSymbol::VV, 6.6);
+        m.insert(StockSymbol::VO, 6.0);
+        m.insert(StockSymbol::VB, 5.5);
+        m.insert(StockSymbol::VXUS, 6.9);
+        m.insert(StockSymbol::VWO, 5.3);
+        m
+    };
+}
+
+// MAX_REGISTERED_SYMBOLS is the number of stock symbols a SymbolRegistry can hold, as each
+// registered symbol keeps its own value within ShareValues
+pub const MAX_REGISTERED_SYMBOLS: usize = 16;
+
+/// RegisteredSymbol is a stock symbol registered within a SymbolRegistry along with the asset
+/// class that it fills and the slot where ShareValues keeps its value.  These are created by
+/// SymbolRegistry::register_symbol.  When rebalanced with calc::to_buy, registered holdings stay
+/// within the account holding them and count towards their asset class.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::{asset::AssetClass, calc, holdings};
+///
+/// let mut registry = holdings::SymbolRegistry::new();
+/// let vti = registry.register_symbol("VTI", AssetClass::UsStockLarge).unwrap();
+/// let quotes = holdings::ShareValues::new_quote();
+/// let mut traditional = holdings::ShareValues::new();
+/// traditional.add_stock_value(holdings::StockSymbol::VMFXX, 50000.0).unwrap();
+/// let mut roth = holdings::ShareValues::new();
+/// roth.add_stock_value(vti.clone(), 2000.0).unwrap();
+/// roth.add_stock_value(holdings::StockSymbol::VMFXX, 8000.0).unwrap();
+/// let options = calc::RebalanceOptions {
+///     retirement_year: 2050,
+///     roth_ira: calc::AccountInputs::new(roth),
+///     traditional_ira: calc::AccountInputs::new(traditional),
+///     ..Default::default()
+/// };
+/// let rebalance = calc::to_buy(&options, quotes).unwrap();
+/// assert_eq!(rebalance.roth_ira.target.stock_value(vti.clone()).unwrap(), 2000.0);
+/// assert_eq!(rebalance.roth_ira.sale_purchases_needed.stock_value(vti).unwrap(), 0.0);
+/// assert!((rebalance.roth_ira.target.total_value() - 10000.0).abs() < 0.1);
+/// assert!((rebalance.retirement_target().total_value() - 60000.0).abs() < 0.1);
+///
+/// // The registered holding takes the place of the same value of VV
+/// let mut cash_roth = holdings::ShareValues::new();
+/// cash_roth.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+/// let cash_options = calc::RebalanceOptions {
+///     roth_ira: calc::AccountInputs::new(cash_roth),
+///     ..options
+/// };
+/// let cash_target = calc::to_buy(&cash_options, quotes).unwrap().retirement_target();
+/// let vv = |target: holdings::ShareValues| target.stock_value(holdings::StockSymbol::VV).unwrap();
+/// assert!((vv(rebalance.retirement_target()) + 2000.0 - vv(cash_target)).abs() < 0.1);
+/// ```
+#[derive(Clone, Eq, Hash, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct RegisteredSymbol {
+    symbol: String,
+    asset_class: AssetClass,
+    slot: usize,
+}
+
+impl RegisteredSymbol {
+    /// symbol returns the ticker of the registered stock symbol
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// asset_class returns the asset class the registered stock symbol fills
+    pub fn asset_class(&self) -> AssetClass {
+        self.asset_class
+    }
+}
+
+/// SymbolRegistry holds the user registered stock symbols, beyond the supported Vanguard ETFs,
+/// along with the asset class each fills.  Registered symbols are parsed as
+/// StockSymbol::Registered instead of StockSymbol::Other when the registry is passed to
+/// parse_csv_download_with or parse_ofx_download_with, which counts them towards their asset
+/// class instead of leaving them out of the rebalance.  Each symbol keeps its own value, so
+/// multiple symbols can fill the same asset class.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::{asset::AssetClass, holdings};
+///
+/// let mut registry = holdings::SymbolRegistry::new();
+/// let vti = registry.register_symbol("VTI", AssetClass::UsStockLarge).unwrap();
+/// let schb = registry.register_symbol("SCHB", AssetClass::UsStockLarge).unwrap();
+///
+/// assert_eq!(registry.stock_symbol("VTI"), vti);
+/// assert_eq!(vti.asset_class(), Some(AssetClass::UsStockLarge));
+/// assert_eq!(vti.description(), "VTI: Registered as UsStockLarge");
+/// assert_eq!(registry.stock_symbol("BND"), holdings::StockSymbol::BND);
+/// assert_eq!(
+///     registry.stock_symbol("SWAGX"),
+///     holdings::StockSymbol::Other("SWAGX".to_string())
+/// );
+/// assert!(registry.register_symbol("BND", AssetClass::UsTotalBond).is_err());
+/// assert!(registry.register_symbol("VTI", AssetClass::UsStockMid).is_err());
+///
+/// // Symbols within the same asset class keep their own values
+/// let mut new_values = holdings::ShareValues::new();
+/// new_values.add_stock_value(vti.clone(), 1000.0).unwrap();
+/// new_values.add_stock_value(schb.clone(), 2000.0).unwrap();
+/// assert_eq!(new_values.stock_value(vti).unwrap(), 1000.0);
+/// assert_eq!(new_values.stock_value(schb).unwrap(), 2000.0);
+/// ```
+#[derive(Clone, PartialEq, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct SymbolRegistry {
+    symbols: Vec<RegisteredSymbol>,
+}
+
+impl SymbolRegistry {
+    /// new creates an empty SymbolRegistry
+    pub fn new() -> Self {
+        SymbolRegistry {
+            symbols: Vec::new(),
+        }
+    }
+
+    /// register_symbol adds a stock symbol to the registry along with the asset class that it
+    /// fills and returns the StockSymbol::Registered to use for it.  The supported Vanguard ETFs,
+    /// the empty symbol, and symbols which are already registered cannot be registered, and errors
+    /// once MAX_REGISTERED_SYMBOLS are registered.  Symbols registered within the same asset class
+    /// each keep their own quote and value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset::AssetClass, holdings};
+    ///
+    /// let mut registry = holdings::SymbolRegistry::new();
+    /// let vti = registry.register_symbol("VTI", AssetClass::UsStockLarge).unwrap();
+    /// let schb = registry.register_symbol("SCHB", AssetClass::UsStockLarge).unwrap();
+    /// assert_ne!(vti, schb);
+    ///
+    /// let mut quotes = holdings::ShareValues::new_quote();
+    /// quotes.add_stock_value(vti.clone(), 250.0).unwrap();
+    /// quotes.add_stock_value(schb.clone(), 60.0).unwrap();
+    /// assert_eq!(quotes.stock_value(vti.clone()).unwrap(), 250.0);
+    /// assert_eq!(quotes.stock_value(schb.clone()).unwrap(), 60.0);
+    ///
+    /// // Both fill the US large cap stock of the target
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(vti, 1000.0).unwrap();
+    /// current.add_stock_value(schb, 3000.0).unwrap();
+    /// assert_eq!(current.total_value(), 4000.0);
+    /// assert_eq!(current.percent_stock_bond_infl(), (100.0, 0.0, 0.0));
+    /// ```
+    pub fn register_symbol(
+        &mut self,
+        symbol: &str,
+        asset_class: AssetClass,
+    ) -> Result<StockSymbol> {
+        ensure!(
+            matches!(self.stock_symbol(symbol), StockSymbol::Other(_)),
+            "Stock symbol {:?} is supported, empty, or already registered",
+            symbol
+        );
+        ensure!(
+            self.symbols.len() < MAX_REGISTERED_SYMBOLS,
+            "Only {} stock symbols can be registered",
+            MAX_REGISTERED_SYMBOLS
+        );
+        let registered = RegisteredSymbol {
+            symbol: symbol.to_string(),
+            asset_class,
+            slot: self.symbols.len(),
+        };
+        self.symbols.push(registered.clone());
+        Ok(StockSymbol::Registered(registered))
+    }
+
+    /// stock_symbol creates the StockSymbol for the string value, which is
+    /// StockSymbol::Registered for registered symbols and otherwise the same as StockSymbol::new
+    pub fn stock_symbol(&self, symbol: &str) -> StockSymbol {
+        self.symbols
+            .iter()
+            .find(|registered| registered.symbol == symbol)
+            .map_or_else(
+                || StockSymbol::new(symbol),
+                |registered| StockSymbol::Registered(registered.clone()),
+            )
+    }
+
+    /// symbols returns the registered stock symbols in the order they were registered
+    pub fn symbols(&self) -> Vec<StockSymbol> {
+        self.symbols
+            .iter()
+            .cloned()
+            .map(StockSymbol::Registered)
+            .collect()
+    }
+}
+
+/// FundMeta holds the expense ratio and the yield, both in percent, of a fund loaded with
+/// load_fund_metadata
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct FundMeta {
+    pub expense_ratio: f32,
+    pub yield_percent: f32,
+}
+
+/// load_fund_metadata parses a CSV of the current expense ratios and yields, in percent, with the
+/// header symbol,expense_ratio,yield.  The returned metadata can be passed to
+/// blended_expense_ratio_with in place of the built in expense ratios, and its yields to
+/// yield_on_cost.  Symbols need to be either supported or registered within the registry.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::holdings;
+///
+/// let csv_path = std::env::temp_dir().join("vapore_fund_metadata_example.csv");
+/// std::fs::write(&csv_path, "symbol,expense_ratio,yield\nVXUS,0.05,3.2\nBND,0.03,3.6\n").unwrap();
+/// let registry = holdings::SymbolRegistry::new();
+/// let fund_metadata = holdings::load_fund_metadata(csv_path.clone(), &registry).unwrap();
+/// std::fs::remove_file(csv_path).unwrap();
+///
+/// assert_eq!(
+///     fund_metadata[&holdings::StockSymbol::VXUS],
+///     holdings::FundMeta { expense_ratio: 0.05, yield_percent: 3.2 }
+/// );
+///
+/// let mut new_values = holdings::ShareValues::new();
+/// new_values.add_stock_value(holdings::StockSymbol::VXUS, 5000.0).unwrap();
+/// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
+/// assert!((new_values.blended_expense_ratio_with(&fund_metadata) - 0.04).abs() < 0.0001);
+/// // Loading the metadata does not change the built in expense ratios
+/// assert!((new_values.blended_expense_ratio() - 0.055).abs() < 0.0001);
+///
+/// let yields = fund_metadata
+///     .iter()
+///     .map(|(stock_symbol, fund_meta)| (stock_symbol.clone(), fund_meta.yield_percent))
+///     .collect();
+/// assert!((new_values.yield_on_cost(&new_values, &yields) - 3.4).abs() < 0.0001);
+/// ```
+pub fn load_fund_metadata(
+    csv_path: PathBuf,
+    registry: &SymbolRegistry,
+) -> Result<HashMap<StockSymbol, FundMeta>> {
+    let csv_file = File::open(&csv_path)
+        .with_context(|| format!("Fund metadata file {:?} not found", csv_path))?;
+    let mut header = Vec::new();
+    let mut fund_metadata = HashMap::new();
+    for row_result in BufReader::new(csv_file).lines() {
+        let row = row_result?;
+        if row.trim().is_empty() {
+            continue;
+        }
+        let row_split = split_csv_row(&row)
+            .iter()
+            .map(|value| value.trim().to_string())
+            .collect::<Vec<String>>();
+        if header.is_empty() {
+            header = row_split;
+            ensure!(
+                header == ["symbol", "expense_ratio", "yield"],
+                "Header of fund metadata ({:?}) does not match ['symbol','expense_ratio','yield']",
+                header
+            );
+            continue;
+        }
+        ensure!(
+            row_split.len() == 3,
+            "Fund metadata row {} does not have 3 values",
+            row
+        );
+        let stock_symbol = registry.stock_symbol(&row_split[0]);
+        ensure!(
+            !matches!(stock_symbol, StockSymbol::Other(_) | StockSymbol::Empty),
+            "Fund metadata symbol {} is not supported or registered",
+            row_split[0]
+        );
+        let fund_meta = FundMeta {
+            expense_ratio: row_split[1]
+                .parse::<f32>()
+                .with_context(|| format!("Expense ratio of {} is not a number", row_split[0]))?,
+            yield_percent: row_split[2]
+                .parse::<f32>()
+                .with_context(|| format!("Yield of {} is not a number", row_split[0]))?,
+        };
+        fund_metadata.insert(stock_symbol, fund_meta);
+    }
+    Ok(fund_metadata)
+}
+
 /// StockSymbol is an enum which holds all stock symbols which are supported.  Empty is used to
-/// initiated structs which use this enum.  Other<String> is a holder of any stock that is not
+/// initiated structs which use this enum.  Registered<RegisteredSymbol> is a holder of a stock
+/// registered within a SymbolRegistry, MoneyMarket<String> is a holder of a money market fund
+/// other than VMFXX, which is held as cash, and Other<String> is a holder of any stock that is not
 /// supported, where the String is the stock symbol.
-#[derive(Clone, Eq, Hash, PartialEq, Debug)]
+#[derive(Clone, Eq, Hash, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
 pub enum StockSymbol {
     VXUS,
     BNDX,
@@ -50,19 +396,23 @@ pub enum StockSymbol {
     VV,
     VMFXX,
     Empty,
+    Registered(RegisteredSymbol),
+    MoneyMarket(String),
     Other(String),
 }
 
 impl StockSymbol {
-    /// new creates a new StockSymbol enum based on the string value.
+    /// new creates a new StockSymbol enum based on the string value.  Money market funds, which
+    /// have five letter symbols ending in XX, are StockSymbol::MoneyMarket.
     ///
     ///  # Example
     ///
     ///  ```
-    ///  use vapore::holdings::StockSymbol;
+    ///  use vapore_gui::holdings::StockSymbol;
     ///
     ///  let bnd = StockSymbol::new("BND");
     ///  assert_eq!(bnd, StockSymbol::BND);
+    ///  assert_eq!(StockSymbol::new("VMRXX"), StockSymbol::MoneyMarket("VMRXX".to_string()));
     ///  ```
     pub fn new(symbol: &str) -> Self {
         match symbol {
@@ -77,6 +427,9 @@ impl StockSymbol {
             "VV" => StockSymbol::VV,
             "VMFXX" => StockSymbol::VMFXX,
             "" => StockSymbol::Empty,
+            _ if symbol.len() == 5 && symbol.ends_with("XX") => {
+                StockSymbol::MoneyMarket(symbol.to_string())
+            }
             _ => StockSymbol::Other(symbol.to_string()),
         }
     }
@@ -87,7 +440,7 @@ impl StockSymbol {
     /// # Example
     ///
     /// ```
-    ///  use vapore::holdings::StockSymbol;
+    ///  use vapore_gui::holdings::StockSymbol;
     ///
     ///  let bnd = StockSymbol::new("BND");
     ///  let bnd_description = bnd.description();
@@ -95,6 +448,15 @@ impl StockSymbol {
     ///
     /// ```
     pub fn description(&self) -> String {
+        if let StockSymbol::Registered(registered) = self {
+            return format!(
+                "{}: Registered as {:?}",
+                registered.symbol, registered.asset_class
+            );
+        }
+        if let StockSymbol::MoneyMarket(symbol) = self {
+            return format!("{}: Money market cash", symbol);
+        }
         let description_option = STOCK_DESCRIPTION.get(self);
         if let Some(description) = description_option {
             format!("{:?}: {}", self, description)
@@ -103,6 +465,66 @@ impl StockSymbol {
         }
     }
 
+    /// is_cash returns whether the stock symbol is VMFXX or another money market fund, which are
+    /// all held within the cash of the ShareValues
+    pub fn is_cash(&self) -> bool {
+        matches!(self, StockSymbol::VMFXX | StockSymbol::MoneyMarket(_))
+    }
+
+    /// is_stock returns whether the stock symbol is one of the supported stock ETFs
+    pub fn is_stock(&self) -> bool {
+        matches!(
+            self,
+            StockSymbol::VV
+                | StockSymbol::VO
+                | StockSymbol::VB
+                | StockSymbol::VXUS
+                | StockSymbol::VWO
+        )
+    }
+
+    /// is_bond returns whether the stock symbol is one of the supported bond ETFs, which includes
+    /// inflation protected securities
+    pub fn is_bond(&self) -> bool {
+        matches!(
+            self,
+            StockSymbol::BND | StockSymbol::VTC | StockSymbol::BNDX | StockSymbol::VTIP
+        )
+    }
+
+    /// asset_class returns the asset class that the stock symbol fills.  Cash, empty, and other
+    /// stock symbols return None.
+    pub fn asset_class(&self) -> Option<AssetClass> {
+        match self {
+            StockSymbol::VV => Some(AssetClass::UsStockLarge),
+            StockSymbol::VO => Some(AssetClass::UsStockMid),
+            StockSymbol::VB => Some(AssetClass::UsStockSmall),
+            StockSymbol::BND => Some(AssetClass::UsTotalBond),
+            StockSymbol::VTC => Some(AssetClass::UsCorpBond),
+            StockSymbol::VXUS => Some(AssetClass::IntTotalStock),
+            StockSymbol::VWO => Some(AssetClass::IntEmergingStock),
+            StockSymbol::BNDX => Some(AssetClass::IntBond),
+            StockSymbol::VTIP => Some(AssetClass::InflationProtected),
+            StockSymbol::Registered(registered) => Some(registered.asset_class),
+            _ => None,
+        }
+    }
+
+    // The supported Vanguard ETF which fills the asset class
+    fn from_asset_class(asset_class: AssetClass) -> Self {
+        match asset_class {
+            AssetClass::UsStockLarge => StockSymbol::VV,
+            AssetClass::UsStockMid => StockSymbol::VO,
+            AssetClass::UsStockSmall => StockSymbol::VB,
+            AssetClass::UsTotalBond => StockSymbol::BND,
+            AssetClass::UsCorpBond => StockSymbol::VTC,
+            AssetClass::IntTotalStock => StockSymbol::VXUS,
+            AssetClass::IntEmergingStock => StockSymbol::VWO,
+            AssetClass::IntBond => StockSymbol::BNDX,
+            AssetClass::InflationProtected => StockSymbol::VTIP,
+        }
+    }
+
     pub fn list() -> [StockSymbol; 9] {
         [
             StockSymbol::VV,
@@ -118,6 +540,28 @@ impl StockSymbol {
     }
 }
 
+/// HoldingLabel labels each value stored within ShareValues.  The supported ETFs and VMFXX are
+/// labeled by their stock symbol, while the combined other stock symbols and the outside stock and
+/// bond values, which are not a single stock symbol, have their own labels.
+#[derive(Clone, PartialEq, Debug)]
+pub enum HoldingLabel {
+    Symbol(StockSymbol),
+    Other,
+    OutsideStock,
+    OutsideBond,
+}
+
+impl fmt::Display for HoldingLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HoldingLabel::Symbol(stock_symbol) => write!(f, "{:?}", stock_symbol),
+            HoldingLabel::Other => write!(f, "Other"),
+            HoldingLabel::OutsideStock => write!(f, "Outside stock"),
+            HoldingLabel::OutsideBond => write!(f, "Outside bond"),
+        }
+    }
+}
+
 /// all_stock_descriptions returns a String containing the description of all stocks which are
 /// supported with each separated by a new line.  This is used to display on screen or write to
 /// file all of the descriptions.
@@ -125,7 +569,7 @@ impl StockSymbol {
 /// # Example
 ///
 /// ```
-/// use vapore::holdings;
+/// use vapore_gui::holdings;
 ///
 /// let descriptions = holdings::all_stock_descriptions();
 /// println!("{}", descriptions);
@@ -155,9 +599,9 @@ pub fn all_stock_descriptions() -> String {
 pub struct StockInfo {
     pub account_number: u32,
     pub symbol: StockSymbol,
-    pub share_price: f32,
-    pub shares: f32,
-    pub total_value: f32,
+    pub share_price: f64,
+    pub shares: f64,
+    pub total_value: f64,
     account_added: bool,
     symbol_added: bool,
     share_price_added: bool,
@@ -172,7 +616,7 @@ impl StockInfo {
     /// # Example
     ///
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let mut new_stock = holdings::StockInfo::new();
     /// ```
@@ -196,7 +640,7 @@ impl StockInfo {
     /// # Example
     ///
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let mut new_stock = holdings::StockInfo::new();
     /// new_stock.add_account(123456789);
@@ -213,7 +657,7 @@ impl StockInfo {
     /// # Example
     ///
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let mut new_stock = holdings::StockInfo::new();
     /// new_stock.add_account(123456789);
@@ -231,7 +675,7 @@ impl StockInfo {
     /// # Example
     ///
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let mut new_stock = holdings::StockInfo::new();
     /// new_stock.add_account(123456789);
@@ -240,7 +684,7 @@ impl StockInfo {
     ///
     /// assert_eq!(new_stock.share_price, 234.50);
     /// ```
-    pub fn add_share_price(&mut self, share_price: f32) {
+    pub fn add_share_price(&mut self, share_price: f64) {
         self.share_price = share_price;
         self.share_price_added = true;
     }
@@ -250,17 +694,17 @@ impl StockInfo {
     /// # Example
     ///
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let mut new_stock = holdings::StockInfo::new();
     /// new_stock.add_account(123456789);
     /// new_stock.add_symbol(holdings::StockSymbol::BND);
     /// new_stock.add_share_price(234.50);
-    /// new_stock.add_shares(10.0)
+    /// new_stock.add_shares(10.0);
     ///
     /// assert_eq!(new_stock.shares, 10.0);
     /// ```
-    pub fn add_shares(&mut self, share_num: f32) {
+    pub fn add_shares(&mut self, share_num: f64) {
         self.shares = share_num;
         self.shares_added = true;
     }
@@ -270,7 +714,7 @@ impl StockInfo {
     /// # Example
     ///
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let mut new_stock = holdings::StockInfo::new();
     /// new_stock.add_account(123456789);
@@ -280,7 +724,7 @@ impl StockInfo {
     ///
     /// assert_eq!(new_stock.total_value, 5000.00);
     /// ```
-    pub fn add_total_value(&mut self, total_value: f32) {
+    pub fn add_total_value(&mut self, total_value: f64) {
         self.total_value = total_value;
         self.total_value_added = true;
     }
@@ -290,7 +734,7 @@ impl StockInfo {
     /// # Example
     ///
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let mut new_stock = holdings::StockInfo::new();
     /// new_stock.add_account(123456789);
@@ -323,64 +767,436 @@ impl Default for StockInfo {
     }
 }
 
+// QUOTE_CACHE_MINUTES is the default age in minutes below which retrieved quotes are reused
+// instead of being requested again
+pub const QUOTE_CACHE_MINUTES: i64 = 15;
+// QUOTE_TIMEOUT is the default time to wait on each quote request before giving up
+pub const QUOTE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+// QUOTE_ATTEMPTS is the default number of times a quote is requested before giving up and
+// QUOTE_BACKOFF is the default wait after the first failed attempt, which doubles each attempt
+pub const QUOTE_ATTEMPTS: u32 = 3;
+pub const QUOTE_BACKOFF: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// QuoteFetchOptions holds the settings used when requesting quotes from Yahoo.  The timeout is
+/// applied to each request, the attempts are the total number of tries, and the backoff is the
+/// wait after the first failed attempt, which doubles after each further failure.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct QuoteFetchOptions {
+    pub timeout: std::time::Duration,
+    pub attempts: u32,
+    pub backoff: std::time::Duration,
+}
+
+impl Default for QuoteFetchOptions {
+    fn default() -> Self {
+        QuoteFetchOptions {
+            timeout: QUOTE_TIMEOUT,
+            attempts: QUOTE_ATTEMPTS,
+            backoff: QUOTE_BACKOFF,
+        }
+    }
+}
+
+// Waits for the duration without blocking the executor.  The async timer works with any executor,
+// including the browser's on wasm32
+async fn sleep(duration: std::time::Duration) {
+    Delay::new(duration).await;
+}
+
+/// with_retries runs the quote request up to the number of attempts within the options, waiting
+/// with an exponential backoff between failed attempts.  The last error is returned when all of
+/// the attempts fail.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::anyhow;
+/// use futures::executor::block_on;
+/// use std::time::Duration;
+/// use vapore_gui::holdings;
+///
+/// let options = holdings::QuoteFetchOptions {
+///     timeout: Duration::from_secs(1),
+///     attempts: 3,
+///     backoff: Duration::from_millis(10),
+/// };
+///
+/// let mut tries = 0;
+/// let result = block_on(holdings::with_retries(options, || {
+///     tries += 1;
+///     let attempt = tries;
+///     async move {
+///         if attempt < 3 {
+///             Err(anyhow!("Server error"))
+///         } else {
+///             Ok(234.5)
+///         }
+///     }
+/// }));
+/// assert_eq!(result.unwrap(), 234.5);
+///
+/// let result = block_on(holdings::with_retries(options, || async {
+///     Err::<f32, _>(anyhow!("Server error"))
+/// }));
+/// assert!(result.unwrap_err().to_string().contains("after 3 attempts"));
+/// ```
+pub async fn with_retries<F, Fut>(options: QuoteFetchOptions, mut request: F) -> Result<f32>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<f32>>,
+{
+    let attempts = options.attempts.max(1);
+    let mut backoff = options.backoff;
+    let mut attempt = 1;
+    loop {
+        let error = match request().await {
+            Ok(quote) => return Ok(quote),
+            Err(error) => error,
+        };
+        if attempt >= attempts {
+            return Err(error)
+                .with_context(|| format!("Quote request failed after {} attempts", attempts));
+        }
+        sleep(backoff).await;
+        backoff *= 2;
+        attempt += 1;
+    }
+}
+
+/// with_timeout awaits the future and returns an error if it does not finish within the timeout.
+/// This keeps a hung quote request from blocking indefinitely.  The timer is an async timer that
+/// works with any executor and is cancelled when the future finishes first.
+///
+/// # Example
+///
+/// ```
+/// use futures::executor::block_on;
+/// use std::time::Duration;
+/// use vapore_gui::holdings;
+///
+/// let never_resolves = futures::future::pending::<f32>();
+/// let result = block_on(holdings::with_timeout(never_resolves, Duration::from_millis(50)));
+/// assert!(result.unwrap_err().to_string().contains("timed out"));
+///
+/// let resolves = futures::future::ready(234.5);
+/// let result = block_on(holdings::with_timeout(resolves, Duration::from_secs(1)));
+/// assert_eq!(result.unwrap(), 234.5);
+/// ```
+pub async fn with_timeout<F: Future>(future: F, timeout: std::time::Duration) -> Result<F::Output> {
+    pin_mut!(future);
+    match select(future, Delay::new(timeout)).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(anyhow!("Quote request timed out after {:?}", timeout)),
+    }
+}
+
+// Yahoo symbol of the supported and registered stock symbols, where any other stock symbol is None
 #[cfg(not(target_arch = "wasm32"))]
-pub async fn get_yahoo_quote(stock_symbol: StockSymbol) -> Result<f32> {
-    let stock_str = match stock_symbol {
-        StockSymbol::VO => "VO",
-        StockSymbol::VB => "VB",
-        StockSymbol::VV => "VV",
-        StockSymbol::BND => "BND",
-        StockSymbol::VWO => "VWO",
-        StockSymbol::VTC => "VTC",
-        StockSymbol::VXUS => "VXUS",
-        StockSymbol::BNDX => "BNDX",
-        StockSymbol::VTIP => "VTIP",
-        _ => "none",
-    };
-    if stock_str == "none" {
+fn yahoo_symbol(stock_symbol: StockSymbol) -> Option<String> {
+    match stock_symbol {
+        StockSymbol::VO => Some("VO".to_string()),
+        StockSymbol::VB => Some("VB".to_string()),
+        StockSymbol::VV => Some("VV".to_string()),
+        StockSymbol::BND => Some("BND".to_string()),
+        StockSymbol::VWO => Some("VWO".to_string()),
+        StockSymbol::VTC => Some("VTC".to_string()),
+        StockSymbol::VXUS => Some("VXUS".to_string()),
+        StockSymbol::BNDX => Some("BNDX".to_string()),
+        StockSymbol::VTIP => Some("VTIP".to_string()),
+        StockSymbol::Registered(registered) => Some(registered.symbol),
+        _ => None,
+    }
+}
+
+/// get_yahoo_quote retrieves the latest quote of the stock symbol from Yahoo, retrying with the
+/// fetch options when the request fails.  Unsupported stock symbols, other than registered ones,
+/// return 1.0.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn get_yahoo_quote(stock_symbol: StockSymbol, options: QuoteFetchOptions) -> Result<f32> {
+    if let Some(stock_str) = yahoo_symbol(stock_symbol) {
+        with_retries(options, || fetch_yahoo_quote(&stock_str, options.timeout)).await
+    } else {
         Ok(1.0)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn fetch_yahoo_quote(stock_str: &str, timeout: std::time::Duration) -> Result<f32> {
+    let provider = yahoo::YahooConnector::new()?;
+    let response_err = with_timeout(provider.get_latest_quotes(stock_str, "1m"), timeout).await?;
+    // If the market is closed, an error occurs.  If so, get quote history then the last quote
+    if let Ok(response) = response_err {
+        Ok(response.last_quote()?.close as f32)
     } else {
-        let provider = yahoo::YahooConnector::new()?;
-        let response_err = provider.get_latest_quotes(stock_str, "1m").await;
-        // If the market is closed, an error occurs.  If so, get quote history then the last quote
-        if let Ok(response) = response_err {
-            Ok(response.last_quote()?.close as f32)
-        } else {
-            let today = OffsetDateTime::now_utc();
-            let week_ago = today - time::Duration::days(7);
-            let response = provider
-                .get_quote_history(stock_str, week_ago, today)
-                .await?;
-            Ok(response.last_quote()?.close as f32)
-        }
+        let today = OffsetDateTime::now_utc();
+        let week_ago = today - time::Duration::days(7);
+        let response = with_timeout(
+            provider.get_quote_history(stock_str, week_ago, today),
+            timeout,
+        )
+        .await??;
+        Ok(response.last_quote()?.close as f32)
     }
 }
 
+/// get_yahoo_eoy_quote retrieves the last quote of the year for the stock symbol from Yahoo,
+/// retrying with the fetch options when the request fails.  Unsupported stock symbols return 1.0.
 #[cfg(not(target_arch = "wasm32"))]
-pub async fn get_yahoo_eoy_quote(stock_symbol: StockSymbol, year: u32) -> Result<f32> {
-    let stock_str = match stock_symbol {
-        StockSymbol::VO => "VO",
-        StockSymbol::VB => "VB",
-        StockSymbol::VV => "VV",
-        StockSymbol::BND => "BND",
-        StockSymbol::VWO => "VWO",
-        StockSymbol::VTC => "VTC",
-        StockSymbol::VXUS => "VXUS",
-        StockSymbol::BNDX => "BNDX",
-        StockSymbol::VTIP => "VTIP",
-        _ => "none",
-    };
-    if stock_str == "none" {
-        Ok(1.0)
+pub async fn get_yahoo_eoy_quote(
+    stock_symbol: StockSymbol,
+    year: u32,
+    options: QuoteFetchOptions,
+) -> Result<f32> {
+    if let Some(stock_str) = yahoo_symbol(stock_symbol) {
+        with_retries(options, || {
+            fetch_yahoo_eoy_quote(&stock_str, year, options.timeout)
+        })
+        .await
     } else {
-        let provider = yahoo::YahooConnector::new()?;
-        let format = format_description!(
-            "[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour sign:mandatory]"
-        );
-        let start = OffsetDateTime::parse(&format!("{}-12-25 00:00:01 -05", year), format)?;
-        let stop = OffsetDateTime::parse(&format!("{}-12-31 23:59:59 -05", year), format)?;
-        let response = provider.get_quote_history(stock_str, start, stop).await?;
-        Ok(response.quotes()?.last().unwrap().close as f32)
+        Ok(1.0)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn fetch_yahoo_eoy_quote(
+    stock_str: &str,
+    year: u32,
+    timeout: std::time::Duration,
+) -> Result<f32> {
+    let provider = yahoo::YahooConnector::new()?;
+    let format = format_description!(
+        "[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour sign:mandatory]"
+    );
+    let start = OffsetDateTime::parse(&format!("{}-12-25 00:00:01 -05", year), format)?;
+    let stop = OffsetDateTime::parse(&format!("{}-12-31 23:59:59 -05", year), format)?;
+    let response =
+        with_timeout(provider.get_quote_history(stock_str, start, stop), timeout).await??;
+    Ok(response.quotes()?.last().unwrap().close as f32)
+}
+
+/// QuoteProvider is a source of stock quotes, which allows quote sources other than Yahoo to be
+/// used.  The async latest and eoy quotes default to the blocking quote and quote_at, and are
+/// overridden by providers which request quotes over the network.
+#[async_trait]
+pub trait QuoteProvider: Send + Sync {
+    /// quote returns the latest share price for the stock symbol
+    fn quote(&self, stock_symbol: StockSymbol) -> Result<f32>;
+
+    /// quote_at returns the closing share price for the stock symbol on the date.  Providers
+    /// without historical quotes return an error.
+    fn quote_at(&self, stock_symbol: StockSymbol, date: NaiveDate) -> Result<f32> {
+        bail!(
+            "Historical quotes are not supported.  Symbol: {:?}, date: {}",
+            stock_symbol,
+            date
+        )
+    }
+
+    /// latest retrieves the latest share price for the stock symbol
+    async fn latest(&self, stock_symbol: &StockSymbol) -> Result<f32> {
+        self.quote(stock_symbol.clone())
+    }
+
+    /// eoy retrieves the last share price of the year for the stock symbol, which defaults to the
+    /// closing price on December 31st
+    async fn eoy(&self, stock_symbol: &StockSymbol, year: u32) -> Result<f32> {
+        let date = NaiveDate::from_ymd_opt(year as i32, 12, 31)
+            .ok_or_else(|| anyhow!("Invalid year: {}", year))?;
+        self.quote_at(stock_symbol.clone(), date)
+    }
+}
+
+/// YahooProvider retrieves quotes from Yahoo with the fetch options.  This is the provider used
+/// when updating the holdings with Yahoo quotes.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct YahooProvider {
+    pub options: QuoteFetchOptions,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl YahooProvider {
+    /// new creates a YahooProvider which requests quotes with the fetch options
+    pub fn new(options: QuoteFetchOptions) -> Self {
+        YahooProvider { options }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait]
+impl QuoteProvider for YahooProvider {
+    fn quote(&self, stock_symbol: StockSymbol) -> Result<f32> {
+        block_on(get_yahoo_quote(stock_symbol, self.options))
+    }
+
+    async fn latest(&self, stock_symbol: &StockSymbol) -> Result<f32> {
+        get_yahoo_quote(stock_symbol.clone(), self.options).await
+    }
+
+    async fn eoy(&self, stock_symbol: &StockSymbol, year: u32) -> Result<f32> {
+        get_yahoo_eoy_quote(stock_symbol.clone(), year, self.options).await
+    }
+}
+
+/// StaticProvider returns quotes from a fixed set of share prices, which allows quotes to be used
+/// without network requests, such as within tests or the website.  Stock symbols without a quote
+/// return an error.
+///
+/// # Example
+///
+/// ```
+/// use futures::executor::block_on;
+/// use std::collections::HashMap;
+/// use vapore_gui::holdings::{QuoteProvider, ShareValues, StaticProvider, StockSymbol};
+///
+/// let mut provider = StaticProvider::new(HashMap::from([(StockSymbol::BND, 72.5)]));
+/// provider.add_eoy_quote(StockSymbol::BND, 2023, 70.0);
+/// assert_eq!(block_on(provider.latest(&StockSymbol::BND)).unwrap(), 72.5);
+/// assert_eq!(block_on(provider.eoy(&StockSymbol::BND, 2023)).unwrap(), 70.0);
+/// assert!(block_on(provider.latest(&StockSymbol::VV)).is_err());
+/// ```
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct StaticProvider {
+    quotes: HashMap<StockSymbol, f32>,
+    eoy_quotes: HashMap<(StockSymbol, u32), f32>,
+}
+
+impl StaticProvider {
+    /// new creates a StaticProvider which returns the latest quotes given
+    pub fn new(quotes: HashMap<StockSymbol, f32>) -> Self {
+        StaticProvider {
+            quotes,
+            eoy_quotes: HashMap::new(),
+        }
+    }
+
+    /// add_eoy_quote sets the last share price of the year for the stock symbol
+    pub fn add_eoy_quote(&mut self, stock_symbol: StockSymbol, year: u32, quote: f32) {
+        self.eoy_quotes.insert((stock_symbol, year), quote);
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for StaticProvider {
+    fn quote(&self, stock_symbol: StockSymbol) -> Result<f32> {
+        self.quotes
+            .get(&stock_symbol)
+            .copied()
+            .ok_or_else(|| anyhow!("No quote for {:?}", stock_symbol))
+    }
+
+    async fn eoy(&self, stock_symbol: &StockSymbol, year: u32) -> Result<f32> {
+        self.eoy_quotes
+            .get(&(stock_symbol.clone(), year))
+            .copied()
+            .ok_or_else(|| anyhow!("No {} end of year quote for {:?}", year, stock_symbol))
+    }
+}
+
+/// ChainedProvider tries each quote provider in order until one returns a quote, which allows a
+/// primary provider to be used with fallbacks for when it is unavailable.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::{bail, Result};
+/// use vapore_gui::holdings::{ChainedProvider, QuoteProvider, StockSymbol};
+///
+/// struct UnavailableProvider;
+/// impl QuoteProvider for UnavailableProvider {
+///     fn quote(&self, _stock_symbol: StockSymbol) -> Result<f32> {
+///         bail!("Provider unavailable")
+///     }
+/// }
+///
+/// struct FixedProvider(f32);
+/// impl QuoteProvider for FixedProvider {
+///     fn quote(&self, _stock_symbol: StockSymbol) -> Result<f32> {
+///         Ok(self.0)
+///     }
+/// }
+///
+/// let provider = ChainedProvider::new(vec![
+///     Box::new(UnavailableProvider),
+///     Box::new(FixedProvider(72.5)),
+/// ]);
+/// assert_eq!(provider.quote(StockSymbol::BND).unwrap(), 72.5);
+///
+/// let provider = ChainedProvider::new(vec![Box::new(UnavailableProvider)]);
+/// assert!(provider.quote(StockSymbol::BND).is_err());
+/// ```
+pub struct ChainedProvider {
+    providers: Vec<Box<dyn QuoteProvider>>,
+}
+
+impl ChainedProvider {
+    /// new creates a ChainedProvider which tries the providers in the order given
+    pub fn new(providers: Vec<Box<dyn QuoteProvider>>) -> Self {
+        ChainedProvider { providers }
+    }
+}
+
+impl ChainedProvider {
+    // Returns the first successful quote from the providers or an error with all of the provider
+    // errors
+    fn first_quote<F>(&self, stock_symbol: &StockSymbol, get_quote: F) -> Result<f32>
+    where
+        F: Fn(&dyn QuoteProvider) -> Result<f32>,
+    {
+        let mut errors = Vec::new();
+        for provider in &self.providers {
+            match get_quote(provider.as_ref()) {
+                Ok(quote) => return Ok(quote),
+                Err(error) => errors.push(error.to_string()),
+            }
+        }
+        Err(chained_error(stock_symbol, errors))
+    }
+}
+
+// Error for when all of the chained providers failed to return a quote
+fn chained_error(stock_symbol: &StockSymbol, errors: Vec<String>) -> anyhow::Error {
+    anyhow!(
+        "All quote providers failed for {:?}: {}",
+        stock_symbol,
+        errors.join("; ")
+    )
+}
+
+#[async_trait]
+impl QuoteProvider for ChainedProvider {
+    fn quote(&self, stock_symbol: StockSymbol) -> Result<f32> {
+        self.first_quote(&stock_symbol, |provider| {
+            provider.quote(stock_symbol.clone())
+        })
+    }
+
+    fn quote_at(&self, stock_symbol: StockSymbol, date: NaiveDate) -> Result<f32> {
+        self.first_quote(&stock_symbol, |provider| {
+            provider.quote_at(stock_symbol.clone(), date)
+        })
+    }
+
+    async fn latest(&self, stock_symbol: &StockSymbol) -> Result<f32> {
+        let mut errors = Vec::new();
+        for provider in &self.providers {
+            match provider.latest(stock_symbol).await {
+                Ok(quote) => return Ok(quote),
+                Err(error) => errors.push(error.to_string()),
+            }
+        }
+        Err(chained_error(stock_symbol, errors))
+    }
+
+    async fn eoy(&self, stock_symbol: &StockSymbol, year: u32) -> Result<f32> {
+        let mut errors = Vec::new();
+        for provider in &self.providers {
+            match provider.eoy(stock_symbol, year).await {
+                Ok(quote) => return Ok(quote),
+                Err(error) => errors.push(error.to_string()),
+            }
+        }
+        Err(chained_error(stock_symbol, errors))
     }
 }
 
@@ -393,22 +1209,60 @@ pub enum AddType {
 }
 
 /// ShareValues holds the values for the supported ETF stocks.  The value can represent price,
-/// holding value, stock quantity etc.
-#[derive(Clone, PartialEq, Debug, Copy)]
+/// holding value, stock quantity etc.  Values are stored as f64 so that rounding errors do not
+/// accumulate across a large portfolio.  They are returned as f32, with add_stock_value_f64,
+/// stock_value_f64, and total_value_f64 keeping the full precision.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::holdings;
+///
+/// let mut new_values = holdings::ShareValues::new();
+/// new_values.add_stock_value(holdings::StockSymbol::VXUS, 16777216.0).unwrap();
+/// for stock_symbol in [
+///     holdings::StockSymbol::BNDX,
+///     holdings::StockSymbol::BND,
+///     holdings::StockSymbol::VWO,
+///     holdings::StockSymbol::VO,
+///     holdings::StockSymbol::VB,
+///     holdings::StockSymbol::VTC,
+///     holdings::StockSymbol::VV,
+///     holdings::StockSymbol::VMFXX,
+/// ] {
+///     new_values.add_stock_value(stock_symbol, 0.75).unwrap();
+/// }
+///
+/// // Summing as f32 loses each 0.75 added to the large holding
+/// let f32_total = (0..8).fold(16777216.0_f32, |total, _| total + 0.75);
+/// assert_eq!(f32_total, 16777216.0);
+/// assert_eq!(new_values.total_value(), 16777222.0);
+///
+/// // The f64 accessors keep the cents which f32 rounds away
+/// new_values.add_stock_value_f64(holdings::StockSymbol::VXUS, 16777216.01).unwrap();
+/// assert_eq!(new_values.stock_value(holdings::StockSymbol::VXUS).unwrap(), 16777216.0);
+/// assert_eq!(new_values.stock_value_f64(holdings::StockSymbol::VXUS).unwrap(), 16777216.01);
+/// assert_eq!(new_values.total_value_f64(), 16777222.01);
+/// ```
+#[derive(Clone, PartialEq, Debug, Copy, serde::Deserialize, serde::Serialize)]
+#[serde(default)] // values added after the save file was written default to 0
 pub struct ShareValues {
-    vxus: f32,
-    bndx: f32,
-    bnd: f32,
-    vwo: f32,
-    vo: f32,
-    vb: f32,
-    vtc: f32,
-    vv: f32,
-    vtip: f32,
-    vmfxx: f32,
-    other: f32,
-    outside_bond: f32,
-    outside_stock: f32,
+    vxus: f64,
+    bndx: f64,
+    bnd: f64,
+    vwo: f64,
+    vo: f64,
+    vb: f64,
+    vtc: f64,
+    vv: f64,
+    vtip: f64,
+    vmfxx: f64,
+    other: f64,
+    registered: [f64; MAX_REGISTERED_SYMBOLS], // values of each registered symbol by slot
+    registered_classes: [Option<AssetClass>; MAX_REGISTERED_SYMBOLS],
+    other_classified: [f64; 9], // other values by asset class, which are also within other
+    outside_bond: f64,
+    outside_stock: f64,
 }
 
 impl ShareValues {
@@ -417,7 +1271,7 @@ impl ShareValues {
     ///
     /// # Example
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let new_values = holdings::ShareValues::new();
     /// ```
@@ -434,12 +1288,263 @@ impl ShareValues {
             vv: 0.0,
             other: 0.0,
             vmfxx: 0.0,
+            registered: [0.0; MAX_REGISTERED_SYMBOLS],
+            registered_classes: [None; MAX_REGISTERED_SYMBOLS],
+            other_classified: [0.0; 9],
             outside_bond: 0.0,
             outside_stock: 0.0,
         }
     }
 
-    pub fn value_added(&self, default_value: f32) -> bool {
+    // Labels of the stored values in the same order as values and values_mut
+    fn labels() -> [HoldingLabel; 13] {
+        [
+            HoldingLabel::Symbol(StockSymbol::VXUS),
+            HoldingLabel::Symbol(StockSymbol::BNDX),
+            HoldingLabel::Symbol(StockSymbol::VTIP),
+            HoldingLabel::Symbol(StockSymbol::BND),
+            HoldingLabel::Symbol(StockSymbol::VWO),
+            HoldingLabel::Symbol(StockSymbol::VO),
+            HoldingLabel::Symbol(StockSymbol::VB),
+            HoldingLabel::Symbol(StockSymbol::VTC),
+            HoldingLabel::Symbol(StockSymbol::VV),
+            HoldingLabel::Symbol(StockSymbol::VMFXX),
+            HoldingLabel::Other,
+            HoldingLabel::OutsideStock,
+            HoldingLabel::OutsideBond,
+        ]
+    }
+
+    // Stored values, where outside stock and outside bond are the last two as they are not held
+    // within the account
+    fn values(&self) -> [f64; 13] {
+        [
+            self.vxus,
+            self.bndx,
+            self.vtip,
+            self.bnd,
+            self.vwo,
+            self.vo,
+            self.vb,
+            self.vtc,
+            self.vv,
+            self.vmfxx,
+            self.other,
+            self.outside_stock,
+            self.outside_bond,
+        ]
+    }
+
+    fn values_mut(&mut self) -> [&mut f64; 13] {
+        [
+            &mut self.vxus,
+            &mut self.bndx,
+            &mut self.vtip,
+            &mut self.bnd,
+            &mut self.vwo,
+            &mut self.vo,
+            &mut self.vb,
+            &mut self.vtc,
+            &mut self.vv,
+            &mut self.vmfxx,
+            &mut self.other,
+            &mut self.outside_stock,
+            &mut self.outside_bond,
+        ]
+    }
+
+    /// iter returns an iterator of the label and value pairs for the nine supported ETFs, VMFXX,
+    /// other, outside stock, and outside bond.  Values are returned as f64 to keep precision on
+    /// large holdings.  Registered stock symbols are not included.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VB, 4000.0).unwrap();
+    /// new_values.add_outside_stock_value(1000.0);
+    ///
+    /// let held = new_values
+    ///     .iter()
+    ///     .filter(|(_, value)| *value > 0.0)
+    ///     .map(|(label, _)| label)
+    ///     .collect::<Vec<holdings::HoldingLabel>>();
+    /// assert_eq!(
+    ///     held,
+    ///     vec![
+    ///         holdings::HoldingLabel::Symbol(holdings::StockSymbol::BND),
+    ///         holdings::HoldingLabel::Symbol(holdings::StockSymbol::VB),
+    ///         holdings::HoldingLabel::OutsideStock
+    ///     ]
+    /// );
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (HoldingLabel, f64)> {
+        Self::labels().into_iter().zip(self.values())
+    }
+
+    /// pie_data returns the (label, value) pairs of the values held within the account that are
+    /// not zero, to at least a cent, which can be fed directly into a pie chart.  The supported
+    /// ETFs are labeled by their stock symbol, other stock symbols as "Other", and registered
+    /// stock symbols by their asset class.  Outside stock and bond are left out since they are not
+    /// held within the account.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 6000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 4000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VMFXX, 0.0).unwrap();
+    /// new_values.add_outside_stock_value(1000.0);
+    ///
+    /// assert_eq!(
+    ///     new_values.pie_data(),
+    ///     vec![("BND".to_string(), 4000.0), ("VV".to_string(), 6000.0)]
+    /// );
+    /// assert!(holdings::ShareValues::new().pie_data().is_empty());
+    /// ```
+    pub fn pie_data(&self) -> Vec<(String, f32)> {
+        let held_values = self.iter().filter_map(|(label, value)| match label {
+            HoldingLabel::OutsideStock | HoldingLabel::OutsideBond => None,
+            label => Some((label.to_string(), value as f32)),
+        });
+        let registered_values = AssetClass::list().into_iter().map(|asset_class| {
+            (
+                format!("{:?}", asset_class),
+                self.registered_value(asset_class) as f32,
+            )
+        });
+        held_values
+            .chain(registered_values)
+            .filter(|(_, value)| value.abs() >= 0.01)
+            .collect()
+    }
+
+    /// iter_mut returns an iterator of the label and mutable value pairs in the same order and
+    /// with the same labels as iter.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VB, 4000.0).unwrap();
+    ///
+    /// for (_, value) in new_values.iter_mut() {
+    ///     *value *= 2.0;
+    /// }
+    ///
+    /// assert_eq!(new_values.total_value(), 18000.0);
+    /// ```
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (HoldingLabel, &mut f64)> {
+        Self::labels().into_iter().zip(self.values_mut())
+    }
+
+    // Combines each value, including the registered and classified other values, with the matching
+    // value of the other ShareValues through the operation
+    fn combine(mut self, other: ShareValues, operation: impl Fn(f64, f64) -> f64) -> ShareValues {
+        for (value, other_value) in self.values_mut().into_iter().zip(other.values()) {
+            *value = operation(*value, other_value)
+        }
+        for (value, other_value) in self.registered.iter_mut().zip(other.registered) {
+            *value = operation(*value, other_value)
+        }
+        for (asset_class, other_class) in self
+            .registered_classes
+            .iter_mut()
+            .zip(other.registered_classes)
+        {
+            *asset_class = asset_class.or(other_class)
+        }
+        for (value, other_value) in self.other_classified.iter_mut().zip(other.other_classified) {
+            *value = operation(*value, other_value)
+        }
+        self
+    }
+
+    // Applies the operation to each value, including the registered and classified other values
+    fn map_values(mut self, operation: impl Fn(f64) -> f64) -> ShareValues {
+        for value in self.values_mut() {
+            *value = operation(*value)
+        }
+        for value in self.registered.iter_mut() {
+            *value = operation(*value)
+        }
+        for value in self.other_classified.iter_mut() {
+            *value = operation(*value)
+        }
+        self
+    }
+
+    /// scale returns the values with every value, including the outside stock and bond,
+    /// multiplied by the factor, such as to model the account after it grows by 10%.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 6000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 4000.0).unwrap();
+    ///
+    /// let scaled = new_values.scale(1.5);
+    /// assert_eq!(scaled.stock_value(holdings::StockSymbol::VV).unwrap(), 9000.0);
+    /// assert_eq!(scaled.total_value(), 15000.0);
+    /// ```
+    pub fn scale(&self, factor: f64) -> ShareValues {
+        self.map_values(|value| value * factor)
+    }
+
+    /// abs returns the absolute value of every value, such as the size of each purchase or sale
+    /// no matter the direction.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut trades = holdings::ShareValues::new();
+    /// trades.add_stock_value(holdings::StockSymbol::VV, -2000.0).unwrap();
+    /// trades.add_stock_value(holdings::StockSymbol::BND, 1500.0).unwrap();
+    ///
+    /// let magnitudes = trades.abs();
+    /// assert_eq!(magnitudes.stock_value(holdings::StockSymbol::VV).unwrap(), 2000.0);
+    /// assert_eq!(magnitudes.stock_value(holdings::StockSymbol::BND).unwrap(), 1500.0);
+    /// ```
+    pub fn abs(&self) -> ShareValues {
+        self.map_values(f64::abs)
+    }
+
+    /// sum_abs returns the sum of the absolute values held within the account, which is the total
+    /// dollars traded when used on the dollar value of purchases and sales.  Outside stock and bond
+    /// are left out the same as total_value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut trades = holdings::ShareValues::new();
+    /// trades.add_stock_value(holdings::StockSymbol::VV, -2000.0).unwrap();
+    /// trades.add_stock_value(holdings::StockSymbol::BND, 1500.0).unwrap();
+    /// trades.add_stock_value(holdings::StockSymbol::VMFXX, 500.0).unwrap();
+    ///
+    /// assert_eq!(trades.total_value(), 0.0);
+    /// assert_eq!(trades.sum_abs(), 4000.0);
+    /// ```
+    pub fn sum_abs(&self) -> f32 {
+        self.abs().total_value()
+    }
+
+    pub fn value_added(&self, default_value: f32) -> bool {
         [
             self.vxus,
             self.bndx,
@@ -455,7 +1560,8 @@ impl ShareValues {
             self.outside_stock,
         ]
         .iter()
-        .any(|val| val != &default_value)
+        .chain(self.registered.iter())
+        .any(|val| *val as f32 != default_value)
     }
     /// new_quote creates a new ShareValues struct where all values are set to 1.  This is used for
     /// creating a new struct for stock quotes.  This way if any quotes are missing, they are
@@ -465,7 +1571,7 @@ impl ShareValues {
     ///
     /// # Example
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let new_quotes = holdings::ShareValues::new_quote();
     /// ```
@@ -482,49 +1588,86 @@ impl ShareValues {
             vv: 1.0,
             vmfxx: 1.0,
             other: 1.0,
+            registered: [1.0; MAX_REGISTERED_SYMBOLS],
+            registered_classes: [None; MAX_REGISTERED_SYMBOLS],
+            other_classified: [1.0; 9],
             outside_bond: 1.0,
             outside_stock: 1.0,
         }
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    pub async fn add_missing_quotes(&mut self) -> Result<()> {
-        for stock_symbol in [
-            StockSymbol::VV,
-            StockSymbol::VO,
-            StockSymbol::VB,
-            StockSymbol::VTC,
-            StockSymbol::BND,
-            StockSymbol::VXUS,
-            StockSymbol::VWO,
-            StockSymbol::BNDX,
-            StockSymbol::VTIP,
-        ] {
-            if self.stock_value(stock_symbol.clone()) == 1.0 {
-                let new_quote = get_yahoo_quote(stock_symbol.clone()).await?;
-                self.add_stock_value(stock_symbol, new_quote);
+    // Stock symbols, including those of the registry, whose quote is still the 1.0 placeholder
+    // and needs to be retrieved
+    fn missing_quote_symbols(&self, registry: &SymbolRegistry) -> Result<Vec<StockSymbol>> {
+        let mut missing = Vec::new();
+        for stock_symbol in StockSymbol::list().into_iter().chain(registry.symbols()) {
+            if self.stock_value(stock_symbol.clone())? == 1.0 {
+                missing.push(stock_symbol);
             }
         }
+        Ok(missing)
+    }
+
+    /// add_missing_quotes retrieves the latest quotes from the provider for the supported stock
+    /// symbols whose quote is still the 1.0 placeholder.  All of the quotes are requested at the
+    /// same time and the first error is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use std::collections::HashMap;
+    /// use vapore_gui::holdings::{ShareValues, StaticProvider, StockSymbol};
+    ///
+    /// let provider = StaticProvider::new(HashMap::from(StockSymbol::list().map(|stock_symbol| (stock_symbol, 50.0))));
+    /// let mut quotes = ShareValues::new_quote();
+    /// quotes.add_stock_value(StockSymbol::VV, 200.0).unwrap();
+    /// block_on(quotes.add_missing_quotes(&provider)).unwrap();
+    /// assert_eq!(quotes.stock_value(StockSymbol::VV).unwrap(), 200.0);
+    /// assert_eq!(quotes.stock_value(StockSymbol::BND).unwrap(), 50.0);
+    /// ```
+    pub async fn add_missing_quotes(&mut self, provider: &impl QuoteProvider) -> Result<()> {
+        self.add_missing_quotes_with(provider, &SymbolRegistry::new())
+            .await
+    }
+
+    /// add_missing_quotes_with retrieves the latest quotes from the provider the same as
+    /// add_missing_quotes, which includes the registered stock symbols of the registry.
+    pub async fn add_missing_quotes_with(
+        &mut self,
+        provider: &impl QuoteProvider,
+        registry: &SymbolRegistry,
+    ) -> Result<()> {
+        let missing = self.missing_quote_symbols(registry)?;
+        let new_quotes = try_join_all(
+            missing
+                .iter()
+                .map(|stock_symbol| provider.latest(stock_symbol)),
+        )
+        .await?;
+        for (stock_symbol, new_quote) in missing.into_iter().zip(new_quotes) {
+            self.add_stock_value(stock_symbol, new_quote)?;
+        }
         Ok(())
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    pub async fn add_missing_eoy_quotes(&mut self, year: u32) -> Result<()> {
-        for stock_symbol in [
-            StockSymbol::VV,
-            StockSymbol::VO,
-            StockSymbol::VB,
-            StockSymbol::VTC,
-            StockSymbol::BND,
-            StockSymbol::VXUS,
-            StockSymbol::VWO,
-            StockSymbol::BNDX,
-            StockSymbol::VTIP,
-        ] {
-            if self.stock_value(stock_symbol.clone()) == 1.0 {
-                let new_quote = get_yahoo_eoy_quote(stock_symbol.clone(), year).await?;
-                self.add_stock_value(stock_symbol, new_quote);
-            }
+    /// add_missing_eoy_quotes retrieves the end of year quotes from the provider for the
+    /// supported stock symbols whose quote is still the 1.0 placeholder.  All of the quotes are
+    /// requested at the same time and the first error is returned.
+    pub async fn add_missing_eoy_quotes(
+        &mut self,
+        year: u32,
+        provider: &impl QuoteProvider,
+    ) -> Result<()> {
+        let missing = self.missing_quote_symbols(&SymbolRegistry::new())?;
+        let new_quotes = try_join_all(
+            missing
+                .iter()
+                .map(|stock_symbol| provider.eoy(stock_symbol, year)),
+        )
+        .await?;
+        for (stock_symbol, new_quote) in missing.into_iter().zip(new_quotes) {
+            self.add_stock_value(stock_symbol, new_quote)?;
         }
         Ok(())
     }
@@ -542,7 +1685,7 @@ impl ShareValues {
     /// # Example
     ///
     /// ```
-    /// use vapore::{asset, holdings};
+    /// use vapore_gui::{asset, holdings};
     ///
     /// let sub_allocations = asset::SubAllocations::new().unwrap();
     ///
@@ -583,20 +1726,137 @@ impl ShareValues {
 
         // set vmfxx, ie cash, target value to 0 and return ShareValues
         ShareValues {
-            vxus: vxus_value,
-            bndx: bndx_value,
-            bnd: bnd_value,
-            vwo: vwo_value,
-            vo: vo_value,
-            vb: vb_value,
-            vtc: vtc_value,
-            vv: vv_value,
-            vtip: vtip_value,
+            vxus: vxus_value as f64,
+            bndx: bndx_value as f64,
+            bnd: bnd_value as f64,
+            vwo: vwo_value as f64,
+            vo: vo_value as f64,
+            vb: vb_value as f64,
+            vtc: vtc_value as f64,
+            vv: vv_value as f64,
+            vtip: vtip_value as f64,
             other: 0.0,
             vmfxx: 0.0,
-            outside_bond: other_int_bond_value + other_us_bond_value,
-            outside_stock: other_us_stock_value + other_int_stock_value,
+            registered: [0.0; MAX_REGISTERED_SYMBOLS],
+            registered_classes: [None; MAX_REGISTERED_SYMBOLS],
+            other_classified: [0.0; 9],
+            outside_bond: (other_int_bond_value + other_us_bond_value) as f64,
+            outside_stock: (other_us_stock_value + other_int_stock_value) as f64,
+        }
+    }
+
+    /// new_target_with_registered creates a new target ShareValues struct the same as new_target
+    /// while keeping the registered stock symbol values of the holdings.  The target of the
+    /// Vanguard ETF filling each asset class is reduced by the registered value within that asset
+    /// class, so the registered holdings count towards the allocation instead of being bought
+    /// again.  ETF targets do not go below 0, and registered holdings above the target of their
+    /// asset class are taken from the other ETF targets pro rata so the total is unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset::{self, AssetClass}, holdings};
+    ///
+    /// let mut registry = holdings::SymbolRegistry::new();
+    /// let vti = registry.register_symbol("VTI", AssetClass::UsStockLarge).unwrap();
+    ///
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(vti.clone(), 1000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::BND, 9000.0).unwrap();
+    ///
+    /// let sub_allocations = asset::SubAllocations::new().unwrap();
+    /// let target = holdings::ShareValues::new_target_with_registered(
+    ///     sub_allocations, current.total_value(), 0.0, 0.0, 0.0, 0.0, &current,
+    /// );
+    ///
+    /// assert_eq!(target.stock_value(holdings::StockSymbol::VV).unwrap(), 1000.0);
+    /// assert_eq!(target.stock_value(vti.clone()).unwrap(), 1000.0);
+    /// assert_eq!(target.total_value(), 10000.0);
+    ///
+    /// // Registered holdings above the VV target leave VV at 0 and reduce the other targets
+    /// current.add_stock_value(vti.clone(), 3000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::BND, 7000.0).unwrap();
+    /// let sub_allocations = asset::SubAllocations::new().unwrap();
+    /// let target = holdings::ShareValues::new_target_with_registered(
+    ///     sub_allocations, current.total_value(), 0.0, 0.0, 0.0, 0.0, &current,
+    /// );
+    /// assert_eq!(target.stock_value(holdings::StockSymbol::VV).unwrap(), 0.0);
+    /// assert_eq!(target.stock_value(vti).unwrap(), 3000.0);
+    /// assert!((target.total_value() - 10000.0).abs() < 0.01);
+    /// ```
+    pub fn new_target_with_registered(
+        sub_allocations: SubAllocations,
+        total_vanguard_value: f32,
+        other_us_stock_value: f32,
+        other_us_bond_value: f32,
+        other_int_stock_value: f32,
+        other_int_bond_value: f32,
+        holdings: &ShareValues,
+    ) -> Self {
+        let mut target = Self::new_target(
+            sub_allocations,
+            total_vanguard_value,
+            other_us_stock_value,
+            other_us_bond_value,
+            other_int_stock_value,
+            other_int_bond_value,
+        );
+        let mut excess = 0.0;
+        for asset_class in AssetClass::list() {
+            let registered_value = holdings.registered_value(asset_class);
+            if let Ok(value) = target.value_mut(StockSymbol::from_asset_class(asset_class)) {
+                excess += (registered_value - *value).max(0.0);
+                *value = (*value - registered_value).max(0.0)
+            }
+        }
+        // registered holdings above their asset class target are kept, so the excess is taken
+        // from the remaining ETF targets pro rata
+        let remaining = target.values()[..9].iter().sum::<f64>();
+        if excess > 0.0 && remaining > 0.0 {
+            let factor = ((remaining - excess) / remaining).max(0.0);
+            for value in target.values_mut().into_iter().take(9) {
+                *value *= factor
+            }
         }
+        target.registered = holdings.registered;
+        target.registered_classes = holdings.registered_classes;
+        target
+    }
+
+    /// registered_holdings returns only the registered stock symbol values, with every other
+    /// value set to 0.  This is the part of an account which is kept when it is rebalanced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset::AssetClass, holdings};
+    ///
+    /// let mut registry = holdings::SymbolRegistry::new();
+    /// let vti = registry.register_symbol("VTI", AssetClass::UsStockLarge).unwrap();
+    ///
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(vti.clone(), 1000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::BND, 9000.0).unwrap();
+    ///
+    /// let registered = current.registered_holdings();
+    /// assert_eq!(registered.stock_value(vti).unwrap(), 1000.0);
+    /// assert_eq!(registered.total_value(), 1000.0);
+    /// ```
+    pub fn registered_holdings(&self) -> ShareValues {
+        let mut registered = ShareValues::new();
+        registered.registered = self.registered;
+        registered.registered_classes = self.registered_classes;
+        registered
+    }
+
+    // Sum of the registered values which fill the asset class
+    fn registered_value(&self, asset_class: AssetClass) -> f64 {
+        self.registered
+            .iter()
+            .zip(self.registered_classes)
+            .filter(|(_, registered_class)| *registered_class == Some(asset_class))
+            .map(|(value, _)| value)
+            .sum()
     }
 
     /// add_stockinfo_value adds stock value to the ShareValues struct with a StockInfo input.  StockInfo
@@ -605,15 +1865,15 @@ impl ShareValues {
     /// struc.  The add_type is used to distinguish between these two groups to know where from
     /// within the StockInfo struct to pull the dollar amount from.
     ///
-    /// # Panic
+    /// # Errors
     ///
-    /// Panics when an empty stock symbol is passed.  This will happen if the StockInfo struct is
+    /// Errors when an empty stock symbol is passed.  This will happen if the StockInfo struct is
     /// initialized without any content added.
     ///
     /// # Example
     ///
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let mut new_stock = holdings::StockInfo::new();
     /// new_stock.add_account(123456789);
@@ -622,12 +1882,12 @@ impl ShareValues {
     /// new_stock.add_total_value(5000.00);
     ///
     /// let mut new_quotes = holdings::ShareValues::new_quote();
-    /// new_quotes.add_stockinfo_value(new_stock, holdings::AddType::StockPrice);
+    /// new_quotes.add_stockinfo_value(new_stock, holdings::AddType::StockPrice).unwrap();
     ///
-    /// assert_eq!(new_quotes.stock_value(holdings::StockSymbol::BND), 234.50);
+    /// assert_eq!(new_quotes.stock_value(holdings::StockSymbol::BND).unwrap(), 234.50);
     ///
     /// ```
-    pub fn add_stockinfo_value(&mut self, stock_info: StockInfo, add_type: AddType) {
+    pub fn add_stockinfo_value(&mut self, stock_info: StockInfo, add_type: AddType) -> Result<()> {
         let value = match add_type {
             AddType::StockPrice => stock_info.share_price,
             AddType::HoldingValue => stock_info.total_value,
@@ -643,108 +1903,170 @@ impl ShareValues {
             StockSymbol::VB => self.vb = value,
             StockSymbol::VTC => self.vtc = value,
             StockSymbol::VV => self.vv = value,
-            StockSymbol::VMFXX => self.vmfxx = value,
-            StockSymbol::Empty => panic!("Stock symbol not set before adding value"),
+            StockSymbol::VMFXX | StockSymbol::MoneyMarket(_) => match add_type {
+                AddType::StockPrice => self.vmfxx = value,
+                AddType::HoldingValue | AddType::HoldingShares => self.vmfxx += value,
+            },
+            StockSymbol::Empty => bail!("Stock symbol not set before adding value"),
+            StockSymbol::Registered(registered) => {
+                self.registered_classes[registered.slot] = Some(registered.asset_class);
+                match add_type {
+                    AddType::HoldingValue => self.registered[registered.slot] += value,
+                    AddType::StockPrice => self.registered[registered.slot] = value,
+                    AddType::HoldingShares => self.registered[registered.slot] += value,
+                }
+            }
             StockSymbol::Other(_) => match add_type {
                 AddType::HoldingValue => self.other += value,
                 AddType::StockPrice => self.other = 1.0,
                 AddType::HoldingShares => self.other = 1.0,
             },
         }
+        Ok(())
     }
 
     /// add_stock_value adds stock value to the ShareValues struct with a float.  
     ///
-    /// # Panic
+    /// # Errors
     ///
-    /// Panics when an empty stock symbol is passed.  This will happen if the StockInfo struct is
+    /// Errors when an empty stock symbol is passed.  This will happen if the StockInfo struct is
     /// initialized without any content added.
     ///
     /// # Example
     ///
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let mut new_values = holdings::ShareValues::new();
-    /// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0);
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
     ///
-    /// assert_eq!(new_values.stock_value(holdings::StockSymbol::BND), 5000.0);
+    /// assert_eq!(new_values.stock_value(holdings::StockSymbol::BND).unwrap(), 5000.0);
+    /// assert!(new_values
+    ///     .add_stock_value(holdings::StockSymbol::Empty, 5000.0)
+    ///     .is_err());
     ///
     /// ```
-    pub fn add_stock_value(&mut self, stock_symbol: StockSymbol, value: f32) {
-        match stock_symbol {
-            StockSymbol::VXUS => self.vxus = value,
-            StockSymbol::BNDX => self.bndx = value,
-            StockSymbol::VTIP => self.vtip = value,
-            StockSymbol::BND => self.bnd = value,
-            StockSymbol::VWO => self.vwo = value,
-            StockSymbol::VO => self.vo = value,
-            StockSymbol::VB => self.vb = value,
-            StockSymbol::VTC => self.vtc = value,
-            StockSymbol::VV => self.vv = value,
-            StockSymbol::VMFXX => self.vmfxx = value,
-            StockSymbol::Empty => panic!("Stock symbol not set before adding value"),
-            StockSymbol::Other(_) => self.other = value,
+    pub fn add_stock_value(&mut self, stock_symbol: StockSymbol, value: f32) -> Result<()> {
+        self.add_stock_value_f64(stock_symbol, value as f64)
+    }
+
+    /// add_stock_value_f64 adds stock value to the ShareValues struct the same as add_stock_value
+    /// without rounding the value to f32.
+    pub fn add_stock_value_f64(&mut self, stock_symbol: StockSymbol, value: f64) -> Result<()> {
+        *self.value_mut(stock_symbol)? = value;
+        Ok(())
+    }
+
+    // Retrieves the stored value of the stock symbol to be changed
+    fn value_mut(&mut self, stock_symbol: StockSymbol) -> Result<&mut f64> {
+        let value = match stock_symbol {
+            StockSymbol::VXUS => &mut self.vxus,
+            StockSymbol::BNDX => &mut self.bndx,
+            StockSymbol::VTIP => &mut self.vtip,
+            StockSymbol::BND => &mut self.bnd,
+            StockSymbol::VWO => &mut self.vwo,
+            StockSymbol::VO => &mut self.vo,
+            StockSymbol::VB => &mut self.vb,
+            StockSymbol::VTC => &mut self.vtc,
+            StockSymbol::VV => &mut self.vv,
+            StockSymbol::VMFXX | StockSymbol::MoneyMarket(_) => &mut self.vmfxx,
+            StockSymbol::Empty => bail!("Stock symbol not set before adding value"),
+            StockSymbol::Registered(registered) => {
+                self.registered_classes[registered.slot] = Some(registered.asset_class);
+                &mut self.registered[registered.slot]
+            }
+            StockSymbol::Other(_) => &mut self.other,
+        };
+        Ok(value)
+    }
+
+    /// add_other_values sets the other value from the value of each unsupported stock symbol, and
+    /// counts the symbols within the classifications towards the stock, bond, and inflation
+    /// protected percents of their asset class.  The classified symbols remain StockSymbol::Other
+    /// and are not rebalanced.  Any previously added other values are replaced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use vapore_gui::{asset::AssetClass, holdings};
+    ///
+    /// let other_values = HashMap::from([("SCHB".to_string(), 3000.0), ("SWAGX".to_string(), 5000.0)]);
+    /// let classifications = HashMap::from([("SCHB".to_string(), AssetClass::UsStockLarge)]);
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 1000.0).unwrap();
+    /// new_values.add_other_values(&other_values, &classifications);
+    ///
+    /// assert_eq!(new_values.stock_value(holdings::StockSymbol::new("SCHB")).unwrap(), 8000.0);
+    /// assert_eq!(new_values.percent_stock_bond_infl(), (75.0, 25.0, 0.0));
+    /// ```
+    pub fn add_other_values(
+        &mut self,
+        other_values: &HashMap<String, f64>,
+        classifications: &HashMap<String, AssetClass>,
+    ) {
+        self.other = other_values.values().sum();
+        self.other_classified = [0.0; 9];
+        for (symbol, value) in other_values {
+            if let Some(asset_class) = classifications.get(symbol) {
+                self.other_classified[*asset_class as usize] += value
+            }
         }
     }
 
     /// Adds other stock value that is not included within the vanguard account.  This is used for
     /// calculating current stock/bond ratios
     pub fn add_outside_stock_value(&mut self, stock_value: f32) {
-        self.outside_stock = stock_value
+        self.outside_stock = stock_value as f64
     }
 
     pub fn outside_stock_value(&self) -> f32 {
-        self.outside_stock
+        self.outside_stock as f32
     }
 
     /// Adds other bond value that is not included within the vanguard account.  This is used for
     /// calculating current stock/bond ratios
     pub fn add_outside_bond_value(&mut self, bond_value: f32) {
-        self.outside_bond = bond_value
+        self.outside_bond = bond_value as f64
     }
 
     pub fn outside_bond_value(&self) -> f32 {
-        self.outside_bond
+        self.outside_bond as f32
     }
 
-    pub fn subtract_stock_value(&mut self, stock_symbol: StockSymbol, value: f32) {
-        match stock_symbol {
-            StockSymbol::VXUS => self.vxus -= value,
-            StockSymbol::BNDX => self.bndx -= value,
-            StockSymbol::VTIP => self.vtip -= value,
-            StockSymbol::BND => self.bnd -= value,
-            StockSymbol::VWO => self.vwo -= value,
-            StockSymbol::VO => self.vo -= value,
-            StockSymbol::VB => self.vb -= value,
-            StockSymbol::VTC => self.vtc -= value,
-            StockSymbol::VV => self.vv -= value,
-            StockSymbol::VMFXX => self.vmfxx -= value,
-            StockSymbol::Empty => panic!("Stock symbol not set before adding value"),
-            StockSymbol::Other(_) => self.other -= value,
-        }
+    pub fn subtract_stock_value(&mut self, stock_symbol: StockSymbol, value: f32) -> Result<()> {
+        *self.value_mut(stock_symbol)? -= value as f64;
+        Ok(())
     }
 
     /// stock_value retrieves the stored stock value within the ShareValues struct
     ///
-    /// # Panic
+    /// # Errors
     ///
-    /// Panics when an empty stock symbol is passed.  This will happen if the StockInfo struct is
+    /// Errors when an empty stock symbol is passed.  This will happen if the StockInfo struct is
     /// initialized without any content added.
     ///
     /// # Example
     ///
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let mut new_values = holdings::ShareValues::new();
-    /// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0);
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
     ///
-    /// assert_eq!(new_values.stock_value(holdings::StockSymbol::BND), 5000.0);
+    /// assert_eq!(new_values.stock_value(holdings::StockSymbol::BND).unwrap(), 5000.0);
+    /// assert!(new_values.stock_value(holdings::StockSymbol::Empty).is_err());
     ///
     /// ```
-    pub fn stock_value(&self, stock_symbol: StockSymbol) -> f32 {
-        match stock_symbol {
+    pub fn stock_value(&self, stock_symbol: StockSymbol) -> Result<f32> {
+        Ok(self.stock_value_f64(stock_symbol)? as f32)
+    }
+
+    /// stock_value_f64 retrieves the stored stock value the same as stock_value without rounding
+    /// the value to f32.
+    pub fn stock_value_f64(&self, stock_symbol: StockSymbol) -> Result<f64> {
+        let value = match stock_symbol {
             StockSymbol::VXUS => self.vxus,
             StockSymbol::BNDX => self.bndx,
             StockSymbol::VTIP => self.vtip,
@@ -754,10 +2076,12 @@ impl ShareValues {
             StockSymbol::VB => self.vb,
             StockSymbol::VTC => self.vtc,
             StockSymbol::VV => self.vv,
-            StockSymbol::VMFXX => self.vmfxx,
-            StockSymbol::Empty => panic!("Value retrieval not supported for empty stock symbol"),
+            StockSymbol::VMFXX | StockSymbol::MoneyMarket(_) => self.vmfxx,
+            StockSymbol::Empty => bail!("Value retrieval not supported for empty stock symbol"),
+            StockSymbol::Registered(registered) => self.registered[registered.slot],
             StockSymbol::Other(_) => self.other,
-        }
+        };
+        Ok(value)
     }
 
     /// total_value returns the sum of all of the values within the StockValue struct
@@ -765,115 +2089,776 @@ impl ShareValues {
     /// # Example
     ///
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let mut new_values = holdings::ShareValues::new();
-    /// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0);
-    /// new_values.add_stock_value(holdings::StockSymbol::BNDX, 2000.0);
-    /// new_values.add_stock_value(holdings::StockSymbol::VB, 4000.0);
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::BNDX, 2000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VB, 4000.0).unwrap();
     ///
     /// assert_eq!(new_values.total_value(), 11000.0);
     ///
     /// ```
     pub fn total_value(&self) -> f32 {
-        self.vxus
-            + self.bndx
-            + self.bnd
-            + self.vwo
-            + self.vo
-            + self.vb
-            + self.vtc
-            + self.vv
-            + self.vmfxx
-            + self.vtip
-            + self.other
+        self.total_value_f64() as f32
+    }
+
+    /// total_value_f64 returns the sum of all of the values the same as total_value without
+    /// rounding the sum to f32.
+    pub fn total_value_f64(&self) -> f64 {
+        // leave out the outside stock and bond values, which are the last two
+        let values = self.values();
+        values[..values.len() - 2].iter().sum::<f64>() + self.registered.iter().sum::<f64>()
     }
 
     /// percent_stock_bond_infl calculates the percent of stock, bond, and inflation protected
     /// assets within the ShareValues.  This should only be used when the struct contains dollar
     /// value amounts for the stock values.
+    /// Registered stock symbols and other stock symbols classified with add_other_values are
+    /// counted by their asset class.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset::AssetClass, holdings};
+    ///
+    /// let mut registry = holdings::SymbolRegistry::new();
+    /// let vti = registry.register_symbol("VTI", AssetClass::UsStockLarge).unwrap();
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(vti, 1000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 9000.0).unwrap();
+    ///
+    /// assert_eq!(new_values.percent_stock_bond_infl(), (10.0, 90.0, 0.0));
+    /// ```
     pub fn percent_stock_bond_infl(&self) -> (f32, f32, f32) {
-        let total_bond = self.bndx + self.bnd + self.vtc + self.outside_bond;
-        let total_stock = self.vwo + self.vo + self.vb + self.vv + self.vxus + self.outside_stock;
-        let total =
-            self.total_value() - self.vmfxx - self.other + self.outside_bond + self.outside_stock;
+        let mut total_bond = self.bndx + self.bnd + self.vtc + self.outside_bond;
+        let mut total_stock =
+            self.vwo + self.vo + self.vb + self.vv + self.vxus + self.outside_stock;
+        let mut total_inflation = self.vtip;
+        for asset_class in AssetClass::list() {
+            let registered_value =
+                self.registered_value(asset_class) + self.other_classified[asset_class as usize];
+            if asset_class.is_stock() {
+                total_stock += registered_value
+            } else if asset_class.is_bond() {
+                total_bond += registered_value
+            } else {
+                total_inflation += registered_value
+            }
+        }
+        let total = self.total_value() as f64 - self.vmfxx - self.other
+            + self.other_classified.iter().sum::<f64>()
+            + self.outside_bond
+            + self.outside_stock;
         (
-            total_stock / total * 100.0,
-            total_bond / total * 100.0,
-            self.vtip / total * 100.0,
+            (total_stock / total * 100.0) as f32,
+            (total_bond / total * 100.0) as f32,
+            (total_inflation / total * 100.0) as f32,
         )
     }
-}
-
-impl Default for ShareValues {
-    fn default() -> Self {
-        Self::new()
-    }
-}
 
-impl Add for ShareValues {
-    type Output = ShareValues;
-
-    fn add(self, other: ShareValues) -> ShareValues {
-        ShareValues {
-            vxus: self.vxus + other.vxus,
-            bndx: self.bndx + other.bndx,
-            vtip: self.vtip + other.vtip,
-            bnd: self.bnd + other.bnd,
-            vwo: self.vwo + other.vwo,
-            vo: self.vo + other.vo,
-            vb: self.vb + other.vb,
-            vtc: self.vtc + other.vtc,
-            vv: self.vv + other.vv,
-            vmfxx: self.vmfxx + other.vmfxx,
-            other: self.other + other.other,
-            outside_bond: self.outside_bond + other.outside_bond,
-            outside_stock: self.outside_stock + other.outside_stock,
+    /// effective_allocation returns the percent of the invested value within each of the supported
+    /// stock symbols, keyed by the stock symbol, after distributing the outside stock and bond
+    /// values across them.  Outside holdings are assumed to be 2/3 US and 1/3 international, where
+    /// the US stock is split evenly between large, mid, and small, the international stock is split
+    /// 2/3 total and 1/3 emerging, and the US bond is split evenly between total and corporate,
+    /// the same as new_target.  Registered stock symbols are counted with the stock symbol of
+    /// their asset class.  Cash and other stock symbols are left out, which matches
+    /// percent_stock_bond_infl.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 3000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 3100.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VMFXX, 1000.0).unwrap();
+    /// new_values.add_outside_stock_value(2700.0);
+    /// new_values.add_outside_bond_value(1200.0);
+    ///
+    /// let allocation = new_values.effective_allocation();
+    /// assert!((allocation["VV"] - 36.0).abs() < 0.001);
+    /// assert!((allocation["VO"] - 6.0).abs() < 0.001);
+    /// assert!((allocation["VXUS"] - 6.0).abs() < 0.001);
+    /// assert!((allocation["VWO"] - 3.0).abs() < 0.001);
+    /// assert!((allocation["BND"] - 35.0).abs() < 0.001);
+    /// assert!((allocation["BNDX"] - 4.0).abs() < 0.001);
+    /// assert_eq!(allocation["VTIP"], 0.0);
+    ///
+    /// let (stock, _, _) = new_values.percent_stock_bond_infl();
+    /// let effective_stock = ["VV", "VO", "VB", "VXUS", "VWO"].iter().map(|symbol| allocation[*symbol]).sum::<f32>();
+    /// assert!((effective_stock - stock).abs() < 0.001);
+    /// ```
+    pub fn effective_allocation(&self) -> HashMap<String, f32> {
+        let total = self.total_value() as f64 - self.vmfxx - self.other
+            + self.outside_bond
+            + self.outside_stock;
+        let mut allocation = HashMap::new();
+        for stock_symbol in StockSymbol::list() {
+            let outside_value = match stock_symbol {
+                StockSymbol::VV | StockSymbol::VO | StockSymbol::VB | StockSymbol::VXUS => {
+                    self.outside_stock * 2.0 / 9.0
+                }
+                StockSymbol::VWO => self.outside_stock / 9.0,
+                StockSymbol::BND | StockSymbol::VTC | StockSymbol::BNDX => self.outside_bond / 3.0,
+                _ => 0.0,
+            };
+            let registered_value = AssetClass::list()
+                .into_iter()
+                .filter(|asset_class| stock_symbol.asset_class() == Some(*asset_class))
+                .map(|asset_class| self.registered_value(asset_class))
+                .sum::<f64>();
+            let value = self.stock_value(stock_symbol.clone()).unwrap_or(0.0) as f64
+                + outside_value
+                + registered_value;
+            let percent = if total == 0.0 {
+                0.0
+            } else {
+                value / total * 100.0
+            };
+            allocation.insert(format!("{:?}", stock_symbol), percent as f32);
         }
+        allocation
     }
-}
-
-impl Sub for ShareValues {
-    type Output = ShareValues;
 
-    fn sub(self, other: ShareValues) -> ShareValues {
-        ShareValues {
-            vxus: self.vxus - other.vxus,
-            bndx: self.bndx - other.bndx,
-            vtip: self.vtip - other.vtip,
-            bnd: self.bnd - other.bnd,
-            vwo: self.vwo - other.vwo,
-            vo: self.vo - other.vo,
-            vb: self.vb - other.vb,
-            vtc: self.vtc - other.vtc,
-            vv: self.vv - other.vv,
-            vmfxx: self.vmfxx - other.vmfxx,
-            other: self.other - other.other,
-            outside_bond: self.outside_bond - other.outside_bond,
-            outside_stock: self.outside_stock - other.outside_stock,
+    /// as_percentages returns the values as a percent of the total value instead of dollars, which
+    /// allows allocations to be compared directly.  The outside stock and bond values are also
+    /// given as a percent of the total value.  All values are 0 when the total value is 0.  This
+    /// should only be used when the struct contains dollar value amounts for the stock values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 3000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VXUS, 2000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 1000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VMFXX, 2000.0).unwrap();
+    ///
+    /// let percentages = new_values.as_percentages();
+    /// assert!((percentages.total_value() - 100.0).abs() < 0.0001);
+    /// assert_eq!(percentages.stock_value(holdings::StockSymbol::VV).unwrap(), 37.5);
+    /// assert_eq!(holdings::ShareValues::new().as_percentages(), holdings::ShareValues::new());
+    /// ```
+    pub fn as_percentages(&self) -> ShareValues {
+        let total = self.total_value() as f64;
+        if total == 0.0 {
+            return ShareValues::new();
+        }
+        self.scale(100.0 / total)
+    }
+
+    /// three_fund_equivalent collapses the values into the classic three-fund portfolio buckets of
+    /// total US stock, total international stock, and total bond.  Inflation protected securities
+    /// are counted as bonds.  Cash and other holdings are not included.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 3000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VB, 1000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VWO, 500.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VXUS, 1500.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 2000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VTIP, 500.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VMFXX, 100.0).unwrap();
+    ///
+    /// assert_eq!(new_values.three_fund_equivalent(), (4000.0, 2000.0, 2500.0));
+    /// ```
+    pub fn three_fund_equivalent(&self) -> (f32, f32, f32) {
+        let us_stock = self.vv + self.vo + self.vb;
+        let int_stock = self.vxus + self.vwo;
+        let bond = self.bnd + self.vtc + self.bndx + self.vtip;
+        (us_stock as f32, int_stock as f32, bond as f32)
+    }
+
+    /// drift_from returns the percentage point drift of each supported stock symbol from a
+    /// baseline allocation.  The baseline does not need to be the current target, which allows
+    /// monitoring against any investment policy.  Positive values are over the baseline.  This
+    /// should only be used when the struct contains dollar value amounts for the stock values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset, holdings};
+    ///
+    /// let baseline = asset::SubAllocations::new().unwrap();
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 5000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
+    ///
+    /// let drift = new_values.drift_from(&baseline);
+    /// assert!((drift[&holdings::StockSymbol::VV] - 30.0).abs() < 0.01);
+    /// assert!((drift[&holdings::StockSymbol::BND] - 36.667).abs() < 0.01);
+    /// assert!((drift[&holdings::StockSymbol::VO] + 10.0).abs() < 0.01);
+    /// ```
+    pub fn drift_from(&self, baseline: &SubAllocations) -> HashMap<StockSymbol, f32> {
+        let total = self.total_value();
+        let mut drift = HashMap::new();
+        for stock_symbol in StockSymbol::list() {
+            let current_percent = if total == 0.0 {
+                0.0
+            } else {
+                self.stock_value(stock_symbol.clone()).unwrap_or(0.0) / total * 100.0
+            };
+            let baseline_percent = baseline.symbol_percent(&stock_symbol);
+            drift.insert(stock_symbol, current_percent - baseline_percent);
+        }
+        drift
+    }
+
+    /// blended_expense_ratio calculates the value weighted expense ratio, in percent, of the
+    /// holdings using the built in expense ratios.  Other holdings do not have a known expense
+    /// ratio and are not included.  This should only be used when the struct contains dollar value
+    /// amounts for the stock values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VXUS, 5000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
+    ///
+    /// assert!((new_values.blended_expense_ratio() - 0.055).abs() < 0.0001);
+    /// ```
+    pub fn blended_expense_ratio(&self) -> f32 {
+        self.blended_expense_ratio_with(&HashMap::new())
+    }
+
+    /// blended_expense_ratio_with calculates the value weighted expense ratio, in percent, of the
+    /// holdings where the expense ratios of the fund metadata, such as from load_fund_metadata,
+    /// take the place of the built in ones.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VXUS, 5000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
+    ///
+    /// let fund_metadata = HashMap::from([(
+    ///     holdings::StockSymbol::VXUS,
+    ///     holdings::FundMeta { expense_ratio: 0.05, yield_percent: 3.2 },
+    /// )]);
+    /// assert!((new_values.blended_expense_ratio_with(&fund_metadata) - 0.04).abs() < 0.0001);
+    /// ```
+    pub fn blended_expense_ratio_with(
+        &self,
+        fund_metadata: &HashMap<StockSymbol, FundMeta>,
+    ) -> f32 {
+        let mut weighted_ratio = 0.0;
+        let mut total = 0.0;
+        let mut expense_ratios = EXPENSE_RATIO.clone();
+        for (stock_symbol, fund_meta) in fund_metadata.iter() {
+            expense_ratios.insert(stock_symbol.clone(), fund_meta.expense_ratio);
+        }
+        for (stock_symbol, expense_ratio) in expense_ratios.iter() {
+            let value = self.stock_value(stock_symbol.clone()).unwrap_or(0.0);
+            weighted_ratio += value * expense_ratio;
+            total += value;
+        }
+        if total == 0.0 {
+            0.0
+        } else {
+            weighted_ratio / total
+        }
+    }
+
+    /// yield_on_cost calculates the annual income, as a percent of the cost basis, for the
+    /// holdings.  The yields are the current percent yields for each stock symbol and only stock
+    /// symbols contained within the yields are included.  This should only be used when the struct
+    /// contains dollar value amounts for the stock values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 20000.0).unwrap();
+    /// let mut basis = holdings::ShareValues::new();
+    /// basis.add_stock_value(holdings::StockSymbol::VV, 10000.0).unwrap();
+    /// let mut yields = HashMap::new();
+    /// yields.insert(holdings::StockSymbol::VV, 1.5);
+    ///
+    /// assert!((new_values.yield_on_cost(&basis, &yields) - 3.0).abs() < 0.0001);
+    /// ```
+    pub fn yield_on_cost(&self, basis: &ShareValues, yields: &HashMap<StockSymbol, f32>) -> f32 {
+        let mut income = 0.0;
+        let mut total_basis = 0.0;
+        for (stock_symbol, stock_yield) in yields.iter() {
+            income += self.stock_value(stock_symbol.clone()).unwrap_or(0.0) * stock_yield / 100.0;
+            total_basis += basis.stock_value(stock_symbol.clone()).unwrap_or(0.0);
+        }
+        if total_basis == 0.0 {
+            0.0
+        } else {
+            income / total_basis * 100.0
+        }
+    }
+
+    /// round_targets rounds the value of each supported stock symbol to the nearest increment, for
+    /// those who prefer round numbers.  The residual from rounding is moved into cash so that the
+    /// total value stays the same.  When the round-ups cost more than the cash, the last round-ups
+    /// are rounded down instead until the cash is no longer negative.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 3847.32).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::BND, 1152.68).unwrap();
+    ///
+    /// let rounded = target.round_targets(100.0);
+    /// assert_eq!(rounded.stock_value(holdings::StockSymbol::VV).unwrap(), 3800.0);
+    /// assert_eq!(rounded.stock_value(holdings::StockSymbol::BND).unwrap(), 1200.0);
+    /// assert!(rounded.stock_value(holdings::StockSymbol::VMFXX).unwrap().abs() < 0.01);
+    /// assert!((rounded.total_value() - target.total_value()).abs() < 0.01);
+    ///
+    /// // Rounding all three up would cost $120 of the $20 in cash, so VB is rounded down instead
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 3860.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::VO, 1460.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::VB, 660.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::VMFXX, 20.0).unwrap();
+    ///
+    /// let rounded = target.round_targets(100.0);
+    /// assert_eq!(rounded.stock_value(holdings::StockSymbol::VV).unwrap(), 3900.0);
+    /// assert_eq!(rounded.stock_value(holdings::StockSymbol::VO).unwrap(), 1500.0);
+    /// assert_eq!(rounded.stock_value(holdings::StockSymbol::VB).unwrap(), 600.0);
+    /// assert!(rounded.stock_value(holdings::StockSymbol::VMFXX).unwrap() > -0.01);
+    /// assert!((rounded.total_value() - target.total_value()).abs() < 0.01);
+    /// ```
+    pub fn round_targets(&self, increment: f32) -> ShareValues {
+        let mut rounded = *self;
+        if increment <= 0.0 {
+            return rounded;
+        }
+        let increment = increment as f64;
+        let mut remainder = 0.0;
+        let mut rounded_up = Vec::new();
+        for stock_symbol in StockSymbol::list() {
+            if let Ok(value) = rounded.value_mut(stock_symbol.clone()) {
+                let rounded_value = (*value / increment).round() * increment;
+                if rounded_value > *value {
+                    rounded_up.push(stock_symbol);
+                }
+                remainder += *value - rounded_value;
+                *value = rounded_value;
+            }
+        }
+        // Round the last round-ups down instead while the cash is negative
+        while let Some(stock_symbol) = rounded_up.pop() {
+            if rounded.vmfxx + remainder > -0.01 {
+                break;
+            }
+            if let Ok(value) = rounded.value_mut(stock_symbol) {
+                *value -= increment;
+                remainder += increment;
+            }
+        }
+        rounded.vmfxx += remainder;
+        rounded
+    }
+
+    /// years_of_expenses returns the number of years of annual expenses that the total value
+    /// covers.  This should only be used when the struct contains dollar value amounts for the
+    /// stock values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 600000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 400000.0).unwrap();
+    ///
+    /// assert_eq!(new_values.years_of_expenses(40000.0), 25.0);
+    /// assert_eq!(new_values.years_of_expenses(0.0), f32::INFINITY);
+    /// ```
+    pub fn years_of_expenses(&self, annual_expenses: f32) -> f32 {
+        if annual_expenses <= 0.0 {
+            f32::INFINITY
+        } else {
+            self.total_value() / annual_expenses
+        }
+    }
+
+    /// preview_target calculates the target values for the allocation using the total value of
+    /// the holdings.  This is a lightweight calculation without the cash, outside holdings, and
+    /// purchase/sale adjustments of calc::to_buy, which allows the target to be previewed every
+    /// frame while an allocation is changed.  The current values are returned if the allocation
+    /// can not be split into sub allocations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset, holdings};
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 6000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VMFXX, 4000.0).unwrap();
+    ///
+    /// let allocation = asset::Allocations::custom(60.0, 40.0, 0.0).unwrap();
+    /// let preview = new_values.preview_target(allocation);
+    /// assert!((preview.stock_value(holdings::StockSymbol::VV).unwrap() - 2000.0).abs() < 0.01);
+    /// assert!((preview.stock_value(holdings::StockSymbol::BND).unwrap() - 4000.0 / 3.0).abs() < 0.01);
+    /// assert_eq!(preview.stock_value(holdings::StockSymbol::VMFXX).unwrap(), 0.0);
+    /// assert!((preview.total_value() - new_values.total_value()).abs() < 0.01);
+    /// ```
+    pub fn preview_target(&self, allocation: Allocations) -> ShareValues {
+        match SubAllocations::new_custom(allocation) {
+            Ok(sub_allocations) => {
+                ShareValues::new_target(sub_allocations, self.total_value(), 0.0, 0.0, 0.0, 0.0)
+            }
+            Err(_) => *self,
+        }
+    }
+
+    /// weighted_esg_score calculates the value weighted ESG score of the holdings using the
+    /// default ESG_SCORE table.  Returns 0 when none of the holdings have a score.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 5000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VWO, 5000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
+    ///
+    /// assert!((new_values.weighted_esg_score() - 5.95).abs() < 0.0001);
+    /// ```
+    pub fn weighted_esg_score(&self) -> f32 {
+        self.weighted_esg_score_with(&ESG_SCORE)
+    }
+
+    /// weighted_esg_score_with calculates the value weighted ESG score of the holdings using the
+    /// input ESG scores.  Only stock symbols contained within the scores are included.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 5000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
+    ///
+    /// let mut esg_scores = holdings::ESG_SCORE.clone();
+    /// esg_scores.insert(holdings::StockSymbol::BND, 4.0);
+    /// assert!((new_values.weighted_esg_score_with(&esg_scores) - 5.3).abs() < 0.0001);
+    /// ```
+    pub fn weighted_esg_score_with(&self, esg_scores: &HashMap<StockSymbol, f32>) -> f32 {
+        let mut weighted_score = 0.0;
+        let mut total = 0.0;
+        for (stock_symbol, esg_score) in esg_scores.iter() {
+            let value = self.stock_value(stock_symbol.clone()).unwrap_or(0.0);
+            weighted_score += value * esg_score;
+            total += value;
+        }
+        if total == 0.0 {
+            0.0
+        } else {
+            weighted_score / total
+        }
+    }
+
+    /// expected_sharpe calculates the expected Sharpe ratio of the holdings, which is the expected
+    /// return above the risk free rate of the market assumptions divided by the expected
+    /// volatility.  Returns and volatilities
+    /// are annual fractions, such as 0.07 for 7%, and only stock symbols contained within the
+    /// expected returns are included.  Correlations are looked up for each pair of stock symbols in
+    /// either order, where missing pairs are treated as uncorrelated.  Returns 0 when none of the
+    /// holdings have an expected return or there is no volatility.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use vapore_gui::calc::MarketAssumptions;
+    /// use vapore_gui::holdings::{ShareValues, StockSymbol};
+    ///
+    /// let mut new_values = ShareValues::new();
+    /// new_values.add_stock_value(StockSymbol::VV, 6000.0).unwrap();
+    /// new_values.add_stock_value(StockSymbol::BND, 4000.0).unwrap();
+    /// let assumptions = MarketAssumptions { risk_free: 0.02, ..Default::default() };
+    ///
+    /// let expected_returns = HashMap::from([(StockSymbol::VV, 0.07), (StockSymbol::BND, 0.03)]);
+    /// let volatilities = HashMap::from([(StockSymbol::VV, 0.16), (StockSymbol::BND, 0.05)]);
+    /// let correlations = HashMap::from([((StockSymbol::BND, StockSymbol::VV), 0.2)]);
+    ///
+    /// let sharpe = new_values.expected_sharpe(&assumptions, &expected_returns, &volatilities, &correlations);
+    /// assert!((sharpe - 0.3337).abs() < 0.001);
+    ///
+    /// // Uncorrelated assets diversify away more of the volatility
+    /// let uncorrelated = new_values.expected_sharpe(&assumptions, &expected_returns, &volatilities, &HashMap::new());
+    /// assert!(uncorrelated > sharpe);
+    /// ```
+    pub fn expected_sharpe(
+        &self,
+        assumptions: &MarketAssumptions,
+        expected_returns: &HashMap<StockSymbol, f32>,
+        volatilities: &HashMap<StockSymbol, f32>,
+        correlations: &HashMap<(StockSymbol, StockSymbol), f32>,
+    ) -> f32 {
+        let values = expected_returns
+            .keys()
+            .map(|stock_symbol| {
+                let value = self.stock_value(stock_symbol.clone()).unwrap_or(0.0);
+                (stock_symbol, value)
+            })
+            .collect::<Vec<(&StockSymbol, f32)>>();
+        let total = values.iter().map(|(_, value)| value).sum::<f32>();
+        if total == 0.0 {
+            return 0.0;
+        }
+        let mut expected_return = 0.0;
+        let mut variance = 0.0;
+        for (symbol_a, value_a) in &values {
+            let weight_a = value_a / total;
+            let volatility_a = volatilities.get(symbol_a).copied().unwrap_or(0.0);
+            expected_return += weight_a * expected_returns[symbol_a];
+            for (symbol_b, value_b) in &values {
+                let weight_b = value_b / total;
+                let volatility_b = volatilities.get(symbol_b).copied().unwrap_or(0.0);
+                let correlation = if symbol_a == symbol_b {
+                    1.0
+                } else {
+                    correlations
+                        .get(&((*symbol_a).clone(), (*symbol_b).clone()))
+                        .or_else(|| correlations.get(&((*symbol_b).clone(), (*symbol_a).clone())))
+                        .copied()
+                        .unwrap_or(0.0)
+                };
+                variance += weight_a * weight_b * volatility_a * volatility_b * correlation;
+            }
+        }
+        if variance <= 0.0 {
+            0.0
+        } else {
+            (expected_return - assumptions.risk_free) / variance.sqrt()
+        }
+    }
+
+    /// cash_add_pct returns the cash amount added, or removed when negative, as a percent of the
+    /// current total value.  Returns 0 when there is no current value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 20000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VMFXX, 5000.0).unwrap();
+    ///
+    /// assert_eq!(new_values.cash_add_pct(3000.0), 12.0);
+    /// assert_eq!(new_values.cash_add_pct(-5000.0), -20.0);
+    /// assert_eq!(holdings::ShareValues::new().cash_add_pct(3000.0), 0.0);
+    /// ```
+    pub fn cash_add_pct(&self, amount: f32) -> f32 {
+        let total = self.total_value();
+        if total == 0.0 {
+            0.0
+        } else {
+            amount / total * 100.0
+        }
+    }
+
+    /// post_liquidate_other returns the values after all of the other holdings, which are not
+    /// supported, are sold and moved into cash.  This previews the portfolio after selling
+    /// unsupported funds, since the targets always set other holdings to 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 5000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VMFXX, 500.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::Other("VTSAX".to_string()), 2000.0).unwrap();
+    ///
+    /// let liquidated = new_values.post_liquidate_other();
+    /// assert_eq!(liquidated.stock_value(holdings::StockSymbol::Other(String::new())).unwrap(), 0.0);
+    /// assert_eq!(liquidated.stock_value(holdings::StockSymbol::VMFXX).unwrap(), 2500.0);
+    /// assert_eq!(liquidated.total_value(), new_values.total_value());
+    /// ```
+    pub fn post_liquidate_other(&self) -> ShareValues {
+        let mut liquidated = *self;
+        liquidated.vmfxx += liquidated.other;
+        liquidated.other = 0.0;
+        liquidated.other_classified = [0.0; 9];
+        liquidated
+    }
+
+    /// without_symbol returns the values after the stock symbol is sold entirely and moved into
+    /// the cash symbol, which previews the allocation without the position.  Cash and empty stock
+    /// symbols return the values unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the cash symbol is not VMFXX or another money market fund
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let cash_fund = holdings::StockSymbol::new("VMRXX");
+    /// let mut new_values = holdings::ShareValues::new();
+    /// new_values.add_stock_value(holdings::StockSymbol::VV, 5000.0).unwrap();
+    /// new_values.add_stock_value(holdings::StockSymbol::VWO, 3000.0).unwrap();
+    /// new_values.add_stock_value(cash_fund.clone(), 500.0).unwrap();
+    ///
+    /// let vwo = holdings::StockSymbol::VWO;
+    /// let sold = new_values.without_symbol(vwo.clone(), cash_fund.clone()).unwrap();
+    /// assert_eq!(sold.stock_value(vwo.clone()).unwrap(), 0.0);
+    /// assert_eq!(sold.stock_value(cash_fund.clone()).unwrap(), 3500.0);
+    /// assert_eq!(sold.stock_value(holdings::StockSymbol::VV).unwrap(), 5000.0);
+    /// assert_eq!(sold.total_value(), new_values.total_value());
+    /// let unchanged = new_values.without_symbol(cash_fund.clone(), cash_fund).unwrap();
+    /// assert_eq!(unchanged, new_values);
+    /// assert!(new_values.without_symbol(vwo, holdings::StockSymbol::BND).is_err());
+    /// ```
+    pub fn without_symbol(
+        &self,
+        symbol: StockSymbol,
+        cash_symbol: StockSymbol,
+    ) -> Result<ShareValues> {
+        ensure!(
+            cash_symbol.is_cash(),
+            "{:?} is not a cash symbol",
+            cash_symbol
+        );
+        let mut sold = *self;
+        if symbol.is_cash() {
+            return Ok(sold);
+        }
+        if let StockSymbol::Other(_) = symbol {
+            sold.other_classified = [0.0; 9];
+        }
+        if let Ok(value) = sold.value_mut(symbol) {
+            let symbol_value = *value;
+            *value = 0.0;
+            *sold.value_mut(cash_symbol)? += symbol_value;
+        }
+        Ok(sold)
+    }
+
+    /// effective_holdings returns the inverse Herfindahl index of the holding weights, which is the
+    /// effective number of equally weighted positions held.  A higher number means a more
+    /// diversified portfolio.  Returns 0 when there are no holdings.  This should only be used
+    /// when the struct contains dollar value amounts for the stock values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut concentrated = holdings::ShareValues::new();
+    /// concentrated.add_stock_value(holdings::StockSymbol::VV, 9000.0).unwrap();
+    /// concentrated.add_stock_value(holdings::StockSymbol::BND, 1000.0).unwrap();
+    ///
+    /// let mut balanced = holdings::ShareValues::new();
+    /// for stock_symbol in [
+    ///     holdings::StockSymbol::VV,
+    ///     holdings::StockSymbol::VXUS,
+    ///     holdings::StockSymbol::BND,
+    ///     holdings::StockSymbol::BNDX,
+    /// ] {
+    ///     balanced.add_stock_value(stock_symbol, 2500.0).unwrap();
+    /// }
+    ///
+    /// assert!((concentrated.effective_holdings() - 1.2195).abs() < 0.001);
+    /// assert!((balanced.effective_holdings() - 4.0).abs() < 0.001);
+    /// ```
+    pub fn effective_holdings(&self) -> f32 {
+        let values = [
+            self.vxus, self.bndx, self.bnd, self.vwo, self.vo, self.vb, self.vtc, self.vv,
+            self.vmfxx, self.vtip, self.other,
+        ];
+        let values = values.iter().chain(self.registered.iter());
+        let total: f64 = values.clone().sum();
+        if total == 0.0 {
+            return 0.0;
         }
+        let herfindahl: f64 = values.map(|value| (value / total).powi(2)).sum();
+        (1.0 / herfindahl) as f32
+    }
+}
+
+impl Default for ShareValues {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Add for ShareValues {
+    type Output = ShareValues;
+
+    fn add(self, other: ShareValues) -> ShareValues {
+        self.combine(other, |value, other_value| value + other_value)
+    }
+}
+
+impl Sub for ShareValues {
+    type Output = ShareValues;
+
+    fn sub(self, other: ShareValues) -> ShareValues {
+        self.combine(other, |value, other_value| value - other_value)
     }
 }
 
 impl Div for ShareValues {
     type Output = ShareValues;
 
+    /// div divides each value by the matching value of the other ShareValues.  A zero divisor,
+    /// such as a missing quote or an unset outside value, results in 0 for that value instead of
+    /// NaN or Inf.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut values = holdings::ShareValues::new_quote();
+    /// values.add_outside_stock_value(1000.0);
+    /// values.add_outside_bond_value(500.0);
+    ///
+    /// let quotient = values / holdings::ShareValues::new();
+    ///
+    /// assert!(quotient.iter().all(|(_, value)| value.is_finite()));
+    /// assert_eq!(quotient, holdings::ShareValues::new());
+    /// ```
     fn div(self, other: ShareValues) -> ShareValues {
-        ShareValues {
-            vxus: self.vxus / other.vxus,
-            bndx: self.bndx / other.bndx,
-            vtip: self.vtip / other.vtip,
-            bnd: self.bnd / other.bnd,
-            vwo: self.vwo / other.vwo,
-            vo: self.vo / other.vo,
-            vb: self.vb / other.vb,
-            vtc: self.vtc / other.vtc,
-            vv: self.vv / other.vv,
-            vmfxx: self.vmfxx / other.vmfxx,
-            other: self.other / other.other,
-            outside_bond: self.outside_bond / other.outside_bond,
-            outside_stock: self.outside_stock / other.outside_stock,
-        }
+        self.combine(other, |value, other_value| {
+            if other_value == 0.0 {
+                0.0
+            } else {
+                value / other_value
+            }
+        })
     }
 }
 
@@ -881,21 +2866,7 @@ impl Mul for ShareValues {
     type Output = ShareValues;
 
     fn mul(self, other: ShareValues) -> ShareValues {
-        ShareValues {
-            vxus: self.vxus * other.vxus,
-            bndx: self.bndx * other.bndx,
-            vtip: self.vtip * other.vtip,
-            bnd: self.bnd * other.bnd,
-            vwo: self.vwo * other.vwo,
-            vo: self.vo * other.vo,
-            vb: self.vb * other.vb,
-            vtc: self.vtc * other.vtc,
-            vv: self.vv * other.vv,
-            vmfxx: self.vmfxx * other.vmfxx,
-            other: self.other * other.other,
-            outside_bond: self.outside_bond * other.outside_bond,
-            outside_stock: self.outside_stock * other.outside_stock,
-        }
+        self.combine(other, |value, other_value| value * other_value)
     }
 }
 
@@ -944,15 +2915,44 @@ impl fmt::Display for ShareValues {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, serde::Deserialize, serde::Serialize)]
 pub enum HoldingType {
     Brokerage,
     TraditionalIra,
     RothIra,
+    Hsa,
 }
 
 /// VanguardHoldings contains ShareValues structs for all accounts along with for the quotes.  This
-/// struct is creating during the parsing of the downloaded Vanguard file
-#[derive(Clone, Debug)]
+/// struct is creating during the parsing of the downloaded Vanguard file.  It can be saved with
+/// serde so that the file does not need to be parsed again when the app restarts.
+///
+/// # Example
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use vapore_gui::holdings::{ShareValues, StockSymbol, Transaction, TransactionType, VanguardHoldings};
+///
+/// let mut account = ShareValues::new();
+/// account.add_stock_value(StockSymbol::VV, 1000.0).unwrap();
+/// let mut new_vanguard = VanguardHoldings::new(ShareValues::new_quote());
+/// new_vanguard.accounts_values.insert(1, account);
+/// let date = NaiveDate::from_ymd_opt(2023, 3, 1).unwrap();
+/// new_vanguard.add_transaction(Transaction::new(1, date, StockSymbol::VV, 2.0, -500.0, TransactionType::Buy));
+///
+/// let saved = serde_json::to_string(&new_vanguard).unwrap();
+/// let loaded: VanguardHoldings = serde_json::from_str(&saved).unwrap();
+/// assert_eq!(loaded.accounts_values, new_vanguard.accounts_values);
+/// assert_eq!(loaded.transactions(), new_vanguard.transactions());
+///
+/// // Values missing from older save files default to 0
+/// let old_save = r#"{"vv": 1000.0, "bnd": 500.0}"#;
+/// let loaded: ShareValues = serde_json::from_str(old_save).unwrap();
+/// assert_eq!(loaded.stock_value(StockSymbol::VV).unwrap(), 1000.0);
+/// assert_eq!(loaded.stock_value(StockSymbol::VO).unwrap(), 0.0);
+/// ```
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)] // fields added after the save file was written get their default values
 pub struct VanguardHoldings {
     pub accounts_values: HashMap<u32, ShareValues>,
     pub accounts_shares: HashMap<u32, ShareValues>,
@@ -960,6 +2960,11 @@ pub struct VanguardHoldings {
     transactions: Vec<Transaction>, // holds all transactions, which needs to be filtered by trad
     // acct num later
     distributions: HashMap<u32, f32>,
+    quote_timestamps: HashMap<StockSymbol, DateTime<Utc>>, // time when each quote was retrieved
+    parsed_at: DateTime<Utc>, // time when the holdings snapshot was created
+    other_values: HashMap<u32, HashMap<String, f64>>, // value of each other symbol by account
+    other_classifications: HashMap<String, AssetClass>,
+    registry: SymbolRegistry, // registered symbols whose quotes are retrieved
 }
 
 impl VanguardHoldings {
@@ -969,7 +2974,7 @@ impl VanguardHoldings {
     /// # Example
     ///
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let new_quotes = holdings::ShareValues::new_quote();
     ///
@@ -982,39 +2987,825 @@ impl VanguardHoldings {
             quotes,
             transactions: Vec::new(),
             distributions: HashMap::new(),
+            quote_timestamps: HashMap::new(),
+            parsed_at: Utc::now(),
+            other_values: HashMap::new(),
+            other_classifications: HashMap::new(),
+            registry: SymbolRegistry::new(),
         }
     }
 
     pub fn stock_quotes(&self) -> ShareValues {
         self.quotes
     }
+
     pub fn transactions(&self) -> Vec<Transaction> {
         self.transactions.clone()
     }
-    pub fn get_distributions(&self, account_number: &u32) -> f32 {
-        *self.distributions
-            .get(account_number)
-            .unwrap_or(&0.0)
+
+    /// add_transaction adds a transaction to the transaction history.
+    pub fn add_transaction(&mut self, transaction: Transaction) {
+        self.transactions.push(transaction);
     }
-    // Calculated the previous end of year holdings value based on the holdings times the quotes
-    // from December 31st of the previous year.
-    #[cfg(not(target_arch = "wasm32"))]
-    pub async fn eoy_value(&mut self, year: u32, traditional_acct_num: u32) -> Result<Option<f32>> {
-        let trad_holdings = *self
-            .accounts_shares
-            .get(&traditional_acct_num)
-            .unwrap_or(&ShareValues::new());
+
+    /// transaction_summary returns the count and the total net amount of the transactions for each
+    /// transaction type within the account.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use vapore_gui::holdings::{ShareValues, StockSymbol, Transaction, TransactionType, VanguardHoldings};
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+    /// let mut new_vanguard = VanguardHoldings::new(ShareValues::new_quote());
+    /// new_vanguard.add_transaction(Transaction::new(1, date, StockSymbol::BND, 10.0, -725.0, TransactionType::Buy));
+    /// new_vanguard.add_transaction(Transaction::new(1, date, StockSymbol::VV, 2.0, -450.0, TransactionType::Buy));
+    /// new_vanguard.add_transaction(Transaction::new(1, date, StockSymbol::VV, 0.0, 12.5, TransactionType::Dividend));
+    /// new_vanguard.add_transaction(Transaction::new(2, date, StockSymbol::VV, 1.0, -225.0, TransactionType::Buy));
+    ///
+    /// let summary = new_vanguard.transaction_summary(1);
+    /// assert_eq!(summary[&TransactionType::Buy], (2, -1175.0));
+    /// assert_eq!(summary[&TransactionType::Dividend], (1, 12.5));
+    /// assert!(!summary.contains_key(&TransactionType::Sell));
+    /// ```
+    pub fn transaction_summary(&self, account: u32) -> HashMap<TransactionType, (usize, f32)> {
+        let mut summary = HashMap::new();
+        for transaction in &self.transactions {
+            if transaction.account_number == account {
+                let (count, net_amount) = summary
+                    .entry(transaction.transaction_type.clone())
+                    .or_insert((0, 0.0));
+                *count += 1;
+                *net_amount += transaction.net_amount;
+            }
+        }
+        summary
+    }
+
+    /// holdings_with_reinvestment returns the dollar values of the account as if every dividend
+    /// had been reinvested into the fund that paid it.  Dividends paid out as cash are added back
+    /// as purchases of the fund, which are taken from the cash, VMFXX.  The purchases are capped at
+    /// the cash available, in the order the dividends were paid, so that the total value of the
+    /// account is unchanged.  Dividends which already have matching reinvestment transactions are
+    /// left as is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use vapore_gui::holdings::{ShareValues, StockSymbol, Transaction, TransactionType, VanguardHoldings};
+    ///
+    /// let mut values = ShareValues::new();
+    /// values.add_stock_value(StockSymbol::VV, 10000.0).unwrap();
+    /// values.add_stock_value(StockSymbol::BND, 5000.0).unwrap();
+    /// values.add_stock_value(StockSymbol::VMFXX, 300.0).unwrap();
+    /// let mut new_vanguard = VanguardHoldings::new(ShareValues::new_quote());
+    /// new_vanguard.accounts_values.insert(1, values);
+    ///
+    /// // The VV dividend was paid as cash while the BND dividend was reinvested
+    /// let date = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+    /// new_vanguard.add_transaction(Transaction::new(1, date, StockSymbol::VV, 0.0, 200.0, TransactionType::Dividend));
+    /// new_vanguard.add_transaction(Transaction::new(1, date, StockSymbol::BND, 0.0, 100.0, TransactionType::Dividend));
+    /// new_vanguard.add_transaction(Transaction::new(1, date, StockSymbol::BND, 1.4, -100.0, TransactionType::Reinvestment));
+    ///
+    /// let reinvested = new_vanguard.holdings_with_reinvestment(1);
+    /// assert_eq!(reinvested.stock_value(StockSymbol::VV).unwrap(), 10200.0);
+    /// assert_eq!(reinvested.stock_value(StockSymbol::BND).unwrap(), 5000.0);
+    /// assert_eq!(reinvested.stock_value(StockSymbol::VMFXX).unwrap(), 100.0);
+    /// assert_eq!(reinvested.total_value(), values.total_value());
+    /// assert_eq!(new_vanguard.holdings_with_reinvestment(2), ShareValues::new());
+    ///
+    /// // Only the cash available is reinvested when the dividends are larger
+    /// new_vanguard.add_transaction(Transaction::new(1, date, StockSymbol::BND, 0.0, 250.0, TransactionType::Dividend));
+    /// let reinvested = new_vanguard.holdings_with_reinvestment(1);
+    /// assert_eq!(reinvested.stock_value(StockSymbol::VV).unwrap(), 10200.0);
+    /// assert_eq!(reinvested.stock_value(StockSymbol::BND).unwrap(), 5100.0);
+    /// assert_eq!(reinvested.stock_value(StockSymbol::VMFXX).unwrap(), 0.0);
+    /// assert_eq!(reinvested.total_value(), values.total_value());
+    /// ```
+    pub fn holdings_with_reinvestment(&self, account: u32) -> ShareValues {
+        let mut holdings = *self
+            .accounts_values
+            .get(&account)
+            .unwrap_or(&ShareValues::new());
+        // Dividends are positive and reinvestments negative, which leaves the cash dividends.
+        // These are kept in the order they were first paid so the available cash is used in that
+        // order
+        let mut cash_dividends: Vec<(StockSymbol, f32)> = Vec::new();
+        for transaction in &self.transactions {
+            if transaction.account_number == account
+                && matches!(
+                    transaction.transaction_type,
+                    TransactionType::Dividend | TransactionType::Reinvestment
+                )
+            {
+                match cash_dividends
+                    .iter_mut()
+                    .find(|(stock_symbol, _)| *stock_symbol == transaction.symbol)
+                {
+                    Some((_, cash_dividend)) => *cash_dividend += transaction.net_amount,
+                    None => {
+                        cash_dividends.push((transaction.symbol.clone(), transaction.net_amount))
+                    }
+                }
+            }
+        }
+        for (stock_symbol, cash_dividend) in cash_dividends {
+            if cash_dividend <= 0.0 || stock_symbol.is_cash() {
+                continue;
+            }
+            let reinvested = (cash_dividend as f64).min(holdings.vmfxx.max(0.0));
+            if let Ok(value) = holdings.value_mut(stock_symbol) {
+                *value += reinvested;
+                holdings.vmfxx -= reinvested;
+            }
+        }
+        holdings
+    }
+
+    /// transaction_impact returns the change in the stock, bond, and inflation protected
+    /// percentages of the transaction's account caused by the transaction.  The holdings before
+    /// the transaction are found by removing the transaction from the current holdings, and both
+    /// are valued with the quotes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use vapore_gui::holdings::{ShareValues, StockSymbol, Transaction, TransactionType, VanguardHoldings};
+    ///
+    /// let mut shares = ShareValues::new();
+    /// shares.add_stock_value(StockSymbol::VV, 60.0).unwrap();
+    /// shares.add_stock_value(StockSymbol::BND, 40.0).unwrap();
+    /// let mut new_vanguard = VanguardHoldings::new(ShareValues::new_quote());
+    /// new_vanguard.accounts_shares.insert(1, shares);
+    ///
+    /// // Buying 20 VV moved the account from 50% to 60% stock
+    /// let date = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+    /// let buy = Transaction::new(1, date, StockSymbol::VV, 20.0, -20.0, TransactionType::Buy);
+    /// let impact = new_vanguard.transaction_impact(&buy, ShareValues::new_quote());
+    /// assert_eq!(impact, (10.0, -10.0, 0.0));
+    /// ```
+    pub fn transaction_impact(
+        &self,
+        transaction: &Transaction,
+        quotes: ShareValues,
+    ) -> (f32, f32, f32) {
+        let after = *self
+            .accounts_shares
+            .get(&transaction.account_number)
+            .unwrap_or(&ShareValues::new());
+        let mut before = after;
+        // Cash transactions have 0 shares, so the net amount is removed instead
+        let removed = if transaction.symbol.is_cash() {
+            transaction.net_amount
+        } else {
+            transaction.shares
+        };
+        // Transactions without a stock symbol do not change the holdings
+        if before
+            .subtract_stock_value(transaction.symbol.clone(), removed)
+            .is_err()
+        {
+            return (0.0, 0.0, 0.0);
+        }
+        // Empty holdings have undefined percentages, which are treated as 0
+        let percentages = |shares: ShareValues| {
+            let (stock, bond, inflation) = (shares * quotes).percent_stock_bond_infl();
+            let defined = |percent: f32| if percent.is_nan() { 0.0 } else { percent };
+            (defined(stock), defined(bond), defined(inflation))
+        };
+        let (after_stock, after_bond, after_inflation) = percentages(after);
+        let (before_stock, before_bond, before_inflation) = percentages(before);
+        (
+            after_stock - before_stock,
+            after_bond - before_bond,
+            after_inflation - before_inflation,
+        )
+    }
+
+    /// combined_values returns the values of the accounts added together, which allows several
+    /// accounts of the same type, such as a rollover and a SEP traditional IRA, to be rebalanced as
+    /// one.  Account numbers without holdings are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings::{ShareValues, StockSymbol, VanguardHoldings};
+    ///
+    /// let mut rollover = ShareValues::new();
+    /// rollover.add_stock_value(StockSymbol::VV, 3000.0).unwrap();
+    /// let mut sep = ShareValues::new();
+    /// sep.add_stock_value(StockSymbol::VV, 1000.0).unwrap();
+    /// sep.add_stock_value(StockSymbol::BND, 2000.0).unwrap();
+    ///
+    /// let mut new_vanguard = VanguardHoldings::new(ShareValues::new_quote());
+    /// new_vanguard.accounts_values.insert(1, rollover);
+    /// new_vanguard.accounts_values.insert(2, sep);
+    ///
+    /// let combined = new_vanguard.combined_values(&[1, 2, 3]);
+    /// assert_eq!(combined.stock_value(StockSymbol::VV).unwrap(), 4000.0);
+    /// assert_eq!(combined.stock_value(StockSymbol::BND).unwrap(), 2000.0);
+    /// assert_eq!(new_vanguard.combined_values(&[]), ShareValues::new());
+    /// ```
+    pub fn combined_values(&self, account_numbers: &[u32]) -> ShareValues {
+        account_numbers
+            .iter()
+            .filter_map(|account_number| self.accounts_values.get(account_number))
+            .fold(ShareValues::new(), |combined, values| combined + *values)
+    }
+
+    /// lots returns the tax lots of the accounts built from the buy and reinvestment transactions,
+    /// with the cost basis per share from the net amount paid.  Sells are taken from the oldest
+    /// lots of the stock symbol first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use vapore_gui::holdings::{ShareValues, StockSymbol, Transaction, TransactionType, VanguardHoldings};
+    ///
+    /// let first = NaiveDate::from_ymd_opt(2022, 3, 1).unwrap();
+    /// let second = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+    /// let mut new_vanguard = VanguardHoldings::new(ShareValues::new_quote());
+    /// new_vanguard.add_transaction(Transaction::new(1, first, StockSymbol::VV, 10.0, -1800.0, TransactionType::Buy));
+    /// new_vanguard.add_transaction(Transaction::new(1, second, StockSymbol::VV, 5.0, -1000.0, TransactionType::Reinvestment));
+    /// new_vanguard.add_transaction(Transaction::new(1, second, StockSymbol::VV, -4.0, 800.0, TransactionType::Sell));
+    /// new_vanguard.add_transaction(Transaction::new(2, first, StockSymbol::BND, 10.0, -725.0, TransactionType::Buy));
+    ///
+    /// let lots = new_vanguard.lots(&[1]);
+    /// assert_eq!(lots.len(), 2);
+    /// assert_eq!((lots[0].shares, lots[0].cost_basis, lots[0].acquired), (6.0, 180.0, first));
+    /// assert_eq!((lots[1].shares, lots[1].cost_basis), (5.0, 200.0));
+    ///
+    /// let cost_basis = new_vanguard.cost_basis(&[1, 2]).unwrap();
+    /// assert_eq!(cost_basis.stock_value(StockSymbol::VV).unwrap(), 2080.0);
+    /// assert_eq!(cost_basis.stock_value(StockSymbol::BND).unwrap(), 725.0);
+    /// ```
+    pub fn lots(&self, account_numbers: &[u32]) -> Vec<Lot> {
+        let mut transactions = self
+            .transactions
+            .iter()
+            .filter(|transaction| account_numbers.contains(&transaction.account_number))
+            .collect::<Vec<&Transaction>>();
+        transactions.sort_by_key(|transaction| transaction.trade_date);
+        let mut lots: Vec<Lot> = Vec::new();
+        for transaction in transactions {
+            match transaction.transaction_type {
+                TransactionType::Buy | TransactionType::Reinvestment
+                    if transaction.shares > 0.0 =>
+                {
+                    lots.push(Lot::new(
+                        transaction.symbol.clone(),
+                        transaction.shares,
+                        transaction.net_amount.abs() / transaction.shares,
+                        transaction.trade_date,
+                    ))
+                }
+                TransactionType::Sell => {
+                    let mut shares_sold = transaction.shares.abs();
+                    for lot in lots
+                        .iter_mut()
+                        .filter(|lot| lot.symbol == transaction.symbol)
+                    {
+                        let shares = lot.shares.min(shares_sold);
+                        lot.shares -= shares;
+                        shares_sold -= shares;
+                    }
+                }
+                _ => (),
+            }
+        }
+        lots.retain(|lot| lot.shares > 0.0);
+        lots
+    }
+
+    /// cost_basis returns the total cost basis of each stock symbol within the lots of the
+    /// accounts, which is what was paid for the shares still held.
+    pub fn cost_basis(&self, account_numbers: &[u32]) -> Result<ShareValues> {
+        let mut cost_basis = ShareValues::new();
+        for lot in self.lots(account_numbers) {
+            let value = cost_basis.stock_value(lot.symbol.clone())?;
+            cost_basis.add_stock_value(lot.symbol, value + lot.shares * lot.cost_basis)?;
+        }
+        Ok(cost_basis)
+    }
+
+    /// guess_account_type guesses the account type from the transaction history of the account.
+    /// Accounts with distributions or conversions out are traditional IRAs and accounts with
+    /// conversions in are roth IRAs.  None is returned when the type can not be guessed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use vapore_gui::holdings::{HoldingType, ShareValues, StockSymbol, Transaction, TransactionType, VanguardHoldings};
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+    /// let mut new_vanguard = VanguardHoldings::new(ShareValues::new_quote());
+    /// new_vanguard.add_transaction(Transaction::new(1, date, StockSymbol::Empty, 0.0, -5000.0, TransactionType::Distribution));
+    /// new_vanguard.add_transaction(Transaction::new(2, date, StockSymbol::VV, 2.0, -450.0, TransactionType::Buy));
+    ///
+    /// assert_eq!(new_vanguard.guess_account_type(1), Some(HoldingType::TraditionalIra));
+    /// assert_eq!(new_vanguard.guess_account_type(2), None);
+    /// ```
+    pub fn guess_account_type(&self, account: u32) -> Option<HoldingType> {
+        let mut guess = None;
+        for transaction in &self.transactions {
+            if transaction.account_number == account {
+                match transaction.transaction_type {
+                    TransactionType::Distribution | TransactionType::ConversionOut => {
+                        return Some(HoldingType::TraditionalIra)
+                    }
+                    TransactionType::ConversionIn => guess = Some(HoldingType::RothIra),
+                    _ => (),
+                }
+            }
+        }
+        guess
+    }
+
+    /// parsed_at returns the time when the holdings snapshot was created
+    pub fn parsed_at(&self) -> DateTime<Utc> {
+        self.parsed_at
+    }
+
+    /// set_parsed_at changes the time when the holdings snapshot was created, such as to the date
+    /// the file was downloaded from Vanguard.
+    pub fn set_parsed_at(&mut self, parsed_at: DateTime<Utc>) {
+        self.parsed_at = parsed_at;
+    }
+
+    /// classify_other_symbols replaces the asset classes of the unsupported stock symbols within
+    /// the accounts.  Unlike registered symbols, the classified symbols remain StockSymbol::Other
+    /// and are not rebalanced, but their value counts towards the stock, bond, and inflation
+    /// protected percents.  The classifications are kept and applied again whenever the accounts
+    /// are revalued with new quotes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::Duration;
+    /// use futures::executor::block_on;
+    /// use std::collections::HashMap;
+    /// use vapore_gui::{asset::AssetClass, holdings};
+    ///
+    /// let mut csv = "Account Number,Investment Name,Symbol,Shares,Share Price,Total Value,\n".to_string();
+    /// csv += "12345,Schwab ETF,SCHB,100,30.0,3000.0,\n";
+    /// csv += "12345,American Funds,SWAGX,100,50.0,5000.0,\n";
+    /// csv += "12345,Vanguard ETF,BND,10,100.0,1000.0,\n";
+    /// let (mut vanguard_holdings, _) = holdings::parse_csv_download(csv).unwrap();
+    /// vanguard_holdings.classify_other_symbols(HashMap::from([
+    ///     ("SCHB".to_string(), AssetClass::UsStockLarge),
+    /// ]));
+    /// assert_eq!(vanguard_holdings.accounts_values[&12345].percent_stock_bond_infl(), (75.0, 25.0, 0.0));
+    ///
+    /// // The classifications and other values remain after revaluing with new quotes
+    /// let provider = holdings::StaticProvider::new(HashMap::from(
+    ///     holdings::StockSymbol::list().map(|stock_symbol| (stock_symbol, 100.0)),
+    /// ));
+    /// block_on(vanguard_holdings.add_missing_quotes(&provider, Duration::minutes(15))).unwrap();
+    /// let account = vanguard_holdings.accounts_values[&12345];
+    /// assert_eq!(account.stock_value(holdings::StockSymbol::new("SCHB")).unwrap(), 8000.0);
+    /// assert_eq!(account.percent_stock_bond_infl(), (75.0, 25.0, 0.0));
+    /// ```
+    pub fn classify_other_symbols(&mut self, classifications: HashMap<String, AssetClass>) {
+        self.other_classifications = classifications;
+        for (acct_num, values) in self.accounts_values.iter_mut() {
+            values.add_other_values(
+                self.other_values.get(acct_num).unwrap_or(&HashMap::new()),
+                &self.other_classifications,
+            );
+        }
+    }
+
+    /// merge combines another holdings snapshot into this one.  Accounts found within both
+    /// snapshots keep the values and shares of the newer snapshot based on parsed_at, rather than
+    /// conflicting.  The quotes are taken from the newer snapshot and transactions found within
+    /// both snapshots are only kept once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::{Duration, Utc};
+    /// use vapore_gui::holdings::{ShareValues, StockSymbol, VanguardHoldings};
+    ///
+    /// let mut old_account = ShareValues::new();
+    /// old_account.add_stock_value(StockSymbol::VV, 1000.0).unwrap();
+    /// let mut other_account = ShareValues::new();
+    /// other_account.add_stock_value(StockSymbol::BND, 500.0).unwrap();
+    /// let mut old_snapshot = VanguardHoldings::new(ShareValues::new_quote());
+    /// old_snapshot.accounts_values.insert(1, old_account);
+    /// old_snapshot.accounts_values.insert(2, other_account);
+    /// old_snapshot.set_parsed_at(Utc::now() - Duration::days(30));
+    ///
+    /// let mut new_account = ShareValues::new();
+    /// new_account.add_stock_value(StockSymbol::VV, 1200.0).unwrap();
+    /// let mut new_snapshot = VanguardHoldings::new(ShareValues::new_quote());
+    /// new_snapshot.accounts_values.insert(1, new_account);
+    ///
+    /// // The order of merging does not matter, the newer account values are kept
+    /// let mut merged = new_snapshot.clone();
+    /// merged.merge(old_snapshot.clone());
+    /// assert_eq!(merged.accounts_values[&1].stock_value(StockSymbol::VV).unwrap(), 1200.0);
+    /// assert_eq!(merged.accounts_values[&2].stock_value(StockSymbol::BND).unwrap(), 500.0);
+    /// assert_eq!(merged.parsed_at(), new_snapshot.parsed_at());
+    ///
+    /// old_snapshot.merge(new_snapshot);
+    /// assert_eq!(old_snapshot.accounts_values[&1].stock_value(StockSymbol::VV).unwrap(), 1200.0);
+    /// assert_eq!(old_snapshot.accounts_values[&2].stock_value(StockSymbol::BND).unwrap(), 500.0);
+    /// ```
+    pub fn merge(&mut self, other: VanguardHoldings) {
+        let other_is_newer = other.parsed_at > self.parsed_at;
+        for (acct_num, values) in other.accounts_values {
+            if other_is_newer || !self.accounts_values.contains_key(&acct_num) {
+                self.accounts_values.insert(acct_num, values);
+            }
+        }
+        for (acct_num, shares) in other.accounts_shares {
+            if other_is_newer || !self.accounts_shares.contains_key(&acct_num) {
+                self.accounts_shares.insert(acct_num, shares);
+            }
+        }
+        for (acct_num, values) in other.other_values {
+            if other_is_newer || !self.other_values.contains_key(&acct_num) {
+                self.other_values.insert(acct_num, values);
+            }
+        }
+        for (symbol, asset_class) in other.other_classifications {
+            self.other_classifications
+                .entry(symbol)
+                .or_insert(asset_class);
+        }
+        for (acct_num, distribution) in other.distributions {
+            if other_is_newer || !self.distributions.contains_key(&acct_num) {
+                self.distributions.insert(acct_num, distribution);
+            }
+        }
+        for transaction in other.transactions {
+            if !self.transactions.contains(&transaction) {
+                self.transactions.push(transaction);
+            }
+        }
+        if other_is_newer {
+            self.quotes = other.quotes;
+            self.quote_timestamps = other.quote_timestamps;
+            self.parsed_at = other.parsed_at;
+        }
+    }
+
+    /// add_quote_timestamp records the time when the quote for the stock symbol was retrieved.
+    pub fn add_quote_timestamp(&mut self, stock_symbol: StockSymbol, timestamp: DateTime<Utc>) {
+        self.quote_timestamps.insert(stock_symbol, timestamp);
+    }
+
+    /// quotes_age returns the age of the oldest retrieved quote.  None is returned when no quotes
+    /// have been retrieved, which means the quotes are the prices embedded in the downloaded file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::{Duration, Utc};
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut new_vanguard = holdings::VanguardHoldings::new(holdings::ShareValues::new_quote());
+    /// assert!(new_vanguard.quotes_age().is_none());
+    /// assert_eq!(new_vanguard.quotes_are_stale(Duration::hours(1)), None);
+    ///
+    /// new_vanguard.add_quote_timestamp(holdings::StockSymbol::VV, Utc::now() - Duration::days(3));
+    /// new_vanguard.add_quote_timestamp(holdings::StockSymbol::BND, Utc::now());
+    /// assert!(new_vanguard.quotes_age().unwrap() >= Duration::days(3));
+    /// assert_eq!(new_vanguard.quotes_are_stale(Duration::days(1)), Some(true));
+    /// assert_eq!(new_vanguard.quotes_are_stale(Duration::days(7)), Some(false));
+    /// ```
+    pub fn quotes_age(&self) -> Option<Duration> {
+        self.quote_timestamps
+            .values()
+            .min()
+            .map(|oldest| Utc::now() - *oldest)
+    }
+
+    /// quotes_are_stale returns whether the retrieved quotes are older than the max age.  None is
+    /// returned when no quotes have been retrieved, as with quotes_age, so that quotes from the
+    /// downloaded file are told apart from stale retrieved quotes.
+    pub fn quotes_are_stale(&self, max_age: Duration) -> Option<bool> {
+        self.quotes_age().map(|age| age > max_age)
+    }
+
+    /// add_missing_quotes retrieves the latest quotes from the provider for the supported stock
+    /// symbols whose quote was not retrieved within the ttl, and then revalues the accounts.
+    /// Quotes retrieved within the ttl are reused, so only the stale stock symbols are requested.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::{Duration, Utc};
+    /// use futures::executor::block_on;
+    /// use std::collections::HashMap;
+    /// use vapore_gui::holdings::{ShareValues, StaticProvider, StockSymbol, VanguardHoldings};
+    ///
+    /// let provider = StaticProvider::new(HashMap::from(StockSymbol::list().map(|stock_symbol| (stock_symbol, 50.0))));
+    /// let mut quotes = ShareValues::new_quote();
+    /// quotes.add_stock_value(StockSymbol::VV, 250.0).unwrap();
+    /// quotes.add_stock_value(StockSymbol::BND, 80.0).unwrap();
+    /// let mut shares = ShareValues::new();
+    /// shares.add_stock_value(StockSymbol::VV, 2.0).unwrap();
+    /// shares.add_stock_value(StockSymbol::BND, 2.0).unwrap();
+    /// let mut new_vanguard = VanguardHoldings::new(quotes);
+    /// new_vanguard.accounts_shares.insert(1, shares);
+    /// new_vanguard.add_quote_timestamp(StockSymbol::VV, Utc::now() - Duration::minutes(2));
+    /// new_vanguard.add_quote_timestamp(StockSymbol::BND, Utc::now() - Duration::hours(2));
+    ///
+    /// block_on(new_vanguard.add_missing_quotes(&provider, Duration::minutes(15))).unwrap();
+    /// // The recent VV quote is reused and the old BND quote is retrieved again
+    /// assert_eq!(new_vanguard.stock_quotes().stock_value(StockSymbol::VV).unwrap(), 250.0);
+    /// assert_eq!(new_vanguard.stock_quotes().stock_value(StockSymbol::BND).unwrap(), 50.0);
+    /// assert_eq!(new_vanguard.accounts_values[&1].stock_value(StockSymbol::BND).unwrap(), 100.0);
+    /// assert!(new_vanguard.quotes_age().unwrap() < Duration::minutes(15));
+    /// ```
+    pub async fn add_missing_quotes(
+        &mut self,
+        provider: &impl QuoteProvider,
+        ttl: Duration,
+    ) -> Result<()> {
+        let now = Utc::now();
+        let mut stale = Vec::new();
+        for stock_symbol in StockSymbol::list()
+            .into_iter()
+            .chain(self.registry.symbols())
+        {
+            let cached = self
+                .quote_timestamps
+                .get(&stock_symbol)
+                .map_or(false, |timestamp| now - *timestamp <= ttl);
+            if !cached {
+                self.quotes.add_stock_value(stock_symbol.clone(), 1.0)?;
+                stale.push(stock_symbol);
+            }
+        }
+        self.quotes
+            .add_missing_quotes_with(provider, &self.registry)
+            .await?;
+        for stock_symbol in stale {
+            self.add_quote_timestamp(stock_symbol, now);
+        }
+        self.revalue();
+        Ok(())
+    }
+
+    // Values the accounts from the shares and quotes.  Other symbols are not quoted, so their
+    // values from the download are added back along with their classifications
+    fn revalue(&mut self) {
+        self.accounts_values = HashMap::new();
+        for (acct_num, shares) in self.accounts_shares.iter() {
+            let mut values = *shares * self.quotes;
+            values.add_other_values(
+                self.other_values.get(acct_num).unwrap_or(&HashMap::new()),
+                &self.other_classifications,
+            );
+            self.accounts_values.insert(*acct_num, values);
+        }
+    }
+
+    /// add_placeholder_quotes retrieves quotes from the provider only for the stock symbols whose
+    /// quote is still the 1.0 placeholder, such as those which were not within the downloaded
+    /// file.  The quotes from the file are kept and nothing is requested when the file had all of
+    /// them, so reopening a file does not need the network.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use std::collections::HashMap;
+    /// use vapore_gui::holdings::{ShareValues, StaticProvider, StockSymbol, VanguardHoldings};
+    ///
+    /// let provider = StaticProvider::new(HashMap::from([(StockSymbol::BND, 80.0)]));
+    /// let mut quotes = ShareValues::new_quote();
+    /// for stock_symbol in StockSymbol::list() {
+    ///     quotes.add_stock_value(stock_symbol, 250.0).unwrap();
+    /// }
+    /// quotes.add_stock_value(StockSymbol::BND, 1.0).unwrap();
+    /// let mut new_vanguard = VanguardHoldings::new(quotes);
+    ///
+    /// block_on(new_vanguard.add_placeholder_quotes(&provider)).unwrap();
+    /// assert_eq!(new_vanguard.stock_quotes().stock_value(StockSymbol::VV).unwrap(), 250.0);
+    /// assert_eq!(new_vanguard.stock_quotes().stock_value(StockSymbol::BND).unwrap(), 80.0);
+    /// assert!(new_vanguard.quotes_age().is_some());
+    /// ```
+    pub async fn add_placeholder_quotes(&mut self, provider: &impl QuoteProvider) -> Result<()> {
+        let missing = self.quotes.missing_quote_symbols(&self.registry)?;
+        if missing.is_empty() {
+            return Ok(());
+        }
+        self.quotes
+            .add_missing_quotes_with(provider, &self.registry)
+            .await?;
+        let now = Utc::now();
+        for stock_symbol in missing {
+            self.add_quote_timestamp(stock_symbol, now);
+        }
+        Ok(())
+    }
+
+    /// reuse_cached_quotes copies the quotes that the cached holdings retrieved within the ttl for
+    /// the stock symbols whose quote is still the 1.0 placeholder, along with when they were
+    /// retrieved.  This is used when a file is reopened so that add_placeholder_quotes only
+    /// requests the quotes which are not cached.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::{Duration, Utc};
+    /// use vapore_gui::holdings::{ShareValues, StockSymbol, VanguardHoldings};
+    ///
+    /// let mut cached_quotes = ShareValues::new_quote();
+    /// cached_quotes.add_stock_value(StockSymbol::VV, 250.0).unwrap();
+    /// cached_quotes.add_stock_value(StockSymbol::BND, 80.0).unwrap();
+    /// let mut cached = VanguardHoldings::new(cached_quotes);
+    /// cached.add_quote_timestamp(StockSymbol::VV, Utc::now() - Duration::minutes(2));
+    /// cached.add_quote_timestamp(StockSymbol::BND, Utc::now() - Duration::hours(2));
+    ///
+    /// let mut new_vanguard = VanguardHoldings::new(ShareValues::new_quote());
+    /// new_vanguard.reuse_cached_quotes(&cached, Duration::minutes(15)).unwrap();
+    /// // Only the VV quote is recent enough to reuse
+    /// assert_eq!(new_vanguard.stock_quotes().stock_value(StockSymbol::VV).unwrap(), 250.0);
+    /// assert_eq!(new_vanguard.stock_quotes().stock_value(StockSymbol::BND).unwrap(), 1.0);
+    /// assert!(new_vanguard.quotes_age().unwrap() < Duration::minutes(15));
+    /// ```
+    pub fn reuse_cached_quotes(&mut self, cached: &VanguardHoldings, ttl: Duration) -> Result<()> {
+        let now = Utc::now();
+        for stock_symbol in self.quotes.missing_quote_symbols(&self.registry)? {
+            if let Some(timestamp) = cached.quote_timestamps.get(&stock_symbol) {
+                if now - *timestamp <= ttl {
+                    self.quotes.add_stock_value_f64(
+                        stock_symbol.clone(),
+                        cached.quotes.stock_value_f64(stock_symbol.clone())?,
+                    )?;
+                    self.add_quote_timestamp(stock_symbol, *timestamp);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// missing_quotes returns the held stock symbols whose quote is still the 1.0 placeholder,
+    /// which happens when the quote was not within the downloaded file and the retrieval failed.
+    /// The purchase/sell shares calculated for these symbols will be wrong, so this can be used
+    /// as a dry-run before rebalancing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut quotes = holdings::ShareValues::new_quote();
+    /// quotes.add_stock_value(holdings::StockSymbol::VV, 250.0).unwrap();
+    /// let mut account = holdings::ShareValues::new();
+    /// account.add_stock_value(holdings::StockSymbol::VV, 5000.0).unwrap();
+    /// account.add_stock_value(holdings::StockSymbol::BND, 2000.0).unwrap();
+    ///
+    /// let mut new_vanguard = holdings::VanguardHoldings::new(quotes);
+    /// new_vanguard.accounts_values.insert(12345, account);
+    ///
+    /// assert_eq!(new_vanguard.missing_quotes(), vec![holdings::StockSymbol::BND]);
+    /// ```
+    pub fn missing_quotes(&self) -> Vec<StockSymbol> {
+        StockSymbol::list()
+            .into_iter()
+            .chain(self.registry.symbols())
+            .filter(|stock_symbol| {
+                self.quotes.stock_value(stock_symbol.clone()).unwrap_or(0.0) == 1.0
+                    && self.accounts_values.values().any(|account| {
+                        account.stock_value(stock_symbol.clone()).unwrap_or(0.0) != 0.0
+                    })
+            })
+            .collect()
+    }
+
+    pub fn get_distributions(&self, account_number: &u32) -> f32 {
+        *self.distributions
+            .get(account_number)
+            .unwrap_or(&0.0)
+    }
+
+    /// value_at calculates the value of the account at the end of the date.  The holdings at the
+    /// date are found by removing all of the transactions after the date from the current
+    /// holdings, where the shares of each transaction are removed from its stock symbol and the
+    /// net amount from the cash within VMFXX, so that the cash spent on a later purchase is added
+    /// back.  The holdings are then valued with the historical quotes from the provider.  Other
+    /// holdings are not included.  Errors if the date is before the first transaction of the
+    /// account, since the holdings from before the transaction history are not known.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use chrono::NaiveDate;
+    /// use vapore_gui::holdings::{QuoteProvider, ShareValues, StockSymbol, Transaction, TransactionType, VanguardHoldings};
+    ///
+    /// struct HistoricalProvider;
+    /// impl QuoteProvider for HistoricalProvider {
+    ///     fn quote(&self, _stock_symbol: StockSymbol) -> Result<f32> {
+    ///         Ok(100.0)
+    ///     }
+    ///     fn quote_at(&self, _stock_symbol: StockSymbol, _date: NaiveDate) -> Result<f32> {
+    ///         Ok(50.0)
+    ///     }
+    /// }
+    ///
+    /// let mut shares = ShareValues::new();
+    /// shares.add_stock_value(StockSymbol::VV, 30.0).unwrap();
+    /// shares.add_stock_value(StockSymbol::VMFXX, 200.0).unwrap();
+    /// let mut new_vanguard = VanguardHoldings::new(ShareValues::new_quote());
+    /// new_vanguard.accounts_shares.insert(1, shares);
+    /// let march = NaiveDate::from_ymd_opt(2023, 3, 1).unwrap();
+    /// let june = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+    /// new_vanguard.add_transaction(Transaction::new(1, march, StockSymbol::VV, 20.0, -1000.0, TransactionType::Buy));
+    /// new_vanguard.add_transaction(Transaction::new(1, june, StockSymbol::VV, 10.0, -500.0, TransactionType::Buy));
+    /// new_vanguard.add_transaction(Transaction::new(1, june, StockSymbol::VMFXX, 0.0, 200.0, TransactionType::Sweepin));
+    ///
+    /// // 20 VV shares at 50 on April 1st, along with the 500 of cash spent on VV in June, without
+    /// // the 200 swept in during June
+    /// let april = NaiveDate::from_ymd_opt(2023, 4, 1).unwrap();
+    /// assert_eq!(new_vanguard.value_at(1, april, &HistoricalProvider).unwrap(), 1500.0);
+    ///
+    /// // January is before the first transaction in March
+    /// let january = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+    /// assert!(new_vanguard.value_at(1, january, &HistoricalProvider).is_err());
+    /// ```
+    pub fn value_at(
+        &self,
+        account: u32,
+        date: NaiveDate,
+        provider: &impl QuoteProvider,
+    ) -> Result<f32> {
+        let mut holdings = *self
+            .accounts_shares
+            .get(&account)
+            .ok_or_else(|| anyhow!("Account {} not found", account))?;
+        let account_transactions = self
+            .transactions
+            .iter()
+            .filter(|transaction| transaction.account_number == account)
+            .collect::<Vec<&Transaction>>();
+        let first_trade_date = account_transactions
+            .iter()
+            .map(|transaction| transaction.trade_date)
+            .min()
+            .ok_or_else(|| anyhow!("Account {} has no transaction history", account))?;
+        ensure!(
+            date >= first_trade_date,
+            "{} is before the first transaction of account {} on {}",
+            date,
+            account,
+            first_trade_date
+        );
+        for transaction in account_transactions {
+            if transaction.trade_date <= date {
+                continue;
+            }
+            // Cash transactions have 0 shares and only move the net amount
+            if !transaction.symbol.is_cash() && transaction.symbol != StockSymbol::Empty {
+                holdings.subtract_stock_value(transaction.symbol.clone(), transaction.shares)?;
+            }
+            holdings.subtract_stock_value(StockSymbol::VMFXX, transaction.net_amount)?;
+        }
+        let mut value = holdings.stock_value(StockSymbol::VMFXX)?;
+        for stock_symbol in StockSymbol::list() {
+            let shares = holdings.stock_value(stock_symbol.clone())?;
+            if shares != 0.0 {
+                value += shares * provider.quote_at(stock_symbol, date)?;
+            }
+        }
+        Ok(value)
+    }
+
+    // Calculated the previous end of year holdings value based on the holdings times the quotes
+    // from December 31st of the previous year.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn eoy_value(
+        &mut self,
+        year: u32,
+        traditional_acct_num: u32,
+        provider: &impl QuoteProvider,
+    ) -> Result<Option<f32>> {
+        let trad_holdings = *self
+            .accounts_shares
+            .get(&traditional_acct_num)
+            .unwrap_or(&ShareValues::new());
         if let Some(holdings) =
-            self.eoy_traditional_holdings(year, traditional_acct_num, trad_holdings)
+            self.eoy_traditional_holdings(year, traditional_acct_num, trad_holdings)?
         {
             let mut quotes = ShareValues::new_quote();
-            quotes.add_missing_eoy_quotes(year - 1).await?;
+            quotes.add_missing_eoy_quotes(year - 1, provider).await?;
             let eoy_value = (holdings * quotes).total_value();
             Ok(Some(eoy_value))
         } else {
             Ok(None)
         }
     }
+
     // Takes the current holdings and subtracts all transaction since December 31st to come to the
     // holdings at that date.
     #[cfg(not(target_arch = "wasm32"))]
@@ -1023,11 +3814,13 @@ impl VanguardHoldings {
         year: u32,
         traditional_acct_num: u32,
         trad_holdings: ShareValues,
-    ) -> Option<ShareValues> {
+    ) -> Result<Option<ShareValues>> {
         let mut enough_transaction = false;
         let mut total_transactions = 0;
         let mut eoy_holdings = trad_holdings;
-        let previous_year = NaiveDate::from_ymd_opt(year as i32 - 1, 12, 31)?;
+        let Some(previous_year) = NaiveDate::from_ymd_opt(year as i32 - 1, 12, 31) else {
+            return Ok(None);
+        };
         let following_year = previous_year + Duration::days(365);
         for transaction in &self.transactions {
             // If the transaction is newer thand December 31st of the previous year,
@@ -1040,12 +3833,12 @@ impl VanguardHoldings {
                 total_transactions += 1;
                 // Cash is allocated in VMFXX.  Shares is always 0 in the transaction, so
                 // net amount needs to be subtracted
-                if transaction.symbol == StockSymbol::VMFXX {
+                if transaction.symbol.is_cash() {
                     eoy_holdings
-                        .subtract_stock_value(transaction.symbol.clone(), transaction.net_amount);
+                        .subtract_stock_value(transaction.symbol.clone(), transaction.net_amount)?;
                 } else if transaction.symbol != StockSymbol::Empty {
                     eoy_holdings
-                        .subtract_stock_value(transaction.symbol.clone(), transaction.shares);
+                        .subtract_stock_value(transaction.symbol.clone(), transaction.shares)?;
                 } else if transaction.transaction_type == TransactionType::Distribution && transaction.trade_date < following_year {
                     let distribution = self
                         .distributions
@@ -1058,68 +3851,989 @@ impl VanguardHoldings {
             }
         }
         if !enough_transaction || total_transactions == 0 {
-            None
+            Ok(None)
         } else {
-            Some(eoy_holdings)
+            Ok(Some(eoy_holdings))
         }
     }
-    #[cfg(not(target_arch = "wasm32"))]
-    pub fn update_with_yahoo_quotes(&mut self) -> Result<()> {
-        self.quotes = ShareValues::new_quote();
-        block_on(self.quotes.add_missing_quotes())?;
-        self.accounts_values = HashMap::new();
-        for (acct_num, shares) in self.accounts_shares.iter() {
-            self.accounts_values
-                .insert(*acct_num, *shares * self.quotes);
+}
+
+impl Default for VanguardHoldings {
+    fn default() -> Self {
+        VanguardHoldings::new(ShareValues::new_quote())
+    }
+}
+
+/// OrderAction is whether an order line buys or sells the stock symbol
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OrderAction {
+    Buy,
+    Sell,
+}
+
+/// OrderLine holds a single trade of an order ticket.  The shares and dollars are always positive
+/// with the action giving the direction, and est_tax is the estimated tax owed on the gains of a
+/// taxable sale, which is 0 for purchases and for sales within tax advantaged accounts.
+#[derive(Clone, PartialEq, Debug)]
+pub struct OrderLine {
+    pub label: HoldingLabel,
+    pub action: OrderAction,
+    pub shares: f32,
+    pub dollars: f32,
+    pub est_tax: f32,
+}
+
+/// AccountHoldings is a holder of current, target, and purchase/sales information for an account.
+/// It also creates a Display for this information.
+#[derive(Debug)]
+pub struct AccountHoldings {
+    pub current: ShareValues,
+    pub target: ShareValues,
+    pub sale_purchases_needed: ShareValues,
+    pub wash_sale_warnings: Vec<StockSymbol>, // Symbols sold at a loss while bought elsewhere
+    placement_note: String, // Explanation of why the account holds its target assets
+}
+
+impl AccountHoldings {
+    /// new creates a new AccountHoldings struct from current, target, and sales/purchases
+    /// Sharevalues structs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset, holdings};
+    ///
+    /// let sub_allocations = asset::SubAllocations::new().unwrap();
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    ///
+    /// let brokerage_current = holdings::ShareValues::new();
+    /// let brokerage_target = holdings::ShareValues::new_target(sub_allocations, 10000.0, 0.0, 0.0, 0.0, 0.0);
+    /// let purchase_sales = brokerage_current / quotes;
+    ///
+    /// let brokerage_account = holdings::AccountHoldings::new(brokerage_current, brokerage_target, purchase_sales);
+    /// ```
+    pub fn new(
+        current: ShareValues,
+        target: ShareValues,
+        sale_purchases_needed: ShareValues,
+    ) -> Self {
+        AccountHoldings {
+            current,
+            target,
+            sale_purchases_needed,
+            wash_sale_warnings: Vec::new(),
+            placement_note: String::new(),
+        }
+    }
+
+    /// retarget creates the AccountHoldings needed to move from the current holdings to a new model
+    /// allocation, which allows the strategy to be changed mid-stream.  The total target value is
+    /// kept and invested within the new model, and the sales/purchases needed are calculated with
+    /// the stock quotes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset, holdings};
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let sixty_forty = asset::SubAllocations::new().unwrap();
+    /// let eighty_twenty = asset::SubAllocations::new_custom(asset::Allocations::custom(80.0, 20.0, 0.0).unwrap()).unwrap();
+    ///
+    /// let current = holdings::ShareValues::new_target(sixty_forty, 10000.0, 0.0, 0.0, 0.0, 0.0);
+    /// let account = holdings::AccountHoldings::new(current, current, holdings::ShareValues::new());
+    ///
+    /// let retargeted = account.retarget(eighty_twenty, quotes);
+    /// let (stock, bond, _) = retargeted.target.percent_stock_bond_infl();
+    /// assert!((stock - 80.0).abs() < 0.01);
+    /// assert!((bond - 20.0).abs() < 0.01);
+    /// assert_eq!(retargeted.current, current);
+    /// assert!(retargeted.sale_purchases_needed.stock_value(holdings::StockSymbol::VV).unwrap() > 0.0);
+    /// assert!(retargeted.sale_purchases_needed.stock_value(holdings::StockSymbol::BND).unwrap() < 0.0);
+    /// assert!(retargeted.sale_purchases_needed.total_value().abs() < 0.01);
+    /// ```
+    pub fn retarget(&self, new_model: SubAllocations, quotes: ShareValues) -> AccountHoldings {
+        let target =
+            ShareValues::new_target(new_model, self.target.total_value(), 0.0, 0.0, 0.0, 0.0);
+        AccountHoldings::new(self.current, target, (target - self.current) / quotes)
+    }
+
+    /// split_by_account splits the rebalance of accounts which were combined into one, such as with
+    /// VanguardHoldings::combined_values, back into the accounts so that it is known which account
+    /// to trade in.  Each account receives the share of the target value matching its share of the
+    /// current value, and cash added to the combined account is split in the same way.  To keep
+    /// from buying a stock symbol within one account while selling it within another, the sales
+    /// of each stock symbol are taken from the accounts holding it in proportion to their holdings,
+    /// and the purchases are split between the accounts in proportion to the value left to fill.
+    /// Holdings are only sold across every stock symbol when cash removed from an account is more
+    /// than its sales.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use vapore_gui::{asset, holdings::{AccountHoldings, ShareValues, StockSymbol}};
+    ///
+    /// let mut rollover = ShareValues::new();
+    /// rollover.add_stock_value(StockSymbol::VV, 6000.0).unwrap();
+    /// let mut sep = ShareValues::new();
+    /// sep.add_stock_value(StockSymbol::BND, 2000.0).unwrap();
+    /// let accounts_values = HashMap::from([(1, rollover), (2, sep)]);
+    ///
+    /// let sixty_forty = asset::SubAllocations::new().unwrap();
+    /// let combined = rollover + sep;
+    /// let target = ShareValues::new_target(sixty_forty, combined.total_value(), 0.0, 0.0, 0.0, 0.0);
+    /// let quotes = ShareValues::new_quote();
+    /// let account = AccountHoldings::new(combined, target, (target - combined) / quotes);
+    ///
+    /// let split = account.split_by_account(&accounts_values, quotes);
+    /// assert_eq!(split[&1].current, rollover);
+    /// assert!((split[&1].target.total_value() - 6000.0).abs() < 0.01);
+    /// assert!((split[&2].target.total_value() - 2000.0).abs() < 0.01);
+    /// assert!(split[&1].sale_purchases_needed.stock_value(StockSymbol::VV).unwrap() < 0.0);
+    /// assert!(split[&2].sale_purchases_needed.stock_value(StockSymbol::BND).unwrap() < 0.0);
+    /// let total_traded = split[&1].sale_purchases_needed + split[&2].sale_purchases_needed;
+    /// for stock_symbol in StockSymbol::list() {
+    ///     let combined_traded = account.sale_purchases_needed.stock_value(stock_symbol.clone()).unwrap();
+    ///     assert!((total_traded.stock_value(stock_symbol.clone()).unwrap() - combined_traded).abs() < 0.01);
+    ///
+    ///     // No stock symbol is bought within one account and sold within the other
+    ///     let rollover_traded = split[&1].sale_purchases_needed.stock_value(stock_symbol.clone()).unwrap();
+    ///     let sep_traded = split[&2].sale_purchases_needed.stock_value(stock_symbol).unwrap();
+    ///     assert!(rollover_traded * sep_traded >= 0.0);
+    /// }
+    /// ```
+    pub fn split_by_account(
+        &self,
+        accounts_values: &HashMap<u32, ShareValues>,
+        quotes: ShareValues,
+    ) -> HashMap<u32, AccountHoldings> {
+        let total = accounts_values
+            .values()
+            .map(|values| values.total_value_f64())
+            .sum::<f64>();
+        let combined = accounts_values
+            .values()
+            .fold(ShareValues::new(), |combined, values| combined + *values);
+        // Fraction of each holding kept, which is below 1 for the stock symbols being sold
+        let keep_fraction = self.target.combine(combined, |target, held| {
+            if held > 0.0 {
+                (target / held).clamp(0.0, 1.0)
+            } else {
+                0.0
+            }
+        });
+        let mut accounts_kept = HashMap::new();
+        for (account_number, current) in accounts_values {
+            let account_target = if total == 0.0 {
+                self.target.total_value_f64() / accounts_values.len() as f64
+            } else {
+                self.target.total_value_f64() * current.total_value_f64() / total
+            };
+            let mut kept = current.combine(keep_fraction, |value, fraction| value * fraction);
+            // Cash removed past the sales of the account is taken from all of its holdings
+            if kept.total_value_f64() > account_target {
+                kept = kept.scale(account_target / kept.total_value_f64());
+            }
+            let room = account_target - kept.total_value_f64();
+            accounts_kept.insert(*account_number, (kept, room));
+        }
+        let total_kept = accounts_kept
+            .values()
+            .fold(ShareValues::new(), |total_kept, (kept, _)| {
+                total_kept + *kept
+            });
+        let to_buy = self
+            .target
+            .combine(total_kept, |target, kept| (target - kept).max(0.0));
+        let total_room = accounts_kept.values().map(|(_, room)| room).sum::<f64>();
+        accounts_values
+            .iter()
+            .map(|(account_number, current)| {
+                let (kept, room) = accounts_kept[account_number];
+                let mut target = if total_room > 0.0 {
+                    kept + to_buy.scale(room / total_room)
+                } else {
+                    kept
+                };
+                target.add_outside_stock_value(current.outside_stock_value());
+                target.add_outside_bond_value(current.outside_bond_value());
+                let mut account =
+                    AccountHoldings::new(*current, target, (target - *current) / quotes);
+                account.wash_sale_warnings = self.wash_sale_warnings.clone();
+                account.placement_note = self.placement_note.clone();
+                (*account_number, account)
+            })
+            .collect()
+    }
+
+    // Multiplies the current and target values by the factor.  The purchase/sell values are
+    // shares rather than dollar values and are kept as is.
+    fn scaled(&self, factor: f64) -> AccountHoldings {
+        AccountHoldings {
+            current: self.current.scale(factor),
+            target: self.target.scale(factor),
+            sale_purchases_needed: self.sale_purchases_needed,
+            wash_sale_warnings: self.wash_sale_warnings.clone(),
+            placement_note: self.placement_note.clone(),
+        }
+    }
+
+    /// add_placement_note adds an explanation of why the account holds its target assets
+    pub fn add_placement_note(&mut self, placement_note: String) {
+        self.placement_note = placement_note;
+    }
+
+    /// placement_note returns the explanation of why the account holds its target assets, which
+    /// is added when the targets are calculated with calc::to_buy.  This makes the risk and tax
+    /// placement between accounts auditable.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{calc, holdings};
+    ///
+    /// let mut roth = holdings::ShareValues::new();
+    /// roth.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+    /// let mut traditional = holdings::ShareValues::new();
+    /// traditional.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+    ///
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2070,
+    ///     roth_ira: calc::AccountInputs::new(roth),
+    ///     traditional_ira: calc::AccountInputs::new(traditional),
+    ///     ..Default::default()
+    /// };
+    /// let rebalance = calc::to_buy(&options, holdings::ShareValues::new_quote()).unwrap();
+    ///
+    /// let roth_note = rebalance.roth_ira.placement_note();
+    /// assert!(roth_note.contains("VWO") && roth_note.contains("VXUS"));
+    /// assert!(roth_note.contains("highest risk"));
+    /// let traditional_note = rebalance.traditional_ira.placement_note();
+    /// assert!(traditional_note.contains("BND") && !traditional_note.contains("VWO"));
+    /// ```
+    pub fn placement_note(&self) -> &str {
+        &self.placement_note
+    }
+
+    /// current_allocation returns the stock, bond, and inflation protected percentages of the
+    /// current holdings of the account
+    pub fn current_allocation(&self) -> (f32, f32, f32) {
+        self.current.percent_stock_bond_infl()
+    }
+
+    /// target_allocation returns the stock, bond, and inflation protected percentages of the
+    /// target holdings of the account
+    pub fn target_allocation(&self) -> (f32, f32, f32) {
+        self.target.percent_stock_bond_infl()
+    }
+
+    /// drift returns how far the current stock, bond, and inflation protected percentages of the
+    /// account are from the target, in percentage points.  Positive values are over the target.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VV, 7000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::BND, 3000.0).unwrap();
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 6000.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::BND, 4000.0).unwrap();
+    ///
+    /// let account = holdings::AccountHoldings::new(current, target, target - current);
+    /// assert_eq!(account.current_allocation(), (70.0, 30.0, 0.0));
+    /// assert_eq!(account.target_allocation(), (60.0, 40.0, 0.0));
+    /// assert_eq!(account.drift(), (10.0, -10.0, 0.0));
+    /// ```
+    pub fn drift(&self) -> (f32, f32, f32) {
+        let (current_stock, current_bond, current_inflation) = self.current_allocation();
+        let (target_stock, target_bond, target_inflation) = self.target_allocation();
+        (
+            current_stock - target_stock,
+            current_bond - target_bond,
+            current_inflation - target_inflation,
+        )
+    }
+
+    /// short_term_sells returns the stock symbols which are sold within the sales/purchases needed
+    /// while holding a lot acquired less than a year ago.  Selling these lots would have gains
+    /// taxed at the short term capital gains rate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::{Duration, Local};
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VV, 7000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::BND, 3000.0).unwrap();
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 6000.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::BND, 4000.0).unwrap();
+    /// let account = holdings::AccountHoldings::new(current, target, target - current);
+    ///
+    /// let today = Local::now().date_naive();
+    /// let recent = holdings::Lot::new(holdings::StockSymbol::VV, 5.0, 200.0, today - Duration::days(30));
+    /// let old = holdings::Lot::new(holdings::StockSymbol::VV, 25.0, 150.0, today - Duration::days(800));
+    /// let recent_bond = holdings::Lot::new(holdings::StockSymbol::BND, 5.0, 72.0, today - Duration::days(30));
+    ///
+    /// assert_eq!(account.short_term_sells(&[old.clone()]), Vec::new());
+    /// assert_eq!(
+    ///     account.short_term_sells(&[old, recent.clone(), recent, recent_bond]),
+    ///     vec![holdings::StockSymbol::VV]
+    /// );
+    /// ```
+    pub fn short_term_sells(&self, lots: &[Lot]) -> Vec<StockSymbol> {
+        let today = chrono::Local::now().date_naive();
+        let mut short_term_symbols = Vec::new();
+        for lot in lots {
+            let selling = self
+                .sale_purchases_needed
+                .stock_value(lot.symbol.clone())
+                .map(|value| value < 0.0)
+                .unwrap_or(false);
+            if selling && !lot.is_long_term(today) && !short_term_symbols.contains(&lot.symbol) {
+                short_term_symbols.push(lot.symbol.clone())
+            }
+        }
+        short_term_symbols
+    }
+
+    /// correct_ratio_only returns the shares to buy and sell so that the current holdings reach the
+    /// target stock percent, without rebalancing within the stocks or within the bonds.  The stock
+    /// ETFs and the bond ETFs are each scaled to keep their existing shape, and when there are no
+    /// holdings to scale the default sub allocation is bought.  Registered symbols and the outside
+    /// stock and bond count towards the current ratio but are not traded, and cash is left as is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VV, 6000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::VXUS, 2000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::BND, 2000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::VMFXX, 500.0).unwrap();
+    /// let account = holdings::AccountHoldings::new(
+    ///     current,
+    ///     holdings::ShareValues::new(),
+    ///     holdings::ShareValues::new(),
+    /// );
+    ///
+    /// let trades = account.correct_ratio_only(60.0, holdings::ShareValues::new_quote());
+    /// assert_eq!(trades.stock_value(holdings::StockSymbol::VV).unwrap(), -1500.0);
+    /// assert_eq!(trades.stock_value(holdings::StockSymbol::VXUS).unwrap(), -500.0);
+    /// assert_eq!(trades.stock_value(holdings::StockSymbol::BND).unwrap(), 2000.0);
+    /// assert_eq!(trades.stock_value(holdings::StockSymbol::VO).unwrap(), 0.0);
+    /// assert_eq!(trades.stock_value(holdings::StockSymbol::VMFXX).unwrap(), 0.0);
+    /// ```
+    pub fn correct_ratio_only(&self, target_stock_pct: f32, quotes: ShareValues) -> ShareValues {
+        let current = self.current;
+        let (stock_percent, _, _) = current.percent_stock_bond_infl();
+        let invested_value = (current.total_value() as f64 - current.vmfxx - current.other
+            + current.other_classified.iter().sum::<f64>()
+            + current.outside_bond
+            + current.outside_stock) as f32;
+        let mut trades = ShareValues::new();
+        if invested_value <= 0.0 || stock_percent.is_nan() {
+            return trades;
+        }
+        let value =
+            |stock_symbol: &StockSymbol| current.stock_value(stock_symbol.clone()).unwrap_or(0.0);
+        let (stock_symbols, bond_symbols): (Vec<StockSymbol>, Vec<StockSymbol>) =
+            StockSymbol::list()
+                .into_iter()
+                .partition(|stock_symbol| stock_symbol.is_stock());
+        let stock_value: f32 = stock_symbols.iter().map(value).sum();
+        let bond_value: f32 = bond_symbols.iter().map(value).sum();
+        let change = ((target_stock_pct.clamp(0.0, 100.0) - stock_percent) / 100.0
+            * invested_value)
+            .clamp(-stock_value, bond_value);
+
+        // Each group keeps its shape, or takes the default sub allocation when nothing is held
+        for (symbols, old_value, new_value, default_percent_stock) in [
+            (stock_symbols, stock_value, stock_value + change, 100.0),
+            (bond_symbols, bond_value, bond_value - change, 0.0),
+        ] {
+            let default_sub_allocations =
+                Allocations::custom(default_percent_stock, 100.0 - default_percent_stock, 0.0)
+                    .and_then(SubAllocations::new_custom);
+            for stock_symbol in symbols {
+                let symbol_value = if old_value > 0.0 {
+                    value(&stock_symbol) * new_value / old_value
+                } else {
+                    default_sub_allocations
+                        .as_ref()
+                        .map(|sub_allocations| sub_allocations.symbol_percent(&stock_symbol))
+                        .unwrap_or(0.0)
+                        / 100.0
+                        * new_value
+                };
+                let quote = quotes.stock_value(stock_symbol.clone()).unwrap_or(1.0);
+                if let Ok(trade) = trades.value_mut(stock_symbol.clone()) {
+                    *trade = ((symbol_value - value(&stock_symbol)) / quote) as f64
+                }
+            }
+        }
+        trades
+    }
+
+    /// total_to_buy returns the dollar value of all purchases needed, which is the sum of the
+    /// positive sales/purchases needed times the stock quotes.  Cash, VMFXX, is left out since it
+    /// is what pays for the purchases.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut quotes = holdings::ShareValues::new_quote();
+    /// quotes.add_stock_value(holdings::StockSymbol::VV, 200.0).unwrap();
+    /// quotes.add_stock_value(holdings::StockSymbol::BND, 80.0).unwrap();
+    ///
+    /// // 1000 of cash was added to the account
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VV, 7000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::BND, 2000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::VMFXX, 1000.0).unwrap();
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 6000.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::BND, 4000.0).unwrap();
+    /// let account = holdings::AccountHoldings::new(current, target, (target - current) / quotes);
+    ///
+    /// assert!((account.total_to_buy(quotes) - 2000.0).abs() < 0.01);
+    /// assert!((account.total_to_sell(quotes) - 1000.0).abs() < 0.01);
+    /// assert!((account.net_cash_delta(quotes) - 1000.0).abs() < 0.01);
+    /// ```
+    pub fn total_to_buy(&self, quotes: ShareValues) -> f32 {
+        self.traded_values(quotes)
+            .combine(ShareValues::new_quote(), |value, _| value.max(0.0))
+            .total_value()
+    }
+
+    /// total_to_sell returns the dollar value of all sales needed, as a positive number, which is
+    /// the sum of the negative sales/purchases needed times the stock quotes.  Cash, VMFXX, is left
+    /// out the same as total_to_buy.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VV, 7000.0).unwrap();
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 6000.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::VMFXX, 1000.0).unwrap();
+    /// let account = holdings::AccountHoldings::new(current, target, (target - current) / quotes);
+    ///
+    /// assert_eq!(account.total_to_sell(quotes), 1000.0);
+    /// assert_eq!(account.total_to_buy(quotes), 0.0);
+    /// ```
+    pub fn total_to_sell(&self, quotes: ShareValues) -> f32 {
+        -self
+            .traded_values(quotes)
+            .combine(ShareValues::new_quote(), |value, _| value.min(0.0))
+            .total_value()
+    }
+
+    /// net_cash_delta returns the dollars bought minus the dollars sold, which is the cash that the
+    /// trades use.  For a cash neutral plan this is about equal to the cash added to the account,
+    /// and it is negative when the sales leave cash in the account.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VV, 5000.0).unwrap();
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 3000.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::BND, 1500.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::VMFXX, 500.0).unwrap();
+    /// let account = holdings::AccountHoldings::new(current, target, (target - current) / quotes);
+    ///
+    /// assert_eq!(account.net_cash_delta(quotes), -500.0);
+    /// ```
+    pub fn net_cash_delta(&self, quotes: ShareValues) -> f32 {
+        self.total_to_buy(quotes) - self.total_to_sell(quotes)
+    }
+
+    /// order_ticket returns an order line for each stock symbol bought or sold, including the
+    /// registered stock symbols and the other stock symbols, which are traded as a single dollar
+    /// value.  For taxable accounts the lots held are passed in and the shares sold are taken from
+    /// the lots chosen by the lot strategy.  Each sale is annotated with the tax on the gains of
+    /// those lots, where lots held less than a year are taxed at the short term rate and the rest
+    /// at the long term rate.  Losses offset gains of the same term, and other stock symbols have
+    /// no estimated tax as their lots can not be priced.  Tax advantaged accounts pass in None and
+    /// have no tax on any sale.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::{Duration, Local};
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut quotes = holdings::ShareValues::new_quote();
+    /// quotes.add_stock_value(holdings::StockSymbol::VV, 200.0).unwrap();
+    ///
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VV, 10000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::Other("VTI".to_string()), 1000.0).unwrap();
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 6000.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
+    /// let account = holdings::AccountHoldings::new(current, target, (target - current) / quotes);
+    ///
+    /// // The 20 shares of VV sold come from the long term 100 lot, with 2000 of gains taxed at 15%
+    /// let today = Local::now().date_naive();
+    /// let lots = [
+    ///     holdings::Lot::new(holdings::StockSymbol::VV, 30.0, 100.0, today - Duration::days(800)),
+    ///     holdings::Lot::new(holdings::StockSymbol::VV, 10.0, 150.0, today - Duration::days(30)),
+    ///     holdings::Lot::new(holdings::StockSymbol::VV, 10.0, 250.0, today - Duration::days(30)),
+    /// ];
+    /// let rates = holdings::CapitalGainsRates::new(0.3, 0.15).unwrap();
+    /// let registry = holdings::SymbolRegistry::new();
+    ///
+    /// let order_lines = account.order_ticket(
+    ///     quotes,
+    ///     &registry,
+    ///     Some(&lots),
+    ///     holdings::LotStrategy::LongTermFirst,
+    ///     rates,
+    /// );
+    /// assert_eq!(order_lines.len(), 3);
+    /// let sell = &order_lines[0];
+    /// assert_eq!(sell.label, holdings::HoldingLabel::Symbol(holdings::StockSymbol::VV));
+    /// assert_eq!(sell.action, holdings::OrderAction::Sell);
+    /// assert_eq!(sell.shares, 20.0);
+    /// assert_eq!(sell.dollars, 4000.0);
+    /// assert!((sell.est_tax - 300.0).abs() < 0.01);
+    /// let buy = &order_lines[1];
+    /// assert_eq!(buy.label, holdings::HoldingLabel::Symbol(holdings::StockSymbol::BND));
+    /// assert_eq!(buy.action, holdings::OrderAction::Buy);
+    /// assert_eq!(buy.est_tax, 0.0);
+    /// let other = &order_lines[2];
+    /// assert_eq!(other.label, holdings::HoldingLabel::Other);
+    /// assert_eq!(other.action, holdings::OrderAction::Sell);
+    /// assert_eq!(other.dollars, 1000.0);
+    ///
+    /// // Selling the highest basis lots first sells 10 shares at a 500 short term loss, which
+    /// // offsets 500 of the short term gains of the 150 lot
+    /// let order_lines = account.order_ticket(
+    ///     quotes,
+    ///     &registry,
+    ///     Some(&lots),
+    ///     holdings::LotStrategy::HighestBasis,
+    ///     rates,
+    /// );
+    /// assert_eq!(order_lines[0].est_tax, 0.0);
+    ///
+    /// // The same sale within an IRA has no tax
+    /// let order_lines = account.order_ticket(
+    ///     quotes,
+    ///     &registry,
+    ///     None,
+    ///     holdings::LotStrategy::LongTermFirst,
+    ///     rates,
+    /// );
+    /// assert!(order_lines.iter().all(|order_line| order_line.est_tax == 0.0));
+    /// ```
+    pub fn order_ticket(
+        &self,
+        quotes: ShareValues,
+        registry: &SymbolRegistry,
+        lots: Option<&[Lot]>,
+        strategy: LotStrategy,
+        rates: CapitalGainsRates,
+    ) -> Vec<OrderLine> {
+        let today = chrono::Local::now().date_naive();
+        let short_term_symbols = lots
+            .map(|lots| self.short_term_sells(lots))
+            .unwrap_or_default();
+        let mut order_lines = Vec::new();
+        for stock_symbol in StockSymbol::list().into_iter().chain(registry.symbols()) {
+            let shares = self
+                .sale_purchases_needed
+                .stock_value(stock_symbol.clone())
+                .unwrap_or(0.0);
+            if shares == 0.0 {
+                continue;
+            }
+            let quote = quotes.stock_value(stock_symbol.clone()).unwrap_or(0.0);
+            let action = if shares > 0.0 {
+                OrderAction::Buy
+            } else {
+                OrderAction::Sell
+            };
+            let est_tax = match lots {
+                Some(lots) if action == OrderAction::Sell => {
+                    let symbol_lots = lots
+                        .iter()
+                        .filter(|lot| lot.symbol == stock_symbol)
+                        .cloned()
+                        .collect::<Vec<Lot>>();
+                    let mut short_term_gains = 0.0;
+                    let mut long_term_gains = 0.0;
+                    for lot in select_lots_to_sell(&symbol_lots, shares.abs(), strategy, today) {
+                        let gains = (quote - lot.cost_basis) * lot.shares;
+                        if short_term_symbols.contains(&stock_symbol) && !lot.is_long_term(today) {
+                            short_term_gains += gains
+                        } else {
+                            long_term_gains += gains
+                        }
+                    }
+                    short_term_gains.max(0.0) * rates.short_term
+                        + long_term_gains.max(0.0) * rates.long_term
+                }
+                _ => 0.0,
+            };
+            order_lines.push(OrderLine {
+                label: HoldingLabel::Symbol(stock_symbol),
+                action,
+                shares: shares.abs(),
+                dollars: shares.abs() * quote,
+                est_tax,
+            });
+        }
+        // Other stock symbols share a single value, which is traded in dollars
+        let other_dollars = (self.sale_purchases_needed.other * quotes.other) as f32;
+        if other_dollars != 0.0 {
+            order_lines.push(OrderLine {
+                label: HoldingLabel::Other,
+                action: if other_dollars > 0.0 {
+                    OrderAction::Buy
+                } else {
+                    OrderAction::Sell
+                },
+                shares: self.sale_purchases_needed.other.abs() as f32,
+                dollars: other_dollars.abs(),
+                est_tax: 0.0,
+            });
+        }
+        order_lines
+    }
+
+    // Dollar value of the sales/purchases needed without the cash
+    fn traded_values(&self, quotes: ShareValues) -> ShareValues {
+        let mut traded = self.sale_purchases_needed * quotes;
+        traded.vmfxx = 0.0;
+        traded
+    }
+
+    /// expense_ratio_change returns the change in the blended expense ratio, in percent, after
+    /// rebalancing from the current holdings to the target holdings.  Negative values mean the
+    /// rebalance lowers the expense ratio.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VXUS, 10000.0).unwrap();
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VXUS, 5000.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::BND, 5000.0).unwrap();
+    /// let purchase_sales = target - current;
+    ///
+    /// let account = holdings::AccountHoldings::new(current, target, purchase_sales);
+    /// assert!(account.expense_ratio_change() < 0.0);
+    /// ```
+    pub fn expense_ratio_change(&self) -> f32 {
+        self.target.blended_expense_ratio() - self.current.blended_expense_ratio()
+    }
+
+    /// override_target sets the target value of a stock symbol and proportionally rescales the
+    /// targets of the other supported stock symbols so that the total target value stays the
+    /// same.  Cash and other holdings are not changed.  The sales/purchases needed are not updated
+    /// and need to be recalculated with the stock quotes.  Errors when an empty stock symbol is
+    /// passed, or when the value is negative or larger than the total target of the supported
+    /// stock symbols, which the other targets can not be rescaled to make up for.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset, holdings};
+    ///
+    /// let sub_allocations = asset::SubAllocations::new().unwrap();
+    /// let current = holdings::ShareValues::new();
+    /// let target = holdings::ShareValues::new_target(sub_allocations, 10000.0, 0.0, 0.0, 0.0, 0.0);
+    /// let mut account = holdings::AccountHoldings::new(current, target, target - current);
+    ///
+    /// account.override_target(holdings::StockSymbol::VV, 3000.0).unwrap();
+    /// assert_eq!(account.target.stock_value(holdings::StockSymbol::VV).unwrap(), 3000.0);
+    /// assert!((account.target.total_value() - 10000.0).abs() < 0.01);
+    /// assert!(account.target.stock_value(holdings::StockSymbol::VO).unwrap() < target.stock_value(holdings::StockSymbol::VO).unwrap());
+    ///
+    /// // The target can not be larger than the account or negative
+    /// assert!(account.override_target(holdings::StockSymbol::VV, 12000.0).is_err());
+    /// assert!(account.override_target(holdings::StockSymbol::VV, -100.0).is_err());
+    /// assert_eq!(account.target.stock_value(holdings::StockSymbol::VV).unwrap(), 3000.0);
+    /// ```
+    pub fn override_target(&mut self, symbol: StockSymbol, value: f32) -> Result<()> {
+        let mut symbols_total = 0.0;
+        let mut others_total = 0.0;
+        for stock_symbol in StockSymbol::list() {
+            let target_value = self.target.stock_value(stock_symbol.clone())?;
+            symbols_total += target_value;
+            if stock_symbol != symbol {
+                others_total += target_value;
+            }
+        }
+        ensure!(
+            (0.0..=symbols_total + 0.01).contains(&value),
+            "Target of {:?} needs to be between 0 and the account total of {:.2}: {:.2}",
+            symbol,
+            symbols_total,
+            value
+        );
+        self.target.add_stock_value(symbol.clone(), value)?;
+        if others_total == 0.0 {
+            return Ok(());
+        }
+        let scale = ((symbols_total - value) / others_total).max(0.0);
+        for stock_symbol in StockSymbol::list() {
+            if stock_symbol != symbol {
+                let target_value = self.target.stock_value(stock_symbol.clone())?;
+                self.target
+                    .add_stock_value(stock_symbol, target_value * scale)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// apply_min_investment keeps the target of each stock symbol from being below the minimum
+    /// investment of the fund.  Targets at or above half of the minimum are bumped up to the
+    /// minimum while smaller targets are zeroed out, with the other targets rescaled as in
+    /// override_target.  The sales/purchases needed are then recalculated with the stock quotes.
+    /// Errors when a minimum that a target is bumped up to is larger than the account total.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use vapore_gui::holdings;
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let current = holdings::ShareValues::new();
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 9000.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::VO, 1000.0).unwrap();
+    ///
+    /// let mut account = holdings::AccountHoldings::new(current, target, target / quotes);
+    /// let min_investment = HashMap::from([(holdings::StockSymbol::VO, 1500.0)]);
+    /// account.apply_min_investment(&min_investment, quotes).unwrap();
+    /// assert_eq!(account.target.stock_value(holdings::StockSymbol::VO).unwrap(), 1500.0);
+    /// assert_eq!(account.target.stock_value(holdings::StockSymbol::VV).unwrap(), 8500.0);
+    /// assert_eq!(account.sale_purchases_needed.stock_value(holdings::StockSymbol::VO).unwrap(), 1500.0);
+    ///
+    /// let mut account = holdings::AccountHoldings::new(current, target, target / quotes);
+    /// let min_investment = HashMap::from([(holdings::StockSymbol::VO, 3000.0)]);
+    /// account.apply_min_investment(&min_investment, quotes).unwrap();
+    /// assert_eq!(account.target.stock_value(holdings::StockSymbol::VO).unwrap(), 0.0);
+    /// assert_eq!(account.target.stock_value(holdings::StockSymbol::VV).unwrap(), 10000.0);
+    /// ```
+    pub fn apply_min_investment(
+        &mut self,
+        min_investment: &HashMap<StockSymbol, f32>,
+        quotes: ShareValues,
+    ) -> Result<()> {
+        for stock_symbol in StockSymbol::list() {
+            if let Some(minimum) = min_investment.get(&stock_symbol) {
+                let target_value = self.target.stock_value(stock_symbol.clone())?;
+                if target_value > 0.0 && target_value < *minimum {
+                    let new_value = if target_value >= minimum / 2.0 {
+                        *minimum
+                    } else {
+                        0.0
+                    };
+                    self.override_target(stock_symbol, new_value)?;
+                }
+            }
+        }
+        self.sale_purchases_needed = (self.target - self.current) / quotes;
+        Ok(())
+    }
+
+    /// apply_max_pct keeps the target of each stock symbol from exceeding its cap, which is a
+    /// percent of the total target value.  The excess over the cap is redistributed to the other
+    /// stock symbols that are below their caps, proportional to their targets.  The
+    /// sales/purchases needed are then recalculated with the stock quotes.
+    ///
+    /// # Errors
+    ///
+    /// Errors when there are no stock symbols below their caps to take the excess.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use vapore_gui::holdings;
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let current = holdings::ShareValues::new();
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 6000.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::VO, 3000.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::VB, 1000.0).unwrap();
+    ///
+    /// // Never more than 30% in VV and the excess moves to VO and VB proportionally
+    /// let mut account = holdings::AccountHoldings::new(current, target, target / quotes);
+    /// let max_pct = HashMap::from([(holdings::StockSymbol::VV, 30.0)]);
+    /// account.apply_max_pct(&max_pct, quotes).unwrap();
+    /// assert_eq!(account.target.stock_value(holdings::StockSymbol::VV).unwrap(), 3000.0);
+    /// assert_eq!(account.target.stock_value(holdings::StockSymbol::VO).unwrap(), 5250.0);
+    /// assert_eq!(account.target.stock_value(holdings::StockSymbol::VB).unwrap(), 1750.0);
+    /// assert_eq!(account.sale_purchases_needed.stock_value(holdings::StockSymbol::VO).unwrap(), 5250.0);
+    ///
+    /// // VO is pushed over its cap by the VV excess, which then moves to VB
+    /// let mut account = holdings::AccountHoldings::new(current, target, target / quotes);
+    /// let max_pct = HashMap::from([(holdings::StockSymbol::VV, 30.0), (holdings::StockSymbol::VO, 40.0)]);
+    /// account.apply_max_pct(&max_pct, quotes).unwrap();
+    /// assert_eq!(account.target.stock_value(holdings::StockSymbol::VO).unwrap(), 4000.0);
+    /// assert_eq!(account.target.stock_value(holdings::StockSymbol::VB).unwrap(), 3000.0);
+    ///
+    /// let mut account = holdings::AccountHoldings::new(current, target, target / quotes);
+    /// let max_pct = HashMap::from(holdings::StockSymbol::list().map(|stock_symbol| (stock_symbol, 20.0)));
+    /// assert!(account.apply_max_pct(&max_pct, quotes).is_err());
+    /// ```
+    pub fn apply_max_pct(
+        &mut self,
+        max_pct: &HashMap<StockSymbol, f32>,
+        quotes: ShareValues,
+    ) -> Result<()> {
+        let total_value = self.target.total_value();
+        let cap = |stock_symbol: &StockSymbol| {
+            max_pct
+                .get(stock_symbol)
+                .map(|pct| total_value * pct / 100.0)
+        };
+        loop {
+            let mut excess = 0.0;
+            for stock_symbol in StockSymbol::list() {
+                if let Some(cap) = cap(&stock_symbol) {
+                    let target_value = self.target.stock_value(stock_symbol.clone())?;
+                    if target_value > cap {
+                        excess += target_value - cap;
+                        self.target.add_stock_value(stock_symbol, cap)?;
+                    }
+                }
+            }
+            if excess <= 0.0 {
+                break;
+            }
+            let mut receivers = Vec::new();
+            let mut receivers_total = 0.0;
+            for stock_symbol in StockSymbol::list() {
+                let target_value = self.target.stock_value(stock_symbol.clone())?;
+                let below_cap = cap(&stock_symbol).map_or(true, |cap| target_value < cap);
+                if stock_symbol.is_stock() && below_cap && target_value > 0.0 {
+                    receivers_total += target_value;
+                    receivers.push((stock_symbol, target_value));
+                }
+            }
+            ensure!(
+                receivers_total > 0.0,
+                "No stock symbols are below their caps to take the excess of {}",
+                excess
+            );
+            for (stock_symbol, target_value) in receivers {
+                self.target.add_stock_value(
+                    stock_symbol,
+                    target_value + excess * target_value / receivers_total,
+                )?;
+            }
         }
+        self.sale_purchases_needed = (self.target - self.current) / quotes;
         Ok(())
     }
-}
-
-impl Default for VanguardHoldings {
-    fn default() -> Self {
-        VanguardHoldings::new(ShareValues::new_quote())
-    }
-}
-
-/// AccountHoldings is a holder of current, target, and purchase/sales information for an account.
-/// It also creates a Display for this information.
-#[derive(Debug)]
-pub struct AccountHoldings {
-    pub current: ShareValues,
-    pub target: ShareValues,
-    pub sale_purchases_needed: ShareValues,
-}
 
-impl AccountHoldings {
-    /// new creates a new AccountHoldings struct from current, target, and sales/purchases
-    /// Sharevalues structs.
+    /// round_to_cash_increment returns the dollar amount to buy, or sell when negative, of each
+    /// supported stock symbol rounded to the nearest cash increment.  This is for accounts which
+    /// trade in dollar amounts rather than shares, e.g. whole dollars with an increment of 1.
     ///
     /// # Example
     ///
     /// ```
-    /// use vapore::{asset, holdings};
+    /// use vapore_gui::holdings;
     ///
-    /// let sub_allocations = asset::SubAllocations::new().unwrap();
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VV, 1000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::BND, 1000.0).unwrap();
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 1234.56).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::BND, 765.44).unwrap();
+    /// let account = holdings::AccountHoldings::new(current, target, target - current);
     ///
-    /// let quotes = holdings::ShareValues::new_quote();
+    /// let trades = account.round_to_cash_increment(1.0);
+    /// assert_eq!(trades.stock_value(holdings::StockSymbol::VV).unwrap(), 235.0);
+    /// assert_eq!(trades.stock_value(holdings::StockSymbol::BND).unwrap(), -235.0);
+    /// let trades = account.round_to_cash_increment(50.0);
+    /// assert_eq!(trades.stock_value(holdings::StockSymbol::VV).unwrap(), 250.0);
+    /// ```
+    pub fn round_to_cash_increment(&self, increment: f32) -> ShareValues {
+        let difference = self.target - self.current;
+        let mut trades = ShareValues::new();
+        for stock_symbol in StockSymbol::list() {
+            let trade = difference.stock_value(stock_symbol.clone()).unwrap_or(0.0);
+            if let Ok(value) = trades.value_mut(stock_symbol) {
+                *value = if increment > 0.0 {
+                    ((trade / increment).round() * increment) as f64
+                } else {
+                    trade as f64
+                };
+            }
+        }
+        trades
+    }
+
+    /// cash_shortfall returns how much additional cash is needed to make all of the purchases
+    /// after using the current cash above the cash target and the money from all of the sales.
+    /// Negative values mean there is a cash surplus.
     ///
-    /// let brokerage_current = holdings::ShareValues::new();
-    /// let brokerage_target = holdings::ShareValues::new_target(sub_allocations, 10000.0, 0.0, 0.0, 0.0, 0.0);
-    /// let purchase_sales = brokerage_current / quotes;
+    /// # Example
     ///
-    /// let brokerage_account = holdings::AccountHoldings::new(brokerage_current, brokerage_target, purchase_sales);
     /// ```
-    pub fn new(
-        current: ShareValues,
-        target: ShareValues,
-        sale_purchases_needed: ShareValues,
-    ) -> Self {
-        AccountHoldings {
-            current,
-            target,
-            sale_purchases_needed,
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VV, 1000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::VMFXX, 200.0).unwrap();
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 500.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::BND, 1500.0).unwrap();
+    ///
+    /// let account = holdings::AccountHoldings::new(current, target, target - current);
+    /// assert_eq!(account.cash_shortfall(), 800.0);
+    /// ```
+    pub fn cash_shortfall(&self) -> f32 {
+        let mut purchases = 0.0;
+        let mut sales = 0.0;
+        for stock_symbol in StockSymbol::list()
+            .into_iter()
+            .chain([StockSymbol::Other(String::default())])
+        {
+            let difference = self.target.stock_value(stock_symbol.clone()).unwrap_or(0.0)
+                - self.current.stock_value(stock_symbol).unwrap_or(0.0);
+            if difference > 0.0 {
+                purchases += difference;
+            } else {
+                sales -= difference;
+            }
         }
+        let cash_available = self.current.stock_value(StockSymbol::VMFXX).unwrap_or(0.0)
+            - self.target.stock_value(StockSymbol::VMFXX).unwrap_or(0.0);
+        purchases - sales - cash_available
     }
 }
 
@@ -1129,6 +4843,8 @@ impl Default for AccountHoldings {
             current: ShareValues::new(),
             target: ShareValues::new(),
             sale_purchases_needed: ShareValues::new(),
+            wash_sale_warnings: Vec::new(),
+            placement_note: String::new(),
         }
     }
 }
@@ -1208,15 +4924,106 @@ impl fmt::Display for AccountHoldings {
     }
 }
 
+/// AccountTaxRates holds the rates used to convert account values into after-tax values.  The
+/// traditional rate is the expected income tax rate on traditional IRA withdrawals and the
+/// brokerage embedded gains rate is the fraction of the brokerage value expected to be lost to tax
+/// on the unrealized gains.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AccountTaxRates {
+    pub traditional: f32,
+    pub brokerage_embedded_gains: f32,
+}
+
+impl AccountTaxRates {
+    /// new creates a new AccountTaxRates struct.  Returns an error when either rate is not
+    /// between 0 and 1.
+    pub fn new(traditional: f32, brokerage_embedded_gains: f32) -> Result<Self> {
+        ensure!(
+            (0.0..=1.0).contains(&traditional) && (0.0..=1.0).contains(&brokerage_embedded_gains),
+            "Tax rates need to be between 0 and 1.  Traditional: {}, brokerage embedded gains: {}",
+            traditional,
+            brokerage_embedded_gains
+        );
+        Ok(AccountTaxRates {
+            traditional,
+            brokerage_embedded_gains,
+        })
+    }
+}
+
+/// CapitalGainsRates holds the capital gains tax rates used to estimate the tax on sales within a
+/// taxable account.  Gains on lots held for a year or less are taxed at the short term rate and
+/// the rest at the long term rate.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CapitalGainsRates {
+    pub short_term: f32,
+    pub long_term: f32,
+}
+
+impl CapitalGainsRates {
+    /// new creates a new CapitalGainsRates struct.  Returns an error when either rate is not
+    /// between 0 and 1.
+    pub fn new(short_term: f32, long_term: f32) -> Result<Self> {
+        ensure!(
+            (0.0..=1.0).contains(&short_term) && (0.0..=1.0).contains(&long_term),
+            "Tax rates need to be between 0 and 1.  Short term: {}, long term: {}",
+            short_term,
+            long_term
+        );
+        Ok(CapitalGainsRates {
+            short_term,
+            long_term,
+        })
+    }
+}
+
+/// CashPercents holds the percent of each account, from 0 to 100, which is kept within cash
+/// instead of being invested, such as an emergency reserve within the brokerage.  The default
+/// keeps no cash within any of the accounts.
+#[derive(Clone, Copy, PartialEq, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct CashPercents {
+    pub brokerage: f32,
+    pub traditional_ira: f32,
+    pub roth_ira: f32,
+    pub hsa: f32,
+}
+
+impl CashPercents {
+    /// new creates a new CashPercents struct.  Returns an error when any percent is not between 0
+    /// and 100.
+    pub fn new(brokerage: f32, traditional_ira: f32, roth_ira: f32, hsa: f32) -> Result<Self> {
+        ensure!(
+            [brokerage, traditional_ira, roth_ira, hsa]
+                .iter()
+                .all(|percent| (0.0..=100.0).contains(percent)),
+            "Cash percents need to be between 0 and 100.  Brokerage: {}, traditional IRA: {}, \
+             roth IRA: {}, HSA: {}",
+            brokerage,
+            traditional_ira,
+            roth_ira,
+            hsa
+        );
+        Ok(CashPercents {
+            brokerage,
+            traditional_ira,
+            roth_ira,
+            hsa,
+        })
+    }
+}
+
 /// VanguardRebalance holds AccountHoldings structs for each account; brokerage, traditional IRA,
-/// and roth IRA.  Each AccountHoldings struct holds the information of current holdings, target
-/// holdings, and the amount of stocks needed to purchase/sell in order to rebalance
+/// roth IRA, and HSA.  Each AccountHoldings struct holds the information of current holdings,
+/// target holdings, and the amount of stocks needed to purchase/sell in order to rebalance
 #[derive(Debug)]
 pub struct VanguardRebalance {
     pub brokerage: AccountHoldings,
     pub traditional_ira: AccountHoldings,
     pub roth_ira: AccountHoldings,
+    pub hsa: AccountHoldings,
     retirement_target: ShareValues,
+    brokerage_in_retirement: bool, // Whether the retirement target includes the brokerage
+    allocation_used: Option<(Allocations, SubAllocations)>,
 }
 
 impl VanguardRebalance {
@@ -1225,7 +5032,7 @@ impl VanguardRebalance {
     /// # Example
     ///
     /// ```
-    /// use vapore::holdings;
+    /// use vapore_gui::holdings;
     ///
     /// let vanguard_rebalance = holdings::VanguardRebalance::new();
     /// ```
@@ -1234,65 +5041,867 @@ impl VanguardRebalance {
             brokerage: AccountHoldings::default(),
             traditional_ira: AccountHoldings::default(),
             roth_ira: AccountHoldings::default(),
+            hsa: AccountHoldings::default(),
             retirement_target: ShareValues::default(),
+            brokerage_in_retirement: false,
+            allocation_used: None,
+        }
+    }
+
+    /// add_account_holdings adds either roth IRA, traditional IRA, HSA, or brokerage
+    /// AccountHoldings struct to the current VanguardRebalance struct.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset, holdings};
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    ///
+    /// let sub_allocations = asset::SubAllocations::new().unwrap();
+    ///
+    /// let brokerage_current = holdings::ShareValues::new();
+    /// let brokerage_target = holdings::ShareValues::new_target(sub_allocations, 10000.0, 0.0, 0.0, 0.0, 0.0);
+    /// let purchase_sales = brokerage_current / quotes;
+    ///
+    /// let brokerage_account = holdings::AccountHoldings::new(brokerage_current, brokerage_target, purchase_sales);
+    ///
+    /// let mut vanguard_rebalance = holdings::VanguardRebalance::new();
+    /// vanguard_rebalance.add_account_holdings(brokerage_account, holdings::HoldingType::Brokerage);
+    /// ```
+    pub fn add_account_holdings(&mut self, acct_holding: AccountHoldings, acct_type: HoldingType) {
+        match acct_type {
+            HoldingType::Brokerage => self.brokerage = acct_holding,
+            HoldingType::TraditionalIra => self.traditional_ira = acct_holding,
+            HoldingType::RothIra => self.roth_ira = acct_holding,
+            HoldingType::Hsa => self.hsa = acct_holding,
+        }
+    }
+
+    pub fn add_retirement_target(&mut self, retirement_target: ShareValues) {
+        self.retirement_target = retirement_target;
+    }
+
+    /// add_brokerage_in_retirement records whether the brokerage shares the retirement allocation
+    /// and is included within the retirement target
+    pub fn add_brokerage_in_retirement(&mut self, brokerage_in_retirement: bool) {
+        self.brokerage_in_retirement = brokerage_in_retirement;
+    }
+
+    /// retirement_target returns the household target across the retirement accounts, which can
+    /// be locked with the household_target of calc::RebalanceOptions
+    pub fn retirement_target(&self) -> ShareValues {
+        self.retirement_target
+    }
+
+    pub fn add_allocation_used(
+        &mut self,
+        allocation_used: Allocations,
+        sub_allocations: SubAllocations,
+    ) {
+        self.allocation_used = Some((allocation_used, sub_allocations));
+    }
+
+    /// allocation_used returns the stock, bond, and inflation protected allocation that the
+    /// retirement glide path chose for the retirement year, along with the sub allocations of each
+    /// asset class computed from it.  None is returned when there are no retirement accounts within
+    /// the rebalance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset, calc, holdings};
+    ///
+    /// let mut traditional = holdings::ShareValues::new();
+    /// traditional.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+    ///
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2050,
+    ///     traditional_ira: calc::AccountInputs::new(traditional),
+    ///     ..Default::default()
+    /// };
+    /// let rebalance = calc::to_buy(&options, holdings::ShareValues::new_quote()).unwrap();
+    ///
+    /// let (allocation_used, sub_allocations) = rebalance.allocation_used().unwrap();
+    /// assert_eq!(allocation_used, asset::Allocations::retirement(2050).unwrap());
+    /// assert_eq!(sub_allocations, asset::SubAllocations::new_custom(allocation_used).unwrap());
+    /// let (stock_percent, _, _) = rebalance.retirement_target().percent_stock_bond_infl();
+    /// assert!((allocation_used.total_stock() - stock_percent).abs() < 0.01);
+    /// let vv_value = rebalance.retirement_target().stock_value(holdings::StockSymbol::VV).unwrap();
+    /// assert!((vv_value - sub_allocations.us_stock_large * 100.0).abs() < 0.01);
+    /// assert_eq!(holdings::VanguardRebalance::new().allocation_used(), None);
+    /// ```
+    pub fn allocation_used(&self) -> Option<(Allocations, SubAllocations)> {
+        self.allocation_used
+    }
+
+    /// tax_adjusted_values returns the rebalance with the current and target values of each account
+    /// converted into after-tax values for asset location decisions.  The traditional IRA is
+    /// scaled by 1 minus the traditional rate, the roth IRA and HSA are kept as is, and the
+    /// brokerage is scaled by 1 minus the embedded gains rate.  The purchase/sell shares are not
+    /// dollar values and are kept as is.  The retirement target becomes the sum of the adjusted
+    /// targets of the retirement accounts, which includes the brokerage when it shares the
+    /// retirement allocation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{calc, holdings};
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VV, 10000.0).unwrap();
+    ///
+    /// let mut vanguard_rebalance = holdings::VanguardRebalance::new();
+    /// for holding_type in [
+    ///     holdings::HoldingType::TraditionalIra,
+    ///     holdings::HoldingType::RothIra,
+    ///     holdings::HoldingType::Brokerage,
+    /// ] {
+    ///     let account = holdings::AccountHoldings::new(current, current, current / quotes);
+    ///     vanguard_rebalance.add_account_holdings(account, holding_type);
+    /// }
+    ///
+    /// let rates = holdings::AccountTaxRates::new(0.25, 0.1).unwrap();
+    /// let after_tax = vanguard_rebalance.tax_adjusted_values(rates);
+    ///
+    /// assert_eq!(after_tax.traditional_ira.current.total_value(), 7500.0);
+    /// assert_eq!(after_tax.roth_ira.current.total_value(), 10000.0);
+    /// assert_eq!(after_tax.brokerage.target.total_value(), 9000.0);
+    /// assert_eq!(after_tax.retirement_target().total_value(), 17500.0);
+    /// let shares = after_tax.traditional_ira.sale_purchases_needed;
+    /// assert_eq!(shares.stock_value(holdings::StockSymbol::VV).unwrap(), 10000.0);
+    ///
+    /// // A brokerage sharing the retirement allocation is within the retirement target
+    /// let mut roth = holdings::ShareValues::new();
+    /// roth.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2050,
+    ///     roth_ira: calc::AccountInputs::new(roth),
+    ///     brokerage: calc::AccountInputs::new(current),
+    ///     use_brokerage_retirement: true,
+    ///     ..Default::default()
+    /// };
+    /// let rebalance = calc::to_buy(&options, quotes).unwrap();
+    /// let after_tax = rebalance.tax_adjusted_values(rates);
+    /// assert!((after_tax.retirement_target().total_value() - 19000.0).abs() < 0.01);
+    /// ```
+    pub fn tax_adjusted_values(&self, rates: AccountTaxRates) -> VanguardRebalance {
+        let traditional_ira = self.traditional_ira.scaled(1.0 - rates.traditional as f64);
+        let roth_ira = self.roth_ira.scaled(1.0);
+        let hsa = self.hsa.scaled(1.0);
+        let brokerage = self
+            .brokerage
+            .scaled(1.0 - rates.brokerage_embedded_gains as f64);
+        let mut retirement_target = traditional_ira.target + roth_ira.target + hsa.target;
+        if self.brokerage_in_retirement {
+            retirement_target = retirement_target + brokerage.target;
+        }
+        VanguardRebalance {
+            brokerage,
+            traditional_ira,
+            roth_ira,
+            hsa,
+            retirement_target,
+            brokerage_in_retirement: self.brokerage_in_retirement,
+            allocation_used: self.allocation_used,
+        }
+    }
+
+    /// placement_feasibility returns the minimum number of accounts needed to hold all of the
+    /// invested target assets, the targets outside of cash, while keeping to the risk placement.
+    /// The accounts are taken largest first, and the risk placement holds the bonds outside of the
+    /// roth IRA and HSA as long as the traditional IRA and brokerage have room for them, so that a
+    /// household with only a roth IRA can still hold bonds.  None is returned when the placement
+    /// is not feasible, which is when any account has a negative target or when the invested
+    /// targets are larger than all of the accounts combined.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let mut roth_current = holdings::ShareValues::new();
+    /// roth_current.add_stock_value(holdings::StockSymbol::VMFXX, 10000.0).unwrap();
+    /// let mut roth_target = holdings::ShareValues::new();
+    /// roth_target.add_stock_value(holdings::StockSymbol::VV, 9000.0).unwrap();
+    /// roth_target.add_stock_value(holdings::StockSymbol::BND, 1000.0).unwrap();
+    /// let roth_account =
+    ///     holdings::AccountHoldings::new(roth_current, roth_target, (roth_target - roth_current) / quotes);
+    ///
+    /// // A single roth IRA holds the bonds since there is no other account for them
+    /// let mut vanguard_rebalance = holdings::VanguardRebalance::new();
+    /// vanguard_rebalance.add_account_holdings(roth_account, holdings::HoldingType::RothIra);
+    /// assert_eq!(vanguard_rebalance.placement_feasibility(), Some(1));
+    ///
+    /// // The stock fills the roth IRA and the bonds need the traditional IRA, while the cash only
+    /// // brokerage is not needed
+    /// roth_target.add_stock_value(holdings::StockSymbol::VV, 10000.0).unwrap();
+    /// roth_target.add_stock_value(holdings::StockSymbol::BND, 0.0).unwrap();
+    /// let mut traditional = holdings::ShareValues::new();
+    /// traditional.add_stock_value(holdings::StockSymbol::BND, 2000.0).unwrap();
+    /// let mut brokerage = holdings::ShareValues::new();
+    /// brokerage.add_stock_value(holdings::StockSymbol::VMFXX, 500.0).unwrap();
+    /// for (current, target, holding_type) in [
+    ///     (roth_current, roth_target, holdings::HoldingType::RothIra),
+    ///     (traditional, traditional, holdings::HoldingType::TraditionalIra),
+    ///     (brokerage, brokerage, holdings::HoldingType::Brokerage),
+    /// ] {
+    ///     let account = holdings::AccountHoldings::new(current, target, (target - current) / quotes);
+    ///     vanguard_rebalance.add_account_holdings(account, holding_type);
+    /// }
+    /// assert_eq!(vanguard_rebalance.placement_feasibility(), Some(2));
+    ///
+    /// // A negative target can not be held within any account
+    /// roth_target.add_stock_value(holdings::StockSymbol::BND, -1000.0).unwrap();
+    /// let roth_account =
+    ///     holdings::AccountHoldings::new(roth_current, roth_target, (roth_target - roth_current) / quotes);
+    /// vanguard_rebalance.add_account_holdings(roth_account, holdings::HoldingType::RothIra);
+    /// assert_eq!(vanguard_rebalance.placement_feasibility(), None);
+    /// ```
+    pub fn placement_feasibility(&self) -> Option<usize> {
+        let accounts = [
+            (&self.brokerage, false),
+            (&self.traditional_ira, false),
+            (&self.roth_ira, true),
+            (&self.hsa, true),
+        ];
+        let mut stock_needed = 0.0;
+        let mut bonds_needed = 0.0;
+        for (account, _) in accounts {
+            for stock_symbol in StockSymbol::list() {
+                let value = account
+                    .target
+                    .stock_value(stock_symbol.clone())
+                    .unwrap_or(0.0);
+                if value < -0.01 {
+                    return None;
+                }
+                if stock_symbol.is_bond() {
+                    bonds_needed += value;
+                } else {
+                    stock_needed += value;
+                }
+            }
+        }
+        // The bonds are held within the roth IRA and HSA only after the other accounts are full
+        let bond_room: f32 = accounts
+            .iter()
+            .filter(|(_, tax_free)| !tax_free)
+            .map(|(account, _)| account.current.total_value())
+            .sum();
+        let placed_bonds = bonds_needed.min(bond_room);
+        if stock_needed + bonds_needed < 0.01 {
+            return Some(0);
+        }
+        let mut sizes = accounts
+            .iter()
+            .map(|(account, tax_free)| (account.current.total_value(), *tax_free))
+            .filter(|(total, _)| *total > 0.0)
+            .collect::<Vec<(f32, bool)>>();
+        sizes.sort_by(|(total_a, _), (total_b, _)| total_b.total_cmp(total_a));
+        let mut held = 0.0;
+        let mut held_bond_room = 0.0;
+        for (count, (total, tax_free)) in sizes.into_iter().enumerate() {
+            held += total;
+            if !tax_free {
+                held_bond_room += total;
+            }
+            if held > stock_needed + bonds_needed - 0.01 && held_bond_room > placed_bonds - 0.01 {
+                return Some(count + 1);
+            }
+        }
+        None
+    }
+
+    /// ftc_impact estimates the change in the value of the foreign tax credit eligible holdings,
+    /// which are the international stock funds within the taxable brokerage, caused by the
+    /// brokerage trades.  A negative value means selling international stock within the brokerage
+    /// reduces the foreign tax credit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VXUS, 6000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::VWO, 2000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::BNDX, 2000.0).unwrap();
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VXUS, 4000.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::VWO, 1500.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 4500.0).unwrap();
+    ///
+    /// let mut rebalance = holdings::VanguardRebalance::new();
+    /// rebalance.add_account_holdings(
+    ///     holdings::AccountHoldings::new(current, target, target - current),
+    ///     holdings::HoldingType::Brokerage,
+    /// );
+    /// assert_eq!(rebalance.ftc_impact(), -2500.0);
+    /// ```
+    pub fn ftc_impact(&self) -> f32 {
+        let traded = self.brokerage.target - self.brokerage.current;
+        [StockSymbol::VXUS, StockSymbol::VWO]
+            .into_iter()
+            .map(|stock_symbol| traded.stock_value(stock_symbol).unwrap_or(0.0))
+            .sum()
+    }
+
+    /// placement_efficiency scores how much of the ideal risk placement was achieved, where the
+    /// roth IRA and HSA are filled with the household stock assets so that the largest growth is
+    /// not taxed.  Returns the roth IRA and HSA target stock value as a fraction, from 0 to 1, of
+    /// the stock they could hold, which is the smaller of their combined total and the retirement
+    /// target stock value.  A small roth IRA that only holds stock therefore scores 1.  Returns 1
+    /// when there are no stock assets to place or no roth IRA or HSA to place them within.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{calc, holdings};
+    ///
+    /// let mut roth = holdings::ShareValues::new();
+    /// roth.add_stock_value(holdings::StockSymbol::VMFXX, 1000.0).unwrap();
+    /// let mut traditional = holdings::ShareValues::new();
+    /// traditional.add_stock_value(holdings::StockSymbol::VMFXX, 9000.0).unwrap();
+    ///
+    /// let options = calc::RebalanceOptions {
+    ///     retirement_year: 2050,
+    ///     roth_ira: calc::AccountInputs::new(roth),
+    ///     traditional_ira: calc::AccountInputs::new(traditional),
+    ///     ..Default::default()
+    /// };
+    /// let rebalance = calc::to_buy(&options, holdings::ShareValues::new_quote()).unwrap();
+    ///
+    /// // The tiny roth IRA is filled with stock, which is the best placement possible
+    /// assert!((rebalance.placement_efficiency() - 1.0).abs() < 0.001);
+    /// assert_eq!(holdings::VanguardRebalance::new().placement_efficiency(), 1.0);
+    ///
+    /// // Half of the roth IRA holds bonds while the stock is held elsewhere
+    /// let mut roth_target = holdings::ShareValues::new();
+    /// roth_target.add_stock_value(holdings::StockSymbol::VV, 500.0).unwrap();
+    /// roth_target.add_stock_value(holdings::StockSymbol::BND, 500.0).unwrap();
+    /// let mut household_target = holdings::ShareValues::new();
+    /// household_target.add_stock_value(holdings::StockSymbol::VV, 6000.0).unwrap();
+    /// household_target.add_stock_value(holdings::StockSymbol::BND, 4000.0).unwrap();
+    /// let roth_account =
+    ///     holdings::AccountHoldings::new(roth_target, roth_target, holdings::ShareValues::new());
+    /// let mut rebalance = holdings::VanguardRebalance::new();
+    /// rebalance.add_account_holdings(roth_account, holdings::HoldingType::RothIra);
+    /// rebalance.add_retirement_target(household_target);
+    /// assert!((rebalance.placement_efficiency() - 0.5).abs() < 0.001);
+    /// ```
+    pub fn placement_efficiency(&self) -> f32 {
+        let stock_value = |values: &ShareValues| -> f32 {
+            StockSymbol::list()
+                .into_iter()
+                .filter(|stock_symbol| stock_symbol.is_stock())
+                .map(|stock_symbol| values.stock_value(stock_symbol).unwrap_or(0.0))
+                .sum()
+        };
+        let tax_free_total = self.roth_ira.target.total_value() + self.hsa.target.total_value();
+        let stock_room = stock_value(&self.retirement_target).min(tax_free_total);
+        if stock_room <= 0.0 {
+            return 1.0;
+        }
+        ((stock_value(&self.roth_ira.target) + stock_value(&self.hsa.target)) / stock_room).min(1.0)
+    }
+
+    /// to_plain_table creates a fixed width table of the purchase/sell, current, and target for
+    /// each account, which is suitable for terminal output.  Accounts without holdings or targets
+    /// are left out along with any symbol rows that are all zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VV, 1000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::VMFXX, 500.0).unwrap();
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 1500.0).unwrap();
+    /// let brokerage_account =
+    ///     holdings::AccountHoldings::new(current, target, (target - current) / quotes);
+    ///
+    /// let mut vanguard_rebalance = holdings::VanguardRebalance::new();
+    /// vanguard_rebalance.add_account_holdings(brokerage_account, holdings::HoldingType::Brokerage);
+    ///
+    /// let expected = "\
+    /// Account          Symbol   Purchase/Sell        Current         Target
+    /// ---------------------------------------------------------------------
+    /// Brokerage        VV              500.00        1000.00        1500.00
+    /// Brokerage        VMFXX                          500.00           0.00
+    /// ";
+    /// assert_eq!(vanguard_rebalance.to_plain_table(), expected);
+    /// ```
+    pub fn to_plain_table(&self) -> String {
+        let mut table = format!(
+            "{:<17}{:<6}{:>16}{:>15}{:>15}\n{}\n",
+            "Account",
+            "Symbol",
+            "Purchase/Sell",
+            "Current",
+            "Target",
+            "-".repeat(69)
+        );
+        for (account_name, account) in [
+            ("Traditional IRA", &self.traditional_ira),
+            ("Roth IRA", &self.roth_ira),
+            ("HSA", &self.hsa),
+            ("Brokerage", &self.brokerage),
+        ] {
+            if account.current.total_value() == 0.0 && account.target.total_value() == 0.0 {
+                continue;
+            }
+            for stock_symbol in StockSymbol::list() {
+                let purchase = account
+                    .sale_purchases_needed
+                    .stock_value(stock_symbol.clone())
+                    .unwrap_or(0.0);
+                let current = account
+                    .current
+                    .stock_value(stock_symbol.clone())
+                    .unwrap_or(0.0);
+                let target = account
+                    .target
+                    .stock_value(stock_symbol.clone())
+                    .unwrap_or(0.0);
+                if purchase != 0.0 || current != 0.0 || target != 0.0 {
+                    table.push_str(&format!(
+                        "{:<17}{:<6}{:>16.2}{:>15.2}{:>15.2}\n",
+                        account_name,
+                        format!("{:?}", stock_symbol),
+                        purchase,
+                        current,
+                        target
+                    ));
+                }
+            }
+            let current_cash = account
+                .current
+                .stock_value(StockSymbol::VMFXX)
+                .unwrap_or(0.0);
+            let target_cash = account
+                .target
+                .stock_value(StockSymbol::VMFXX)
+                .unwrap_or(0.0);
+            if current_cash != 0.0 || target_cash != 0.0 {
+                table.push_str(&format!(
+                    "{:<17}{:<6}{:>16}{:>15.2}{:>15.2}\n",
+                    account_name, "VMFXX", "", current_cash, target_cash
+                ));
+            }
+        }
+        table
+    }
+}
+
+impl Default for VanguardRebalance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for VanguardRebalance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out_string = String::new();
+        out_string.push_str(&format!(
+            "Retirement target:\n{}\n\n",
+            self.retirement_target
+        ));
+        out_string.push_str(&format!("Traditional IRA:\n{}\n\n", self.traditional_ira));
+        out_string.push_str(&format!("Roth IRA:\n{}\n\n", self.roth_ira));
+        if self.hsa.current.total_value() != 0.0 || self.hsa.target.total_value() != 0.0 {
+            out_string.push_str(&format!("HSA:\n{}\n\n", self.hsa));
+        }
+        out_string.push_str(&format!("Brokerage:\n{}\n\n", self.brokerage));
+        write!(f, "{}", out_string.trim_end_matches('\n'))
+    }
+}
+
+/// ReportRow holds the values of a single stock symbol within a ReportAccount.  Holdings only
+/// accounts leave the target and purchase/sell values at 0.
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct ReportRow {
+    pub symbol: String,
+    pub purchase: f32,
+    pub current: f32,
+    pub target: f32,
+}
+
+/// ReportAccount holds the rows and the current and target stock/bond/inflation protected
+/// percentages of an account within a Report
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct ReportAccount {
+    pub name: String,
+    pub rows: Vec<ReportRow>,
+    pub current_ratios: (f32, f32, f32),
+    pub target_ratios: (f32, f32, f32),
+}
+
+/// Report combines the parsed holdings of each account and the rebalance into a single
+/// serializable snapshot, which can be written out as a standalone HTML page for printing.
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Report {
+    pub holdings: Vec<ReportAccount>,
+    pub rebalance: Vec<ReportAccount>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl Report {
+    /// new creates a Report from the parsed holdings and the rebalance, generated at the current
+    /// time.  Accounts without any holdings or targets are left out, and the registered stock
+    /// symbols are reported by the asset class that they fill.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::{asset::AssetClass, holdings};
+    ///
+    /// let mut registry = holdings::SymbolRegistry::new();
+    /// let vti = registry.register_symbol("VTI", AssetClass::UsStockLarge).unwrap();
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(vti, 1000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::BND, 1000.0).unwrap();
+    /// let mut vanguard_holdings = holdings::VanguardHoldings::new(holdings::ShareValues::new_quote());
+    /// vanguard_holdings.accounts_values.insert(12345, current);
+    ///
+    /// let report = holdings::Report::new(&vanguard_holdings, &holdings::VanguardRebalance::new());
+    /// let symbols = report.holdings[0]
+    ///     .rows
+    ///     .iter()
+    ///     .map(|row| row.symbol.as_str())
+    ///     .collect::<Vec<&str>>();
+    /// assert_eq!(symbols, vec!["BND", "UsStockLarge"]);
+    /// assert_eq!(report.holdings[0].rows[1].current, 1000.0);
+    /// ```
+    pub fn new(vanguard_holdings: &VanguardHoldings, rebalance: &VanguardRebalance) -> Self {
+        let mut account_numbers = vanguard_holdings
+            .accounts_values
+            .keys()
+            .copied()
+            .collect::<Vec<u32>>();
+        account_numbers.sort();
+        let holdings = account_numbers
+            .into_iter()
+            .map(|account_number| {
+                let values = vanguard_holdings.accounts_values[&account_number];
+                ReportAccount {
+                    name: format!("Account {}", account_number),
+                    rows: report_rows(&values, &ShareValues::new(), &ShareValues::new()),
+                    current_ratios: report_ratios(&values),
+                    target_ratios: (0.0, 0.0, 0.0),
+                }
+            })
+            .filter(|account| !account.rows.is_empty())
+            .collect();
+        let rebalance = [
+            ("Traditional IRA", &rebalance.traditional_ira),
+            ("Roth IRA", &rebalance.roth_ira),
+            ("HSA", &rebalance.hsa),
+            ("Brokerage", &rebalance.brokerage),
+        ]
+        .into_iter()
+        .map(|(account_name, account)| ReportAccount {
+            name: account_name.to_string(),
+            rows: report_rows(
+                &account.current,
+                &account.target,
+                &account.sale_purchases_needed,
+            ),
+            current_ratios: report_ratios(&account.current),
+            target_ratios: report_ratios(&account.target),
+        })
+        .filter(|account| !account.rows.is_empty())
+        .collect();
+        Report {
+            holdings,
+            rebalance,
+            generated_at: Utc::now(),
+        }
+    }
+
+    /// to_html returns a standalone HTML page of the report with a table for each account along
+    /// with the stock/bond/inflation protected percentages.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vapore_gui::holdings;
+    ///
+    /// let quotes = holdings::ShareValues::new_quote();
+    /// let mut current = holdings::ShareValues::new();
+    /// current.add_stock_value(holdings::StockSymbol::VV, 1000.0).unwrap();
+    /// current.add_stock_value(holdings::StockSymbol::BND, 1000.0).unwrap();
+    /// let mut vanguard_holdings = holdings::VanguardHoldings::new(quotes);
+    /// vanguard_holdings.accounts_values.insert(12345, current);
+    ///
+    /// let mut target = holdings::ShareValues::new();
+    /// target.add_stock_value(holdings::StockSymbol::VV, 1200.0).unwrap();
+    /// target.add_stock_value(holdings::StockSymbol::BND, 800.0).unwrap();
+    /// let brokerage_account =
+    ///     holdings::AccountHoldings::new(current, target, (target - current) / quotes);
+    /// let mut vanguard_rebalance = holdings::VanguardRebalance::new();
+    /// vanguard_rebalance.add_account_holdings(brokerage_account, holdings::HoldingType::Brokerage);
+    ///
+    /// let report = holdings::Report::new(&vanguard_holdings, &vanguard_rebalance);
+    /// let html = report.to_html();
+    ///
+    /// assert!(html.starts_with("<!DOCTYPE html>"));
+    /// assert!(html.contains("<h3>Account 12345</h3>"));
+    /// assert!(html.contains("<h3>Brokerage</h3>"));
+    /// assert!(!html.contains("<h3>Roth IRA</h3>"));
+    /// assert!(html.contains("Target: 60.0% stock, 40.0% bond, 0.0% inflation protected"));
+    /// ```
+    pub fn to_html(&self) -> String {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Vapore report</title>\n\
+            <style>\n\
+            body {{ font-family: sans-serif; }}\n\
+            table {{ border-collapse: collapse; margin-bottom: 8px; }}\n\
+            th, td {{ border: 1px solid #999; padding: 4px 8px; text-align: right; }}\n\
+            th:first-child, td:first-child {{ text-align: left; }}\n\
+            </style>\n</head>\n<body>\n<h1>Vapore report</h1>\n<p>Generated {}</p>\n",
+            self.generated_at.format("%Y-%m-%d %H:%M UTC")
+        );
+        html.push_str("<h2>Holdings</h2>\n");
+        for account in &self.holdings {
+            html.push_str(&format!(
+                "<h3>{}</h3>\n<table>\n<tr><th>Symbol</th><th>Value</th></tr>\n",
+                escape_html(&account.name)
+            ));
+            for row in &account.rows {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{:.2}</td></tr>\n",
+                    escape_html(&row.symbol),
+                    row.current
+                ));
+            }
+            html.push_str("</table>\n");
+            html.push_str(&ratios_html("Current", account.current_ratios));
+        }
+        html.push_str("<h2>Rebalance</h2>\n");
+        for account in &self.rebalance {
+            html.push_str(&format!(
+                "<h3>{}</h3>\n<table>\n\
+                <tr><th>Symbol</th><th>Purchase/Sell</th><th>Current</th><th>Target</th></tr>\n",
+                escape_html(&account.name)
+            ));
+            for row in &account.rows {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td></tr>\n",
+                    escape_html(&row.symbol),
+                    row.purchase,
+                    row.current,
+                    row.target
+                ));
+            }
+            html.push_str("</table>\n");
+            html.push_str(&ratios_html("Current", account.current_ratios));
+            html.push_str(&ratios_html("Target", account.target_ratios));
         }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+// Creates the rows of a report account for each value that is held, targeted, or traded, with
+// the registered stock symbols labeled by their asset class as within pie_data
+fn report_rows(
+    current: &ShareValues,
+    target: &ShareValues,
+    purchase: &ShareValues,
+) -> Vec<ReportRow> {
+    let labeled_rows = current.iter().zip(target.iter()).zip(purchase.iter()).map(
+        |(((label, current), (_, target)), (_, purchase))| ReportRow {
+            symbol: label.to_string(),
+            purchase: purchase as f32,
+            current: current as f32,
+            target: target as f32,
+        },
+    );
+    let registered_rows = AssetClass::list().into_iter().map(|asset_class| ReportRow {
+        symbol: format!("{:?}", asset_class),
+        purchase: purchase.registered_value(asset_class) as f32,
+        current: current.registered_value(asset_class) as f32,
+        target: target.registered_value(asset_class) as f32,
+    });
+    labeled_rows
+        .chain(registered_rows)
+        .filter(|row| row.current != 0.0 || row.target != 0.0 || row.purchase != 0.0)
+        .collect()
+}
+
+// Stock/bond/inflation protected percentages, which are left at 0 for an account without value
+fn report_ratios(values: &ShareValues) -> (f32, f32, f32) {
+    let (stock, bond, inflation) = values.percent_stock_bond_infl();
+    if stock.is_nan() || bond.is_nan() || inflation.is_nan() {
+        (0.0, 0.0, 0.0)
+    } else {
+        (stock, bond, inflation)
     }
+}
+
+fn ratios_html(label: &str, (stock, bond, inflation): (f32, f32, f32)) -> String {
+    format!(
+        "<p>{}: {:.1}% stock, {:.1}% bond, {:.1}% inflation protected</p>\n",
+        label, stock, bond, inflation
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Lot holds the shares of a stock symbol bought at the same time, along with the cost basis per
+/// share and the date acquired, which are needed to know the tax owed when sold.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Lot {
+    pub symbol: StockSymbol,
+    pub shares: f32,
+    pub cost_basis: f32, // Cost basis per share
+    pub acquired: NaiveDate,
+}
 
-    /// add_account_holdings adds either roth IRA, traditional IRA, or brokerage AccountHoldings
-    /// struct to the current VanguardRebalance struct.
+impl Lot {
+    /// new creates a new Lot struct
     ///
     /// # Example
     ///
     /// ```
-    /// use vapore::{asset, holdings};
-    ///
-    /// let quotes = holdings::ShareValues::new_quote();
-    ///
-    /// let sub_allocations = asset::SubAllocations::new().unwrap();
-    ///
-    /// let brokerage_current = holdings::ShareValues::new();
-    /// let brokerage_target = holdings::ShareValues::new_target(sub_allocations, 10000.0, 0.0, 0.0, 0.0, 0.0);
-    /// let purchase_sales = brokerage_current / quotes;
+    /// use chrono::NaiveDate;
+    /// use vapore_gui::holdings;
     ///
-    /// let brokerage_account = holdings::AccountHoldings::new(brokerage_current, brokerage_target, purchase_sales);
+    /// let acquired = NaiveDate::from_ymd_opt(2022, 3, 1).unwrap();
+    /// let lot = holdings::Lot::new(holdings::StockSymbol::VV, 10.0, 180.0, acquired);
     ///
-    /// let mut vanguard_rebalance = holdings::VanguardRebalance::new();
-    /// vanguard_rebalance.add_account_holdings(brokerage_account, holdings::HoldingType::Brokerage);
+    /// assert!(lot.is_long_term(NaiveDate::from_ymd_opt(2023, 6, 1).unwrap()));
+    /// assert!(!lot.is_long_term(NaiveDate::from_ymd_opt(2022, 12, 1).unwrap()));
     /// ```
-    pub fn add_account_holdings(&mut self, acct_holding: AccountHoldings, acct_type: HoldingType) {
-        match acct_type {
-            HoldingType::Brokerage => self.brokerage = acct_holding,
-            HoldingType::TraditionalIra => self.traditional_ira = acct_holding,
-            HoldingType::RothIra => self.roth_ira = acct_holding,
+    pub fn new(symbol: StockSymbol, shares: f32, cost_basis: f32, acquired: NaiveDate) -> Self {
+        Lot {
+            symbol,
+            shares,
+            cost_basis,
+            acquired,
         }
     }
 
-    pub fn add_retirement_target(&mut self, retirement_target: ShareValues) {
-        self.retirement_target = retirement_target;
+    /// is_long_term returns whether the lot has been held for more than a year on the date, which
+    /// means gains are taxed at the long term capital gains rate
+    pub fn is_long_term(&self, date: NaiveDate) -> bool {
+        date - self.acquired > Duration::days(365)
     }
 }
 
-impl Default for VanguardRebalance {
-    fn default() -> Self {
-        Self::new()
+/// LotStrategy is the order in which lots are sold.  HighestBasis sells the highest cost basis
+/// lots first to minimize the gains taxed, while LongTermFirst sells the long term lots first so
+/// that gains are taxed at the lower long term rate.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LotStrategy {
+    HighestBasis,
+    LongTermFirst,
+}
+
+/// RebalanceFrequency is how often the portfolio is rebalanced on a calendar schedule, which is
+/// used to determine when the next rebalance is due.
+#[derive(Clone, Copy, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum RebalanceFrequency {
+    Monthly,
+    Quarterly,
+    SemiAnnually,
+    Annually,
+}
+
+impl RebalanceFrequency {
+    /// months returns the number of months between each rebalance
+    pub fn months(&self) -> u32 {
+        match self {
+            RebalanceFrequency::Monthly => 1,
+            RebalanceFrequency::Quarterly => 3,
+            RebalanceFrequency::SemiAnnually => 6,
+            RebalanceFrequency::Annually => 12,
+        }
     }
 }
 
-impl fmt::Display for VanguardRebalance {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut out_string = String::new();
-        out_string.push_str(&format!(
-            "Retirement target:\n{}\n\n",
-            self.retirement_target
-        ));
-        out_string.push_str(&format!("Traditional IRA:\n{}\n\n", self.traditional_ira));
-        out_string.push_str(&format!("Roth IRA:\n{}\n\n", self.roth_ira));
-        out_string.push_str(&format!("Brokerage:\n{}\n\n", self.brokerage));
-        write!(f, "{}", out_string.trim_end_matches('\n'))
+/// RoundingMode is how the shares to buy and sell are rounded when rebalancing.  Fractional keeps
+/// the fractional shares, WholeShares rounds to the nearest whole share, and
+/// WholeSharesConservative truncates both buys and sells towards zero shares.  The difference from
+/// the rounding is kept within cash, and when rounding the buys up would spend more than the cash
+/// available the largest buys are cut back by whole shares.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::{calc, holdings};
+///
+/// let mut quotes = holdings::ShareValues::new_quote();
+/// for stock_symbol in holdings::StockSymbol::list() {
+///     quotes.add_stock_value(stock_symbol, 97.0).unwrap();
+/// }
+/// let mut brokerage = holdings::ShareValues::new();
+/// brokerage.add_stock_value(holdings::StockSymbol::VMFXX, 1100.0).unwrap();
+///
+/// let rebalance_with = |rounding_mode| {
+///     let options = calc::RebalanceOptions {
+///         retirement_year: 2070,
+///         brokerage: calc::AccountInputs::new(brokerage),
+///         rounding_mode,
+///         ..Default::default()
+///     };
+///     calc::to_buy(&options, quotes).unwrap().brokerage
+/// };
+/// let fractional = rebalance_with(holdings::RoundingMode::Fractional);
+/// let whole = rebalance_with(holdings::RoundingMode::WholeShares);
+/// let conservative = rebalance_with(holdings::RoundingMode::WholeSharesConservative);
+///
+/// for stock_symbol in holdings::StockSymbol::list() {
+///     let fractional_shares = fractional.sale_purchases_needed.stock_value(stock_symbol.clone()).unwrap();
+///     let whole_shares = whole.sale_purchases_needed.stock_value(stock_symbol.clone()).unwrap();
+///     let conservative_shares = conservative.sale_purchases_needed.stock_value(stock_symbol).unwrap();
+///     assert_eq!(whole_shares, whole_shares.round());
+///     assert!(whole_shares <= fractional_shares.round());
+///     assert_eq!(conservative_shares, fractional_shares.trunc());
+/// }
+/// // Rounding every buy up would overshoot the cash, so a buy is cut back by a whole share
+/// let rounded_up = holdings::StockSymbol::list()
+///     .into_iter()
+///     .map(|stock_symbol| fractional.sale_purchases_needed.stock_value(stock_symbol).unwrap().round() * 97.0)
+///     .sum::<f32>();
+/// assert!(rounded_up > 1100.0);
+/// assert!(whole.target.stock_value(holdings::StockSymbol::VMFXX).unwrap() >= 0.0);
+///
+/// // The rounding residual stays within cash so the totals still reconcile
+/// assert!((whole.target.total_value() - 1100.0).abs() < 0.1);
+/// assert!(conservative.target.stock_value(holdings::StockSymbol::VMFXX).unwrap() > 0.0);
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub enum RoundingMode {
+    #[default]
+    Fractional,
+    WholeShares,
+    WholeSharesConservative,
+}
+
+impl RoundingMode {
+    /// round rounds the shares to buy, when positive, or sell, when negative, with the rounding
+    /// mode.
+    pub fn round(&self, shares: f32) -> f32 {
+        match self {
+            RoundingMode::Fractional => shares,
+            RoundingMode::WholeShares => shares.round(),
+            RoundingMode::WholeSharesConservative => shares.trunc(),
+        }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
 pub struct Transaction {
     account_number: u32,
     trade_date: NaiveDate,
@@ -1302,8 +5911,78 @@ pub struct Transaction {
     transaction_type: TransactionType,
 }
 
-#[derive(Clone, Eq, Hash, PartialEq, Debug)]
-enum TransactionType {
+impl Transaction {
+    /// new creates a new Transaction struct.  Transactions are normally created when parsing the
+    /// transaction section of the CSV file downloaded from Vanguard.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use vapore_gui::holdings;
+    ///
+    /// let transaction = holdings::Transaction::new(
+    ///     123456789,
+    ///     NaiveDate::from_ymd_opt(2023, 6, 1).unwrap(),
+    ///     holdings::StockSymbol::BND,
+    ///     10.0,
+    ///     -725.0,
+    ///     holdings::TransactionType::Buy,
+    /// );
+    /// ```
+    pub fn new(
+        account_number: u32,
+        trade_date: NaiveDate,
+        symbol: StockSymbol,
+        shares: f32,
+        net_amount: f32,
+        transaction_type: TransactionType,
+    ) -> Self {
+        Transaction {
+            account_number,
+            trade_date,
+            symbol,
+            shares,
+            net_amount,
+            transaction_type,
+        }
+    }
+
+    /// trade_date returns the date of the transaction
+    pub fn trade_date(&self) -> NaiveDate {
+        self.trade_date
+    }
+
+    /// cash_flow returns the net amount of transactions which move money into or out of the
+    /// account, where deposits are positive and withdrawals are negative.  None is returned for
+    /// transactions within the account, such as buys, sells, and dividends.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use vapore_gui::holdings::{StockSymbol, Transaction, TransactionType};
+    ///
+    /// let date = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+    /// let deposit = Transaction::new(1, date, StockSymbol::Empty, 0.0, 1000.0, TransactionType::Fundsreceived);
+    /// let buy = Transaction::new(1, date, StockSymbol::VV, 2.0, -450.0, TransactionType::Buy);
+    ///
+    /// assert_eq!(deposit.cash_flow(), Some(1000.0));
+    /// assert_eq!(buy.cash_flow(), None);
+    /// ```
+    pub fn cash_flow(&self) -> Option<f32> {
+        match self.transaction_type {
+            TransactionType::Fundsreceived
+            | TransactionType::Distribution
+            | TransactionType::ConversionIn
+            | TransactionType::ConversionOut => Some(self.net_amount),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Eq, Hash, PartialEq, Debug, serde::Deserialize, serde::Serialize)]
+pub enum TransactionType {
     ConversionOut,
     Dividend,
     Reinvestment,
@@ -1324,10 +6003,10 @@ impl TransactionType {
     ///  # Example
     ///
     ///  ```
-    ///  use vapore::holdings::TransactionType;
+    ///  use vapore_gui::holdings::TransactionType;
     ///
     ///  let div = TransactionType::new("Dividend");
-    ///  assert_eq!(div, TransactionType::DIVIDEND);
+    ///  assert_eq!(div, TransactionType::Dividend);
     ///  ```
     pub fn new(transaction_type: &str) -> Self {
         match transaction_type {
@@ -1347,125 +6026,834 @@ impl TransactionType {
     }
 }
 
+// Column headers which parse_csv_download needs within the holdings and transaction sections
+const HOLDINGS_HEADERS: [&str; 5] = [
+    "Account Number",
+    "Symbol",
+    "Shares",
+    "Share Price",
+    "Total Value",
+];
+const TRANSACTION_HEADERS: [&str; 6] = [
+    "Account Number",
+    "Trade Date",
+    "Symbol",
+    "Shares",
+    "Net Amount",
+    "Transaction Type",
+];
+
+/// ParseWarning describes a row of the Vanguard download, or an element of the OFX download, which
+/// was skipped while parsing, along with its line number within the file.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ParseWarning {
+    pub line: usize,
+    pub kind: ParseWarningKind,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match &self.kind {
+            ParseWarningKind::MalformedNumber(value) => format!("malformed number {:?}", value),
+            ParseWarningKind::UnparseableDate(value) => format!("unparseable date {:?}", value),
+            ParseWarningKind::MissingHeader(header) => {
+                format!("header row is missing the {:?} column", header)
+            }
+            ParseWarningKind::ShortRow => "row has fewer values than the header".to_string(),
+        };
+        write!(f, "Line {}: {}", self.line, reason)
+    }
+}
+
+/// ParseWarningKind is why a row of the Vanguard download was skipped.  MissingHeader is given for
+/// the header row when a needed column, such as a renamed column without an alias, is not found,
+/// which leaves the rows of that section unparsed.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ParseWarningKind {
+    MalformedNumber(String),
+    UnparseableDate(String),
+    MissingHeader(String),
+    ShortRow,
+}
+
+// Warnings for each of the needed column headers which are not within the header row
+fn missing_headers(header: &[String], needed: &[&str], line: usize) -> Vec<ParseWarning> {
+    needed
+        .iter()
+        .filter(|needed_header| !header.iter().any(|head| head == *needed_header))
+        .map(|needed_header| ParseWarning {
+            line,
+            kind: ParseWarningKind::MissingHeader(needed_header.to_string()),
+        })
+        .collect()
+}
+
+// Header which the parser matches for a column header of the Vanguard download, where renamed
+// columns are matched through their alias
+fn canonical_header(header: &str) -> &str {
+    let header = header.trim();
+    HEADER_ALIASES.get(header).copied().unwrap_or(header)
+}
+
+// Values of a row of the Vanguard download split on commas, where commas within double quoted
+// values, such as "Vanguard Total Bond Market, Admiral", are kept within the value.  The
+// surrounding quotes are removed and doubled quotes within a quoted value become a single quote.
+fn split_csv_row(row: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut value = String::new();
+    let mut in_quotes = false;
+    let mut chars = row.chars().peekable();
+    while let Some(character) = chars.next() {
+        match character {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                value.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => values.push(std::mem::take(&mut value)),
+            _ => value.push(character),
+        }
+    }
+    values.push(value);
+    values
+}
+
+// StockInfo from a row of the holdings section of the Vanguard download.  Rows without a symbol
+// return an unfinished StockInfo, which is skipped.
+fn parse_holdings_row(
+    row_split: &[String],
+    header: &[String],
+    registry: &SymbolRegistry,
+) -> std::result::Result<StockInfo, ParseWarningKind> {
+    if row_split.len() < header.len() {
+        return Err(ParseWarningKind::ShortRow);
+    }
+    let mut stock_info = StockInfo::new();
+    let malformed = |value: &String| ParseWarningKind::MalformedNumber(value.clone());
+    for (value, head) in row_split.iter().zip(header) {
+        match head.as_str() {
+            "Account Number" => {
+                stock_info.add_account(value.trim().parse::<u32>().map_err(|_| malformed(value))?)
+            }
+            "Symbol" => {
+                if value.chars().count() > 1 {
+                    stock_info.add_symbol(registry.stock_symbol(value))
+                } else {
+                    break;
+                }
+            }
+            "Shares" => stock_info.add_shares(parse_money(value).map_err(|_| malformed(value))?),
+            "Share Price" => {
+                stock_info.add_share_price(parse_money(value).map_err(|_| malformed(value))?)
+            }
+            "Total Value" => {
+                stock_info.add_total_value(parse_money(value).map_err(|_| malformed(value))?)
+            }
+            _ => continue,
+        }
+    }
+    Ok(stock_info)
+}
+
+// Transaction from a row of the transaction section of the Vanguard download.  None is returned
+// when a value needed for the Transaction is not within the row.
+fn parse_transaction_row(
+    row_split: &[String],
+    transaction_header: &[String],
+    registry: &SymbolRegistry,
+) -> std::result::Result<Option<Transaction>, ParseWarningKind> {
+    if row_split.len() < transaction_header.len() {
+        return Err(ParseWarningKind::ShortRow);
+    }
+    let mut account_num_option = None;
+    let mut trade_date_option = None;
+    let mut symbol_option = None;
+    let mut shares_option = None;
+    let mut net_amount_option = None;
+    let mut transaction_type_option = None;
+    let malformed = |value: &String| ParseWarningKind::MalformedNumber(value.clone());
+    for (value, head) in row_split.iter().zip(transaction_header) {
+        match head.as_str() {
+            "Account Number" => {
+                account_num_option =
+                    Some(value.trim().parse::<u32>().map_err(|_| malformed(value))?)
+            }
+            "Symbol" => symbol_option = Some(registry.stock_symbol(value)),
+            "Shares" => {
+                shares_option = Some(parse_money(value).map_err(|_| malformed(value))? as f32)
+            }
+            "Trade Date" => {
+                trade_date_option = Some(
+                    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+                        .map_err(|_| ParseWarningKind::UnparseableDate(value.clone()))?,
+                )
+            }
+            "Net Amount" => {
+                net_amount_option = Some(parse_money(value).map_err(|_| malformed(value))? as f32)
+            }
+            "Transaction Type" => transaction_type_option = Some(TransactionType::new(value)),
+            _ => continue,
+        }
+    }
+    if let (
+        Some(account_number),
+        Some(symbol),
+        Some(shares),
+        Some(trade_date),
+        Some(net_amount),
+        Some(transaction_type),
+    ) = (
+        account_num_option,
+        symbol_option,
+        shares_option,
+        trade_date_option,
+        net_amount_option,
+        transaction_type_option,
+    ) {
+        Ok(Some(Transaction {
+            account_number,
+            symbol,
+            shares,
+            trade_date,
+            net_amount,
+            transaction_type,
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// parse_money parses a number from the Vanguard download, which may be formatted with a dollar
+/// sign and thousands separators.  Negative amounts in parentheses are also accepted.  The number
+/// is parsed as f64 so that large values keep their cents.
+///
+/// # Example
+///
+/// ```
+/// use vapore_gui::holdings::parse_money;
+///
+/// assert_eq!(parse_money(" $12,345.67 ").unwrap(), 12345.67);
+/// assert_eq!(parse_money("1,234.5").unwrap(), 1234.5);
+/// assert_eq!(parse_money("(500.00)").unwrap(), -500.0);
+/// assert_eq!(parse_money("-$75.25").unwrap(), -75.25);
+/// assert!(parse_money("N/A").is_err());
+/// assert_eq!(parse_money("$16,777,217.01").unwrap(), 16777217.01);
+/// ```
+pub fn parse_money(value: &str) -> Result<f64> {
+    let trimmed = value.trim();
+    let (negative, amount) = match trimmed
+        .strip_prefix('(')
+        .and_then(|amount| amount.strip_suffix(')'))
+    {
+        Some(amount) => (true, amount),
+        None => (false, trimmed),
+    };
+    let cleaned = amount.replace(['$', ','], "");
+    let parsed = cleaned
+        .trim()
+        .parse::<f64>()
+        .with_context(|| format!("Unable to parse {:?} as a number", value))?;
+    Ok(if negative { -parsed } else { parsed })
+}
+
 /// parse_csv_download takes in the file path of the downloaded file from Vanguard and parses it
 /// into VanguardHoldings.  The VanguardHoldings is a struct which holds the values of what is
-/// contained within the vangaurd account along with quotes for each of the ETFs
-pub async fn parse_csv_download(csv_string: String) -> Result<VanguardHoldings> {
+/// contained within the vangaurd account along with quotes for each of the ETFs.  Column headers
+/// renamed within newer or older Vanguard downloads, such as "Market Value" for "Total Value", are
+/// also recognized.  Values containing commas are expected to be double quoted, as is standard for
+/// CSV files.  Rows which can not be parsed are skipped, and a ParseWarning with the line number
+/// and reason is returned for each of them alongside the VanguardHoldings.  Only the file is read,
+/// so quotes which are not within the file are left as the 1.0 placeholder for
+/// VanguardHoldings::add_placeholder_quotes to retrieve.
+///
+/// # Example
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use vapore_gui::holdings::{
+///     parse_csv_download, ParseWarning, ParseWarningKind, StockSymbol, Transaction, TransactionType,
+/// };
+///
+/// let mut csv = "Account,Investment Name,Fund Symbol,Quantity,Price,Market Value,\n".to_string();
+/// for symbol in StockSymbol::list() {
+///     csv += &format!("12345,Vanguard ETF,{:?},10,50.0,500.0,\n", symbol);
+/// }
+/// csv += "\nAccount,Transaction Date,Settlement Date,Activity Type,Fund Symbol,Quantity,Amount,\n";
+/// csv += "12345,2023-03-01,2023-03-03,Buy,VV,10,-500.0,\n";
+///
+/// let (vanguard_holdings, _) = parse_csv_download(csv).unwrap();
+/// let account = vanguard_holdings.accounts_values[&12345];
+/// assert_eq!(account.stock_value(StockSymbol::VV).unwrap(), 500.0);
+/// assert_eq!(vanguard_holdings.accounts_shares[&12345].stock_value(StockSymbol::BND).unwrap(), 10.0);
+/// assert_eq!(vanguard_holdings.stock_quotes().stock_value(StockSymbol::VXUS).unwrap(), 50.0);
+/// assert_eq!(vanguard_holdings.transactions().len(), 1);
+///
+/// // Quoted values with commas do not shift the columns which follow
+/// let mut csv = "Account Number,Investment Name,Symbol,Shares,Share Price,Total Value,\n".to_string();
+/// for symbol in StockSymbol::list() {
+///     csv += &format!("12345,\"Vanguard Fund, Admiral \"\"ETF\"\"\",{:?},10,50.0,500.0,\n", symbol);
+/// }
+/// let (vanguard_holdings, _) = parse_csv_download(csv).unwrap();
+/// assert_eq!(vanguard_holdings.accounts_values[&12345].stock_value(StockSymbol::BND).unwrap(), 500.0);
+/// assert_eq!(vanguard_holdings.stock_quotes().stock_value(StockSymbol::BND).unwrap(), 50.0);
+///
+/// // Dollar signs, thousands separators, and negatives in parentheses are accepted
+/// let mut csv = "Account Number,Investment Name,Symbol,Shares,Share Price,Total Value,\n".to_string();
+/// for symbol in StockSymbol::list() {
+///     csv += &format!("12345,Vanguard ETF,{:?},\"1,234.5\",$10.00,\"$12,345.00\",\n", symbol);
+/// }
+/// csv += "\nAccount Number,Trade Date,Settlement Date,Transaction Type,Symbol,Shares,Net Amount,\n";
+/// csv += "12345,2023-03-01,2023-03-03,Buy,VV,10,($500.00),\n";
+/// let (vanguard_holdings, warnings) = parse_csv_download(csv).unwrap();
+/// assert_eq!(vanguard_holdings.accounts_shares[&12345].stock_value(StockSymbol::VV).unwrap(), 1234.5);
+/// assert_eq!(vanguard_holdings.accounts_values[&12345].stock_value(StockSymbol::VV).unwrap(), 12345.0);
+/// let buy = Transaction::new(
+///     12345,
+///     NaiveDate::from_ymd_opt(2023, 3, 1).unwrap(),
+///     StockSymbol::VV,
+///     10.0,
+///     -500.0,
+///     TransactionType::Buy,
+/// );
+/// assert_eq!(vanguard_holdings.transactions()[0], buy);
+/// assert!(warnings.is_empty());
+///
+/// // Rows which can not be parsed are skipped with a warning
+/// let mut csv = "Account Number,Investment Name,Symbol,Shares,Share Price,Total Value,\n".to_string();
+/// for symbol in StockSymbol::list() {
+///     csv += &format!("12345,Vanguard ETF,{:?},10,50.0,500.0,\n", symbol);
+/// }
+/// csv += "12345,Vanguard ETF,VTI,ten,50.0,500.0,\n";
+/// csv += "12345,Vanguard ETF,VTI\n";
+/// csv += "\nAccount Number,Trade Date,Settlement Date,Transaction Type,Symbol,Shares,Net Amount,\n";
+/// csv += "12345,03/01/2023,2023-03-03,Buy,VV,10,-500.0,\n";
+/// csv += "12345,2023-03-01,2023-03-03,Buy,VV\n";
+/// csv += "12345,2023-03-01\n";
+/// let (vanguard_holdings, warnings) = parse_csv_download(csv).unwrap();
+/// assert!(vanguard_holdings.transactions().is_empty());
+/// assert_eq!(
+///     warnings,
+///     vec![
+///         ParseWarning { line: 11, kind: ParseWarningKind::MalformedNumber("ten".to_string()) },
+///         ParseWarning { line: 12, kind: ParseWarningKind::ShortRow },
+///         ParseWarning { line: 15, kind: ParseWarningKind::UnparseableDate("03/01/2023".to_string()) },
+///         ParseWarning { line: 16, kind: ParseWarningKind::ShortRow },
+///         ParseWarning { line: 17, kind: ParseWarningKind::ShortRow },
+///     ]
+/// );
+/// assert_eq!(warnings[3].to_string(), "Line 16: row has fewer values than the header");
+/// ```
+pub fn parse_csv_download(csv_string: String) -> Result<(VanguardHoldings, Vec<ParseWarning>)> {
+    parse_csv_download_with(csv_string, &SymbolRegistry::new())
+}
+
+/// parse_csv_download_with parses the Vanguard download the same as parse_csv_download, where the
+/// stock symbols within the registry are parsed as StockSymbol::Registered.  The registry is kept
+/// with the VanguardHoldings so that the quotes of the registered symbols are retrieved along with
+/// the supported ETFs.
+///
+/// # Example
+///
+/// ```
+/// use futures::executor::block_on;
+/// use vapore_gui::{asset::AssetClass, holdings};
+///
+/// let mut registry = holdings::SymbolRegistry::new();
+/// let vti = registry.register_symbol("VTI", AssetClass::UsStockLarge).unwrap();
+/// let schb = registry.register_symbol("SCHB", AssetClass::UsStockLarge).unwrap();
+///
+/// let mut csv = "Account Number,Investment Name,Symbol,Shares,Share Price,Total Value,\n".to_string();
+/// csv += "12345,Vanguard Total Stock,VTI,10,1.0,2000.0,\n";
+/// csv += "12345,Schwab Broad Market,SCHB,10,1.0,500.0,\n";
+/// csv += "12345,Vanguard Total Bond,BND,10,75.0,750.0,\n";
+/// let (mut vanguard_holdings, _) = holdings::parse_csv_download_with(csv, &registry).unwrap();
+/// let account = vanguard_holdings.accounts_values[&12345];
+/// assert_eq!(account.stock_value(vti.clone()).unwrap(), 2000.0);
+/// assert_eq!(account.stock_value(schb.clone()).unwrap(), 500.0);
+///
+/// // The placeholder quotes of the registered symbols are retrieved
+/// let provider = holdings::StaticProvider::new(
+///     holdings::StockSymbol::list()
+///         .into_iter()
+///         .map(|stock_symbol| (stock_symbol, 100.0))
+///         .chain([(vti.clone(), 200.0), (schb.clone(), 50.0)])
+///         .collect(),
+/// );
+/// block_on(vanguard_holdings.add_placeholder_quotes(&provider)).unwrap();
+/// assert_eq!(vanguard_holdings.stock_quotes().stock_value(vti).unwrap(), 200.0);
+/// assert_eq!(vanguard_holdings.stock_quotes().stock_value(schb).unwrap(), 50.0);
+/// assert_eq!(vanguard_holdings.stock_quotes().stock_value(holdings::StockSymbol::BND).unwrap(), 75.0);
+/// ```
+pub fn parse_csv_download_with(
+    csv_string: String,
+    registry: &SymbolRegistry,
+) -> Result<(VanguardHoldings, Vec<ParseWarning>)> {
     let mut header = Vec::new();
     let mut transaction_header = Vec::new();
     let mut accounts_values: HashMap<u32, ShareValues> = HashMap::new();
     let mut accounts_shares: HashMap<u32, ShareValues> = HashMap::new();
     let mut quotes = ShareValues::new_quote();
 
+    let mut other_values: HashMap<u32, HashMap<String, f64>> = HashMap::new();
+
     let mut holdings_row = true;
     let mut transactions = Vec::new();
+    let mut warnings = Vec::new();
 
     // iterate through all of the rows of the vanguard downlaoaded file and add the information to
     // StockInfo structs, which then are aggregated into the accounts hashmap where the account
-    // number is the key
-    for row in csv_string.split('\n') {
+    // number is the key.  Rows which can not be parsed are skipped and added to the warnings.
+    for (line_index, row) in csv_string.split('\n').enumerate() {
+        let line = line_index + 1;
         if row.contains(',') {
-            if row.contains("Trade Date") {
+            let row_split = split_csv_row(row);
+            if row_split
+                .iter()
+                .any(|head| canonical_header(head) == "Trade Date")
+            {
                 holdings_row = false;
             }
-            let row_split = row
-                .split(',')
-                .map(|value| value.to_string())
-                .collect::<Vec<String>>();
             if row_split.len() > 4 {
                 if holdings_row {
-                    let mut stock_info = StockInfo::new();
                     if header.is_empty() {
                         header = row_split
+                            .iter()
+                            .map(|head| canonical_header(head).to_string())
+                            .collect();
+                        warnings.extend(missing_headers(&header, &HOLDINGS_HEADERS, line));
                     } else {
-                        for (value, head) in row_split.iter().zip(&header) {
-                            match head.as_str() {
-                                "Account Number" => stock_info.add_account(value.parse::<u32>()?),
-                                "Symbol" => {
-                                    if value.chars().count() > 1 {
-                                        stock_info.add_symbol(StockSymbol::new(value))
-                                    } else {
-                                        break;
-                                    }
+                        match parse_holdings_row(&row_split, &header, registry) {
+                            Ok(stock_info) => {
+                                if stock_info.finished() {
+                                    let account_value = accounts_values
+                                        .entry(stock_info.account_number)
+                                        .or_insert_with(ShareValues::new);
+                                    account_value.add_stockinfo_value(
+                                        stock_info.clone(),
+                                        AddType::HoldingValue,
+                                    )?;
+                                    let account_shares = accounts_shares
+                                        .entry(stock_info.account_number)
+                                        .or_insert_with(ShareValues::new);
+                                    account_shares.add_stockinfo_value(
+                                        stock_info.clone(),
+                                        AddType::HoldingShares,
+                                    )?;
+                                    quotes.add_stockinfo_value(
+                                        stock_info.clone(),
+                                        AddType::StockPrice,
+                                    )?;
+                                    add_other_value(&mut other_values, &stock_info);
                                 }
-                                "Shares" => stock_info.add_shares(value.parse::<f32>()?),
-                                "Share Price" => stock_info.add_share_price(value.parse::<f32>()?),
-                                "Total Value" => stock_info.add_total_value(value.parse::<f32>()?),
-                                _ => continue,
                             }
-                        }
-                        if stock_info.finished() {
-                            let account_value = accounts_values
-                                .entry(stock_info.account_number)
-                                .or_insert_with(ShareValues::new);
-                            account_value
-                                .add_stockinfo_value(stock_info.clone(), AddType::HoldingValue);
-                            let account_shares = accounts_shares
-                                .entry(stock_info.account_number)
-                                .or_insert_with(ShareValues::new);
-                            account_shares
-                                .add_stockinfo_value(stock_info.clone(), AddType::HoldingShares);
-                            quotes.add_stockinfo_value(stock_info.clone(), AddType::StockPrice);
+                            Err(kind) => warnings.push(ParseWarning { line, kind }),
                         }
                     }
                 } else if transaction_header.is_empty() {
                     transaction_header = row_split
+                        .iter()
+                        .map(|head| canonical_header(head).to_string())
+                        .collect();
+                    warnings.extend(missing_headers(
+                        &transaction_header,
+                        &TRANSACTION_HEADERS,
+                        line,
+                    ));
                 } else {
-                    let mut account_num_option = None;
-                    let mut trade_date_option = None;
-                    let mut symbol_option = None;
-                    let mut shares_option = None;
-                    let mut net_amount_option = None;
-                    let mut transaction_type_option = None;
-                    for (value, head) in row_split.iter().zip(&transaction_header) {
-                        match head.as_str() {
-                            "Account Number" => account_num_option = Some(value.parse::<u32>()?),
-                            "Symbol" => symbol_option = Some(StockSymbol::new(value)),
-                            "Shares" => shares_option = Some(value.parse::<f32>()?),
-                            "Trade Date" => {
-                                trade_date_option =
-                                    Some(NaiveDate::parse_from_str(value, "%Y-%m-%d")?)
-                            }
-                            "Net Amount" => net_amount_option = Some(value.parse::<f32>()?),
-                            "Transaction Type" => {
-                                transaction_type_option = Some(TransactionType::new(value))
-                            }
-                            _ => continue,
-                        }
-                    }
-                    if let Some(account_number) = account_num_option {
-                        if let Some(symbol) = symbol_option {
-                            if let Some(shares) = shares_option {
-                                if let Some(trade_date) = trade_date_option {
-                                    if let Some(net_amount) = net_amount_option {
-                                        if let Some(transaction_type) = transaction_type_option {
-                                            transactions.push(Transaction {
-                                                account_number,
-                                                symbol,
-                                                shares,
-                                                trade_date,
-                                                net_amount,
-                                                transaction_type,
-                                            })
-                                        }
-                                    }
-                                }
-                            }
-                        }
+                    match parse_transaction_row(&row_split, &transaction_header, registry) {
+                        Ok(Some(transaction)) => transactions.push(transaction),
+                        Ok(None) => continue,
+                        Err(kind) => warnings.push(ParseWarning { line, kind }),
                     }
                 }
+            } else if row_split.iter().any(|value| !value.trim().is_empty()) {
+                // Rows too short to be a header are skipped with a warning once the header of
+                // their section has been found, while rows before the header are left out
+                let section_header = if holdings_row {
+                    &header
+                } else {
+                    &transaction_header
+                };
+                if !section_header.is_empty() {
+                    warnings.push(ParseWarning {
+                        line,
+                        kind: ParseWarningKind::ShortRow,
+                    });
+                }
             }
         }
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    quotes.add_missing_quotes().await?;
+    let vanguard_holdings = VanguardHoldings {
+        accounts_values,
+        accounts_shares,
+        quotes,
+        transactions,
+        distributions: HashMap::new(),
+        quote_timestamps: HashMap::new(),
+        parsed_at: Utc::now(),
+        other_values,
+        other_classifications: HashMap::new(),
+        registry: registry.clone(),
+    };
+    Ok((vanguard_holdings, warnings))
+}
+
+// Adds the value of a holding of an unsupported stock symbol to the other values of its account,
+// which keeps each of the symbols apart for classify_other_symbols
+fn add_other_value(other_values: &mut HashMap<u32, HashMap<String, f64>>, stock_info: &StockInfo) {
+    if let StockSymbol::Other(symbol) = &stock_info.symbol {
+        *other_values
+            .entry(stock_info.account_number)
+            .or_default()
+            .entry(symbol.clone())
+            .or_insert(0.0) += stock_info.total_value;
+    }
+}
+
+// Elements of an OFX/QFX document as (line, tag, value) in document order, where the line is the
+// line number of the opening '<'.  Closing tags keep their leading '/' and an empty value.  OFX
+// version 1 files, which do not close the leaf elements, and XML based version 2 files are both
+// handled.
+fn ofx_elements(ofx_string: &str) -> Vec<(usize, String, String)> {
+    let mut line = 1;
+    let mut elements = Vec::new();
+    for (index, element) in ofx_string.split('<').enumerate() {
+        if index > 0 {
+            if let Some((tag, value)) = element.split_once('>') {
+                elements.push((line, tag.trim().to_uppercase(), value.trim().to_string()));
+            }
+        }
+        line += element.matches('\n').count();
+    }
+    elements
+}
 
-    Ok(VanguardHoldings {
+// Number within an OFX element, where a value which is not a number adds a MalformedNumber
+// warning for the line and returns None
+fn ofx_number<T: std::str::FromStr>(
+    value: &str,
+    line: usize,
+    warnings: &mut Vec<ParseWarning>,
+) -> Option<T> {
+    let number = value.parse::<T>().ok();
+    if number.is_none() {
+        warnings.push(ParseWarning {
+            line,
+            kind: ParseWarningKind::MalformedNumber(value.to_string()),
+        })
+    }
+    number
+}
+
+/// parse_ofx_download takes in the contents of an OFX or QFX file downloaded from Vanguard and
+/// parses it into VanguardHoldings in the same way as parse_csv_download.  Positions are read from
+/// the INVPOSLIST section and transactions from the INVTRANLIST section.  Securities are matched
+/// to a StockSymbol through the ticker within the SECLIST section, falling back on the UNIQUEID
+/// when no ticker is listed.  Elements which can not be parsed, such as an account number which
+/// is not a number, are skipped and returned as ParseWarnings, where the positions and
+/// transactions of a skipped account are left out.  A position or transaction with a trade date,
+/// units, unit price, market value, or total which can not be parsed is left out on its own.
+///
+/// # Example
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use vapore_gui::holdings::{
+///     parse_ofx_download, ParseWarning, ParseWarningKind, StockSymbol, Transaction,
+///     TransactionType,
+/// };
+///
+/// let mut ofx = "<OFX><INVSTMTMSGSRSV1><INVSTMTTRNRS><INVSTMTRS>".to_string();
+/// ofx += "<INVACCTFROM><BROKERID>vanguard.com<ACCTID>12345</INVACCTFROM>";
+/// ofx += "<INVTRANLIST><BUYMF><INVBUY><INVTRAN><FITID>1<DTTRADE>20230301120000.000</INVTRAN>";
+/// ofx += "<SECID><UNIQUEID>921937835<UNIQUEIDTYPE>CUSIP</SECID><UNITS>10<UNITPRICE>50.0";
+/// ofx += "<TOTAL>-500.0</INVBUY></BUYMF></INVTRANLIST><INVPOSLIST>";
+/// for symbol in StockSymbol::list() {
+///     ofx += &format!(
+///         "<POSMF><INVPOS><SECID><UNIQUEID>{:?}</SECID><UNITS>10<UNITPRICE>50.0<MKTVAL>500.0</INVPOS></POSMF>",
+///         symbol
+///     );
+/// }
+/// ofx += "</INVPOSLIST></INVSTMTRS></INVSTMTTRNRS></INVSTMTMSGSRSV1>";
+/// ofx += "<SECLISTMSGSRSV1><SECLIST><MFINFO><SECINFO><SECID><UNIQUEID>921937835";
+/// ofx += "<UNIQUEIDTYPE>CUSIP</SECID><SECNAME>Vanguard Total Bond Market ETF<TICKER>BND";
+/// ofx += "</SECINFO></MFINFO></SECLIST></SECLISTMSGSRSV1></OFX>";
+///
+/// let (vanguard_holdings, warnings) = parse_ofx_download(ofx.clone()).unwrap();
+/// assert!(warnings.is_empty());
+/// let account = vanguard_holdings.accounts_values[&12345];
+/// assert_eq!(account.stock_value(StockSymbol::VV).unwrap(), 500.0);
+/// assert_eq!(vanguard_holdings.accounts_shares[&12345].stock_value(StockSymbol::BND).unwrap(), 10.0);
+/// assert_eq!(vanguard_holdings.stock_quotes().stock_value(StockSymbol::VXUS).unwrap(), 50.0);
+/// assert_eq!(vanguard_holdings.transactions().len(), 1);
+/// let buy = Transaction::new(
+///     12345,
+///     NaiveDate::from_ymd_opt(2023, 3, 1).unwrap(),
+///     StockSymbol::BND,
+///     10.0,
+///     -500.0,
+///     TransactionType::Buy,
+/// );
+/// assert_eq!(vanguard_holdings.transactions()[0], buy);
+///
+/// // Dividends which are reinvested keep the reinvestment type
+/// let reinvest = ofx.replace(
+///     "</INVTRANLIST>",
+///     "<REINVEST><INVTRAN><FITID>2<DTTRADE>20230601</INVTRAN><SECID><UNIQUEID>VV</SECID>\
+///     <INCOMETYPE>DIV<TOTAL>-20.0<UNITS>0.1</REINVEST></INVTRANLIST>",
+/// );
+/// let (vanguard_holdings, _) = parse_ofx_download(reinvest).unwrap();
+/// let reinvestment = Transaction::new(
+///     12345,
+///     NaiveDate::from_ymd_opt(2023, 6, 1).unwrap(),
+///     StockSymbol::VV,
+///     0.1,
+///     -20.0,
+///     TransactionType::Reinvestment,
+/// );
+/// assert_eq!(vanguard_holdings.transactions()[1], reinvestment);
+///
+/// // Only the position and the transaction with a malformed element are skipped
+/// let malformed = ofx
+///     .replacen("<UNITS>10<UNITPRICE>50.0<MKTVAL>", "<UNITS>ten<UNITPRICE>50.0<MKTVAL>", 1)
+///     .replace("<DTTRADE>20230301120000.000", "<DTTRADE>March");
+/// let (vanguard_holdings, warnings) = parse_ofx_download(malformed).unwrap();
+/// assert_eq!(vanguard_holdings.accounts_values[&12345].stock_value(StockSymbol::VV).unwrap(), 0.0);
+/// assert_eq!(vanguard_holdings.accounts_values[&12345].stock_value(StockSymbol::VO).unwrap(), 500.0);
+/// assert!(vanguard_holdings.transactions().is_empty());
+/// assert_eq!(
+///     warnings,
+///     vec![
+///         ParseWarning { line: 1, kind: ParseWarningKind::UnparseableDate("March".to_string()) },
+///         ParseWarning { line: 1, kind: ParseWarningKind::MalformedNumber("ten".to_string()) },
+///     ]
+/// );
+///
+/// // Accounts which are not a number are skipped with a warning
+/// let ofx = ofx.replace("<ACCTID>12345", "\n<ACCTID>X1234");
+/// let (vanguard_holdings, warnings) = parse_ofx_download(ofx).unwrap();
+/// assert!(vanguard_holdings.accounts_values.is_empty());
+/// assert!(vanguard_holdings.transactions().is_empty());
+/// assert_eq!(
+///     warnings,
+///     vec![ParseWarning { line: 2, kind: ParseWarningKind::MalformedNumber("X1234".to_string()) }]
+/// );
+/// ```
+pub fn parse_ofx_download(ofx_string: String) -> Result<(VanguardHoldings, Vec<ParseWarning>)> {
+    parse_ofx_download_with(ofx_string, &SymbolRegistry::new())
+}
+
+/// parse_ofx_download_with parses the OFX or QFX download the same as parse_ofx_download, where
+/// the stock symbols within the registry are parsed as StockSymbol::Registered, the same as
+/// parse_csv_download_with.
+pub fn parse_ofx_download_with(
+    ofx_string: String,
+    registry: &SymbolRegistry,
+) -> Result<(VanguardHoldings, Vec<ParseWarning>)> {
+    let elements = ofx_elements(&ofx_string);
+    let mut warnings = Vec::new();
+    let mut accounts_values: HashMap<u32, ShareValues> = HashMap::new();
+    let mut accounts_shares: HashMap<u32, ShareValues> = HashMap::new();
+    let mut quotes = ShareValues::new_quote();
+    let mut other_values: HashMap<u32, HashMap<String, f64>> = HashMap::new();
+    let mut transactions = Vec::new();
+
+    // securities are referenced by their UNIQUEID, often a CUSIP, within positions and
+    // transactions, so first collect the ticker for each of the UNIQUEIDs from the SECLIST
+    let mut tickers: HashMap<String, String> = HashMap::new();
+    let mut unique_id_option = None;
+    for (_, tag, value) in &elements {
+        match tag.as_str() {
+            "SECINFO" => unique_id_option = None,
+            "UNIQUEID" => unique_id_option = Some(value.clone()),
+            "TICKER" => {
+                if let Some(unique_id) = unique_id_option.take() {
+                    tickers.insert(unique_id, value.clone());
+                }
+            }
+            _ => continue,
+        }
+    }
+    let symbol = |unique_id: &str| {
+        registry.stock_symbol(
+            tickers
+                .get(unique_id)
+                .map(|ticker| ticker.as_str())
+                .unwrap_or(unique_id),
+        )
+    };
+
+    // iterate through the elements and build a StockInfo for each of the positions and a
+    // Transaction for each of the investment transactions of the current account.  A position or
+    // transaction with an element that can not be parsed is malformed and left out.
+    let mut account_num_option = None;
+    let mut position_option: Option<StockInfo> = None;
+    let mut transaction_type_option = None;
+    let mut trade_date_option = None;
+    let mut symbol_option = None;
+    let mut shares_option = None;
+    let mut net_amount_option = None;
+    let mut malformed = false;
+    for (line, tag, value) in &elements {
+        match tag.as_str() {
+            "ACCTID" => {
+                account_num_option = value.parse::<u32>().ok();
+                if account_num_option.is_none() {
+                    warnings.push(ParseWarning {
+                        line: *line,
+                        kind: ParseWarningKind::MalformedNumber(value.clone()),
+                    })
+                }
+            }
+            "POSMF" | "POSSTOCK" | "POSDEBT" | "POSOTHER" => {
+                let mut stock_info = StockInfo::new();
+                if let Some(account_number) = account_num_option {
+                    stock_info.add_account(account_number)
+                }
+                position_option = Some(stock_info);
+                malformed = false;
+            }
+            "/POSMF" | "/POSSTOCK" | "/POSDEBT" | "/POSOTHER" => {
+                if let Some(stock_info) = position_option.take() {
+                    if stock_info.finished() && !malformed {
+                        let account_value = accounts_values
+                            .entry(stock_info.account_number)
+                            .or_insert_with(ShareValues::new);
+                        account_value
+                            .add_stockinfo_value(stock_info.clone(), AddType::HoldingValue)?;
+                        let account_shares = accounts_shares
+                            .entry(stock_info.account_number)
+                            .or_insert_with(ShareValues::new);
+                        account_shares
+                            .add_stockinfo_value(stock_info.clone(), AddType::HoldingShares)?;
+                        quotes.add_stockinfo_value(stock_info.clone(), AddType::StockPrice)?;
+                        add_other_value(&mut other_values, &stock_info);
+                    }
+                }
+                malformed = false;
+            }
+            "BUYMF" | "BUYSTOCK" | "BUYDEBT" | "BUYOTHER" => {
+                transaction_type_option = Some(TransactionType::Buy);
+                malformed = false;
+            }
+            "SELLMF" | "SELLSTOCK" | "SELLDEBT" | "SELLOTHER" => {
+                transaction_type_option = Some(TransactionType::Sell);
+                malformed = false;
+            }
+            "REINVEST" => {
+                transaction_type_option = Some(TransactionType::Reinvestment);
+                malformed = false;
+            }
+            "INCOME" => {
+                transaction_type_option = Some(TransactionType::Other(tag.clone()));
+                malformed = false;
+            }
+            // Reinvestments list the type of income reinvested, which keeps the reinvestment type
+            "INCOMETYPE" if transaction_type_option != Some(TransactionType::Reinvestment) => {
+                transaction_type_option = match value.as_str() {
+                    "DIV" => Some(TransactionType::Dividend),
+                    _ => Some(TransactionType::Other(value.clone())),
+                }
+            }
+            "DTTRADE" => {
+                match NaiveDate::parse_from_str(value.get(..8).unwrap_or(value), "%Y%m%d") {
+                    Ok(trade_date) => trade_date_option = Some(trade_date),
+                    Err(_) => {
+                        warnings.push(ParseWarning {
+                            line: *line,
+                            kind: ParseWarningKind::UnparseableDate(value.clone()),
+                        });
+                        malformed = true;
+                    }
+                }
+            }
+            "UNIQUEID" => {
+                if let Some(stock_info) = position_option.as_mut() {
+                    stock_info.add_symbol(symbol(value))
+                } else {
+                    symbol_option = Some(symbol(value))
+                }
+            }
+            "UNITS" => {
+                if let Some(stock_info) = position_option.as_mut() {
+                    match ofx_number(value, *line, &mut warnings) {
+                        Some(shares) => stock_info.add_shares(shares),
+                        None => malformed = true,
+                    }
+                } else {
+                    shares_option = ofx_number(value, *line, &mut warnings);
+                    malformed |= shares_option.is_none();
+                }
+            }
+            "UNITPRICE" => {
+                if let Some(stock_info) = position_option.as_mut() {
+                    match ofx_number(value, *line, &mut warnings) {
+                        Some(share_price) => stock_info.add_share_price(share_price),
+                        None => malformed = true,
+                    }
+                }
+            }
+            "MKTVAL" => {
+                if let Some(stock_info) = position_option.as_mut() {
+                    match ofx_number(value, *line, &mut warnings) {
+                        Some(total_value) => stock_info.add_total_value(total_value),
+                        None => malformed = true,
+                    }
+                }
+            }
+            "TOTAL" => {
+                net_amount_option = ofx_number(value, *line, &mut warnings);
+                malformed |= net_amount_option.is_none();
+            }
+            "/BUYMF" | "/BUYSTOCK" | "/BUYDEBT" | "/BUYOTHER" | "/SELLMF" | "/SELLSTOCK"
+            | "/SELLDEBT" | "/SELLOTHER" | "/REINVEST" | "/INCOME" => {
+                // The pending values are cleared even when the transaction is left out, so that
+                // they are not carried into the next transaction
+                let pending = (
+                    account_num_option,
+                    transaction_type_option.take(),
+                    trade_date_option.take(),
+                    symbol_option.take(),
+                    net_amount_option.take(),
+                );
+                let shares = shares_option.take().unwrap_or(0.0);
+                if let (
+                    Some(account_number),
+                    Some(transaction_type),
+                    Some(trade_date),
+                    Some(symbol),
+                    Some(net_amount),
+                ) = pending
+                {
+                    if !malformed {
+                        transactions.push(Transaction {
+                            account_number,
+                            symbol,
+                            shares,
+                            trade_date,
+                            net_amount,
+                            transaction_type,
+                        })
+                    }
+                }
+                malformed = false;
+            }
+            _ => continue,
+        }
+    }
+
+    let vanguard_holdings = VanguardHoldings {
         accounts_values,
         accounts_shares,
         quotes,
         transactions,
         distributions: HashMap::new(),
-    })
+        quote_timestamps: HashMap::new(),
+        parsed_at: Utc::now(),
+        other_values,
+        other_classifications: HashMap::new(),
+        registry: registry.clone(),
+    };
+    Ok((vanguard_holdings, warnings))
 }